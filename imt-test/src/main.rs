@@ -10,7 +10,7 @@ use parking_lot::Mutex;
 
 pub const TEXT_HEIGHT: f32 = 32.0;
 pub const TEXT: &'static str = "Sphinx of black quartz, judge my vow.";
-pub const VARIATION_INSTANCE: usize = 3;
+pub const VARIATION_INSTANCE: &'static str = "Regular";
 
 /* Axes:
   0: 'wght', Min: 100, Default: 400, Max: 1000
@@ -60,12 +60,12 @@ fn main() {
                 start.elapsed().as_micros() as f32 / 1000.0
             );
 
-            let coords = font.fvar_table().unwrap().instances[VARIATION_INSTANCE]
-                .coordinates
-                .clone();
-            let mut norm_coords = coords.clone();
-
-            imt::util::variation::normalize_axis_coords(&font, &mut norm_coords).unwrap();
+            let coords = font
+                .coords_for_instance_name(VARIATION_INSTANCE, false)
+                .unwrap();
+            let norm_coords = font
+                .coords_for_instance_name(VARIATION_INSTANCE, true)
+                .unwrap();
 
             let rasterizer = GpuRasterizer::new(basalt.compute_queue());
 
@@ -311,7 +311,7 @@ fn render_line<T: AsRef<str>>(
             border_size_r: Some(1.0),
             height: Some(rastered.height as f32),
             width: Some(rastered.width as f32),
-            back_image_raw: Some(imt_image_to_bst(rastered.bitmap)),
+            back_image_raw: Some(imt_image_to_bst(rastered.image_view(rasterizer).unwrap())),
             ..BinStyle::default()
         })
         .debug();
@@ -1,9 +1,20 @@
+use core::fmt;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ImtError {
     pub kind: ImtErrorKind,
     pub source: ImtErrorSource,
 }
 
+impl fmt::Display for ImtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in {:?}", self.kind, self.source)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ImtError {}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ImtErrorKind {
     UnexpectedTag,
@@ -17,6 +28,22 @@ pub enum ImtErrorKind {
     MissingTable,
 }
 
+impl fmt::Display for ImtErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::UnexpectedTag => "unexpected tag",
+            Self::Truncated => "truncated data",
+            Self::CFFNotSupported => "CFF outlines are not supported",
+            Self::InvalidSfntVersion => "invalid sfnt version",
+            Self::FormatNotSupported => "unsupported format",
+            Self::Malformed => "malformed data",
+            Self::UnexpectedVersion => "unexpected version",
+            Self::CollectionNotSupported => "font collections are not supported",
+            Self::MissingTable => "missing table",
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ImtErrorSource {
     TTCHeader,
@@ -39,4 +66,14 @@ pub enum ImtErrorSource {
     GvarTable,
     AvarTable,
     HvarTable,
+    Os2Table,
+    PostTable,
+    MvarTable,
+    BaseTable,
+    CvtTable,
+    FpgmTable,
+    PrepTable,
+    GsubTable,
+    GposTable,
+    GdefTable,
 }
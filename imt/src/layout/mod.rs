@@ -1,5 +1,11 @@
-use crate::parse::Outline;
+use alloc::vec::Vec;
+use core::ops::{Range, RangeInclusive};
+
+use crate::parse::{mvar_table, Font, Outline};
 use crate::raster::ScaledGlyph;
+use crate::util::float;
+pub use crate::util::unicode::is_default_ignorable;
+use crate::util::variation::metric_delta;
 
 pub mod uniform;
 
@@ -27,6 +33,9 @@ pub enum ImtHoriAlign {
     Left,
     Center,
     Right,
+    /// Lines that are wrapped are stretched to the width of the body, the last line and lines
+    /// that end the text are aligned left.
+    Justify,
 }
 
 /// Defines how text is aligned vertically
@@ -85,7 +94,7 @@ pub struct PositionedGlyph {
     pub height: u32,
     /// Outline point values will be between `0..=1` with `Y` down.
     pub outline: Option<Outline>,
-    /// An unique ID derived from glyph_id, size, and axis coordinates.
+    /// An unique ID derived from the font, glyph_id, size, and axis coordinates.
     pub unique_id: u64,
 }
 
@@ -101,3 +110,152 @@ impl PositionedGlyph {
         }
     }
 }
+
+/// Compute the pen location of each glyph along a line starting at zero.
+///
+/// The returned `Vec` has one more location than `glyphs`, the last being the end of the line.
+///
+/// # Notes
+/// - When `fractional` is `true` the unrounded `advance_w_f32` of each glyph is accumulated and
+///   only the pen location is rounded. Otherwise the rounded `advance_w` is accumulated, which
+///   may drift by up to a pixel per glyph.
+pub fn pen_positions(glyphs: &[ScaledGlyph], fractional: bool) -> Vec<i32> {
+    let mut positions = Vec::with_capacity(glyphs.len() + 1);
+    let mut pen_x = 0.0_f32;
+    let mut pen_x_whole = 0_i32;
+    positions.push(0);
+
+    for glyph in glyphs.iter() {
+        if fractional {
+            pen_x += glyph.advance_w_f32;
+            positions.push(float::round(pen_x) as i32);
+        } else {
+            pen_x_whole += glyph.advance_w as i32;
+            positions.push(pen_x_whole);
+        }
+    }
+
+    positions
+}
+
+/// Compute the sidebearings to trim from the ends of a run so its ink is placed flush.
+///
+/// Returns the amount in pixels to shift the run left so the ink of the first glyph starts at
+/// the pen location, and the amount the advance of the last glyph exceeds its ink. Subtracting
+/// both from the end of the run, see `pen_positions`, gives the width of the ink.
+///
+/// # Notes
+/// - Values are negative when ink extends beyond the advance, e.g. for italics.
+/// - Glyphs without an outline, e.g. spaces, have no ink, so a trailing space is trimmed by its
+///   whole advance.
+/// - Returns `(0.0, 0.0)` if `glyphs` is empty.
+pub fn trim_run_sidebearings(glyphs: &[ScaledGlyph]) -> (f32, f32) {
+    let (first, last) = match (glyphs.first(), glyphs.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return (0.0, 0.0),
+    };
+
+    let leading = first.bearing_x as f32;
+    let trailing = last.advance_w_f32 - (last.bearing_x as f32 + last.width as f32);
+    (leading, trailing)
+}
+
+/// Compute the baseline that visually centers a line of text within a box.
+///
+/// Returns the offset in pixels of the baseline from the top of a box of `box_height` pixels.
+/// Capital letters are centered, as centering the full ascent and descent tends to look too
+/// high. The cap height of the `OS/2` table is used, falling back to the ascender when the font
+/// does not define it.
+///
+/// # Notes
+/// - `coords` should be normalized. `MVAR` deltas are applied when `coords` is not empty.
+/// - Invalid coordinates are ignored and the default instance is used instead.
+pub fn vertical_center_baseline(font: &Font, coords: &[f32], size: f32, box_height: f32) -> f32 {
+    let cap_height = font
+        .os2_table()
+        .and_then(|os2| os2.s_cap_height)
+        .filter(|cap_height| *cap_height > 0)
+        .map(|cap_height| (cap_height, mvar_table::value_tag::CPHT));
+
+    let (value, value_tag) = match cap_height {
+        Some(some) => some,
+        None => {
+            match font.os2_table() {
+                Some(os2) => (os2.s_typo_ascender, mvar_table::value_tag::HASC),
+                None => (font.hhea_table().ascender, mvar_table::value_tag::HASC),
+            }
+        },
+    };
+
+    let mut value = value as f32;
+
+    if !coords.is_empty() {
+        value += metric_delta(font, value_tag, coords).unwrap_or(0.0);
+    }
+
+    let cap_height = (value / font.head_table().units_per_em as f32) * size;
+    (box_height + cap_height) / 2.0
+}
+
+/// Ranges of characters that extend the preceding character into one cluster.
+///
+/// These are the common blocks of combining marks along with joiners and variation selectors.
+const COMBINING_RANGES: [RangeInclusive<u32>; 26] = [
+    0x0300..=0x036F,   // Combining Diacritical Marks
+    0x0483..=0x0489,   // Cyrillic
+    0x0591..=0x05BD,   // Hebrew
+    0x05BF..=0x05BF,   // Hebrew
+    0x05C1..=0x05C2,   // Hebrew
+    0x05C4..=0x05C5,   // Hebrew
+    0x05C7..=0x05C7,   // Hebrew
+    0x0610..=0x061A,   // Arabic
+    0x064B..=0x065F,   // Arabic
+    0x0670..=0x0670,   // Arabic
+    0x06D6..=0x06DC,   // Arabic
+    0x06DF..=0x06E4,   // Arabic
+    0x06E7..=0x06E8,   // Arabic
+    0x06EA..=0x06ED,   // Arabic
+    0x0900..=0x0903,   // Devanagari
+    0x093A..=0x093C,   // Devanagari
+    0x093E..=0x094F,   // Devanagari
+    0x0951..=0x0957,   // Devanagari
+    0x1AB0..=0x1AFF,   // Combining Diacritical Marks Extended
+    0x1DC0..=0x1DFF,   // Combining Diacritical Marks Supplement
+    0x200C..=0x200D,   // Zero Width Non-Joiner & Joiner
+    0x20D0..=0x20FF,   // Combining Diacritical Marks for Symbols
+    0xFE00..=0xFE0F,   // Variation Selectors
+    0xFE20..=0xFE2F,   // Combining Half Marks
+    0x1F3FB..=0x1F3FF, // Emoji Modifiers
+    0xE0100..=0xE01EF, // Variation Selectors Supplement
+];
+
+fn is_combining(c: char) -> bool {
+    COMBINING_RANGES
+        .iter()
+        .any(|range| range.contains(&(c as u32)))
+}
+
+/// Split text into clusters that a caret should treat as a single unit.
+///
+/// Each cluster is a byte range of `text` consisting of a base character followed by any
+/// combining marks, e.g. `e` followed by U+0301 COMBINING ACUTE ACCENT.
+///
+/// # Notes
+/// - This is not a full implementation of UAX #29. Only common combining marks, joiners and
+///   variation selectors extend a cluster, so e.g. Hangul syllables and emoji sequences joined
+///   with U+200D are not merged beyond the joiner itself.
+/// - Combining marks at the start of `text` form a cluster of their own.
+pub fn grapheme_clusters(text: &str) -> Vec<Range<usize>> {
+    let mut clusters: Vec<Range<usize>> = Vec::new();
+
+    for (i, c) in text.char_indices() {
+        let end = i + c.len_utf8();
+
+        match clusters.last_mut() {
+            Some(cluster) if is_combining(c) => cluster.end = end,
+            _ => clusters.push(i..end),
+        }
+    }
+
+    clusters
+}
@@ -1,6 +1,13 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
 use crate::layout::*;
 use crate::parse::Font;
 use crate::raster::ScaledGlyph;
+use crate::util::float;
+
+/// The default of `UniformLayoutParams::max_space_stretch`.
+pub const DEFAULT_MAX_SPACE_STRETCH: f32 = 2.0;
 
 /// Output of the method `uniform_layout`.
 pub struct UniformLayout {
@@ -18,10 +25,288 @@ pub struct UniformLayoutParams<'a> {
     pub vert_behav: ImtVertBehav,
     pub vert_align: ImtVertAlign,
     pub glyphs: &'a [ScaledGlyph],
+    /// Accumulate the unrounded advance of glyphs, see `pen_positions`.
+    pub fractional_advance: bool,
+    /// The maximum multiple of its natural advance a space may be stretched to when justifying.
+    ///
+    /// Space beyond this is distributed between every glyph of the line instead, which avoids
+    /// wide gaps between words forming rivers. See `DEFAULT_MAX_SPACE_STRETCH`.
+    pub max_space_stretch: f32,
     // TODO: blocks: &'a [ImtBlock],
 }
 
 /// Layout `ScaledGlyph`'s that are from the same `Font` and share size.
-pub fn uniform_layout(_params: UniformLayoutParams) -> Vec<PositionedGlyph> {
-    todo!()
+///
+/// Glyphs are placed along lines `Font::line_height` apart, with the first baseline placed the
+/// ascender below the top of the text. The returned glyphs are in the same order as
+/// `params.glyphs`, including those without an outline.
+///
+/// Horizontal behavior:
+/// - `ImtHoriBehav::None`: Glyphs are placed along a single line that may overflow the body.
+/// - `ImtHoriBehav::Shift`: Same as `None`, but a line wider than the body is placed at the left
+///   of the body regardless of `hori_align`, so it only overflows on the right.
+/// - `ImtHoriBehav::Regular`: Lines are wrapped after the glyphs without an outline, e.g.
+///   spaces, that precede a glyph that would not fit. A word wider than the body is wrapped
+///   where it no longer fits. Glyphs without an outline at the end of a line may overflow.
+///
+/// Vertical behavior:
+/// - `ImtVertBehav::None`: The text is aligned per `vert_align` and may overflow the body.
+/// - `ImtVertBehav::Shift`: Text taller than the body is placed at the top of the body
+///   regardless of `vert_align`, so it only overflows at the bottom.
+///
+/// # Notes
+/// - When `ImtBody.width` is *zero* lines are never wrapped and alignment is relative to
+///   `ImtBody.x`, e.g. `ImtHoriAlign::Right` ends lines at `ImtBody.x`. The same applies to
+///   `ImtBody.height` & `ImtBody.y` vertically.
+/// - The width of a line is the advance of its glyphs excluding trailing glyphs without an
+///   outline. Ink extending beyond the advance, e.g. for italics, is not considered.
+/// - Vertical metrics are those of the default instance.
+pub fn uniform_layout(params: UniformLayoutParams) -> UniformLayout {
+    let UniformLayoutParams {
+        font,
+        size,
+        body,
+        hori_behav,
+        hori_align,
+        vert_behav,
+        vert_align,
+        glyphs,
+        fractional_advance,
+        max_space_stretch,
+    } = params;
+
+    let wrap_width = match hori_behav {
+        ImtHoriBehav::Regular if body.width != 0 => body.width as f32,
+        _ => f32::INFINITY,
+    };
+
+    let lines = wrap_lines(glyphs, wrap_width, fractional_advance);
+
+    let ascender = match font.os2_table() {
+        Some(os2) => os2.s_typo_ascender,
+        None => font.hhea_table().ascender,
+    };
+
+    let ascender =
+        float::round((ascender as f32 / font.head_table().units_per_em as f32) * size) as i32;
+    let line_height = float::round(font.line_height(&[], size)) as i32;
+    let text_height = line_height * lines.len() as i32;
+
+    let text_top = body.y
+        + aligned_offset(
+            body.height,
+            text_height,
+            vert_behav == ImtVertBehav::Shift,
+            match vert_align {
+                ImtVertAlign::Top => Align::Start,
+                ImtVertAlign::Center => Align::Center,
+                ImtVertAlign::Bottom => Align::End,
+            },
+        );
+
+    let mut positioned = Vec::with_capacity(glyphs.len());
+    let mut text_left = i32::MAX;
+    let mut text_right = i32::MIN;
+
+    let line_count = lines.len();
+
+    for (line_i, line) in lines.into_iter().enumerate() {
+        let line_glyphs = &glyphs[line];
+        let positions = pen_positions(line_glyphs, fractional_advance);
+        let blank_end = line_glyphs
+            .iter()
+            .rev()
+            .take_while(|glyph| glyph.outline.is_none())
+            .count();
+        let content_len = line_glyphs.len() - blank_end;
+        let mut line_width = positions[content_len];
+
+        let justify_offsets = if hori_align == ImtHoriAlign::Justify
+            && line_i + 1 < line_count
+            && body.width as i32 > line_width
+        {
+            let offsets = justify_offsets(
+                &line_glyphs[..content_len],
+                (body.width as i32 - line_width) as f32,
+                max_space_stretch,
+                fractional_advance,
+            );
+
+            line_width += float::round(offsets.last().copied().unwrap_or(0.0)) as i32;
+            Some(offsets)
+        } else {
+            None
+        };
+
+        let line_left = body.x
+            + aligned_offset(
+                body.width,
+                line_width,
+                hori_behav == ImtHoriBehav::Shift,
+                match hori_align {
+                    ImtHoriAlign::Left | ImtHoriAlign::Justify => Align::Start,
+                    ImtHoriAlign::Center => Align::Center,
+                    ImtHoriAlign::Right => Align::End,
+                },
+            );
+
+        text_left = text_left.min(line_left);
+        text_right = text_right.max(line_left + line_width);
+        let baseline = text_top + (line_i as i32 * line_height) + ascender;
+
+        for (i, (glyph, pen_x)) in line_glyphs.iter().zip(positions.iter()).enumerate() {
+            let justify_offset = justify_offsets
+                .as_ref()
+                .and_then(|offsets| offsets.get(i))
+                .map(|offset| float::round(*offset) as i32)
+                .unwrap_or(0);
+
+            let x = line_left + pen_x + justify_offset + glyph.bearing_x as i32;
+            let y = baseline - (glyph.bearing_y as i32 + glyph.height as i32);
+            positioned.push(PositionedGlyph::from_scaled(x, y, glyph.clone()));
+        }
+    }
+
+    let (left, right) = match body.width {
+        0 => (0, 0),
+        width => (body.x - text_left, text_right - (body.x + width as i32)),
+    };
+
+    let (top, bottom) = match body.height {
+        0 => (0, 0),
+        height => {
+            (
+                body.y - text_top,
+                (text_top + text_height) - (body.y + height as i32),
+            )
+        },
+    };
+
+    UniformLayout {
+        glyphs: positioned,
+        overflow: ImtOverflow {
+            left,
+            right,
+            top,
+            bottom,
+        },
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Align {
+    Start,
+    Center,
+    End,
+}
+
+/// Offset of content of `length` from the start of a body of `body_length`. When `body_length`
+/// is zero the body is a single point.
+fn aligned_offset(body_length: u32, length: i32, shift: bool, align: Align) -> i32 {
+    let body_length = body_length as i32;
+
+    if shift && body_length != 0 && length > body_length {
+        return 0;
+    }
+
+    match align {
+        Align::Start => 0,
+        Align::Center => (body_length - length) / 2,
+        Align::End => body_length - length,
+    }
+}
+
+/// Compute the offset of each glyph of a line to distribute `extra` space between them.
+///
+/// Glyphs without an outline, i.e. spaces, are stretched up to `max_space_stretch` times their
+/// advance. What remains is distributed evenly between every glyph. The returned `Vec` has one
+/// more offset than `glyphs`, the last being the offset of the end of the line.
+fn justify_offsets(
+    glyphs: &[ScaledGlyph],
+    extra: f32,
+    max_space_stretch: f32,
+    fractional: bool,
+) -> Vec<f32> {
+    let advance = |glyph: &ScaledGlyph| {
+        if fractional {
+            glyph.advance_w_f32
+        } else {
+            glyph.advance_w as f32
+        }
+    };
+
+    let natural_space = glyphs
+        .iter()
+        .filter(|glyph| glyph.outline.is_none())
+        .map(advance)
+        .sum::<f32>();
+
+    let space_extra = extra.min(natural_space * (max_space_stretch - 1.0).max(0.0));
+    let gaps = glyphs.len().saturating_sub(1);
+
+    let glyph_extra = match gaps {
+        0 => 0.0,
+        gaps => (extra - space_extra) / gaps as f32,
+    };
+
+    let mut offsets = Vec::with_capacity(glyphs.len() + 1);
+    let mut offset = 0.0;
+    offsets.push(offset);
+
+    for (i, glyph) in glyphs.iter().enumerate() {
+        if glyph.outline.is_none() && natural_space > 0.0 {
+            offset += space_extra * (advance(glyph) / natural_space);
+        }
+
+        if i + 1 < glyphs.len() {
+            offset += glyph_extra;
+        }
+
+        offsets.push(offset);
+    }
+
+    offsets
+}
+
+/// Split `glyphs` into lines no wider than `width`, see `uniform_layout`.
+fn wrap_lines(glyphs: &[ScaledGlyph], width: f32, fractional: bool) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    // The first glyph after the last glyph without an outline within the line.
+    let mut word_start = 0;
+    let mut pen_x = 0.0_f32;
+
+    let advance = |glyph: &ScaledGlyph| {
+        if fractional {
+            glyph.advance_w_f32
+        } else {
+            glyph.advance_w as f32
+        }
+    };
+
+    for (i, glyph) in glyphs.iter().enumerate() {
+        if glyph.outline.is_none() {
+            pen_x += advance(glyph);
+            word_start = i + 1;
+            continue;
+        }
+
+        if i > line_start && float::round(pen_x + advance(glyph)) > width {
+            let line_end = if word_start > line_start {
+                word_start
+            } else {
+                i
+            };
+
+            lines.push(line_start..line_end);
+            line_start = line_end;
+            word_start = line_start;
+            pen_x = glyphs[line_start..i].iter().map(advance).sum();
+        }
+
+        pen_x += advance(glyph);
+    }
+
+    lines.push(line_start..glyphs.len());
+    lines
 }
@@ -1,3 +1,10 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("either the `std` or the `libm` feature must be enabled");
+
+extern crate alloc;
+
 pub mod error;
 pub mod layout;
 pub mod parse;
@@ -12,4 +19,3996 @@ mod tests {
     fn it_works() {
         parse::test();
     }
+
+    fn glyph_id(font: &parse::Font, c: char) -> u16 {
+        font.cmap_table()
+            .encoding_records
+            .iter()
+            .find(|record| record.platform_id == 3)
+            .unwrap()
+            .subtable
+            .glyph_id_map[&(c as u16)]
+    }
+
+    #[test]
+    fn pure_descender_bounds() {
+        let font = parse::test();
+        let glyph_id = glyph_id(&font, '_');
+        let outline = font.glyf_table().outlines.get(&glyph_id).unwrap();
+        assert!(outline.y_max < 0.0);
+
+        // At 64 the bottom of the underscore lands exactly on the pixel grid.
+        for size in [12.0, 16.0, 64.0, 128.0] {
+            let scaler = size / font.head_table().units_per_em as f32;
+            let glyph = raster::ScaledGlyph::evaluate(&font, None, false, glyph_id, size).unwrap();
+            let top = glyph.bearing_y as i32 + glyph.height as i32;
+            assert_eq!(glyph.bearing_y as f32, (outline.y_min * scaler).floor());
+            assert_eq!(top as f32, (outline.y_max * scaler).ceil());
+            assert!(top <= 0);
+        }
+    }
+
+    #[test]
+    fn pure_ascender_bounds() {
+        let font = parse::test();
+        let glyph_id = glyph_id(&font, '\'');
+        let outline = font.glyf_table().outlines.get(&glyph_id).unwrap();
+        assert!(outline.y_min > 0.0);
+
+        // At 128 the top of the apostrophe lands exactly on the pixel grid.
+        for size in [12.0, 16.0, 64.0, 128.0] {
+            let scaler = size / font.head_table().units_per_em as f32;
+            let glyph = raster::ScaledGlyph::evaluate(&font, None, false, glyph_id, size).unwrap();
+            let top = glyph.bearing_y as i32 + glyph.height as i32;
+            assert_eq!(glyph.bearing_y as f32, (outline.y_min * scaler).floor());
+            assert_eq!(top as f32, (outline.y_max * scaler).ceil());
+            assert!(glyph.bearing_y >= 0);
+        }
+    }
+
+    #[test]
+    fn negative_bearing_bounds() {
+        use raster::cpu::CpuRasterizer;
+
+        // The fraction slash overhangs its neighbors on both sides.
+        let font = parse::test();
+        let glyph_id = glyph_id(&font, '\u{2044}');
+        let outline = font.glyf_table().outlines.get(&glyph_id).unwrap();
+        assert!(outline.x_min < -300.0);
+
+        for size in [12.0, 32.0, 64.0] {
+            let scaler = size / font.head_table().units_per_em as f32;
+            let glyph = raster::ScaledGlyph::evaluate(&font, None, false, glyph_id, size).unwrap();
+            let right = glyph.bearing_x as i32 + glyph.width as i32;
+            assert_eq!(glyph.bearing_x as f32, (outline.x_min * scaler).floor());
+            assert_eq!(right as f32, (outline.x_max * scaler).ceil());
+            assert!(glyph.bearing_x < 0);
+            assert!(right > glyph.advance_w as i32);
+
+            // Ink left of the pen location is kept within the image.
+            let bitmap = CpuRasterizer::new().render(&glyph);
+            let width = glyph.width as usize;
+            let left_of_pen = -glyph.bearing_x as usize;
+
+            assert!(bitmap
+                .chunks_exact(width)
+                .any(|row| row[..left_of_pen].iter().any(|value| *value != 0)));
+        }
+    }
+
+    #[test]
+    fn rle_round_trip() {
+        use raster::rle::RleMask;
+
+        let (width, height) = (7, 5);
+        let mut pixels = vec![[0, 0, 0, 255]; width * height];
+        pixels[10] = [85, 170, 255, 255];
+        pixels[11] = [255; 4];
+        pixels[12] = [255; 4];
+        pixels[17] = [255; 4];
+        pixels[18] = [170, 85, 0, 255];
+
+        let mask = RleMask::encode(width as u32, height as u32, &pixels).unwrap();
+        assert_eq!(mask.runs.len(), 7);
+        assert_eq!(mask.runs.iter().map(|run| run.length).sum::<u32>(), 35);
+        assert_eq!(mask.decode(), pixels);
+
+        assert!(RleMask::encode(width as u32, height as u32, &pixels[1..]).is_none());
+        assert!(RleMask::encode(0, 0, &[]).unwrap().decode().is_empty());
+    }
+
+    #[test]
+    fn infer_delta_outside_neighbors() {
+        use util::variation::infer_delta;
+
+        // The delta of the neighbor with the nearest coordinate is used.
+        assert_eq!(infer_delta(10.0, 5.0, 20.0, 1.0, 2.0), 1.0);
+        assert_eq!(infer_delta(20.0, 5.0, 10.0, 1.0, 2.0), 2.0);
+        assert_eq!(infer_delta(10.0, 25.0, 20.0, 1.0, 2.0), 2.0);
+        assert_eq!(infer_delta(20.0, 25.0, 10.0, 1.0, 2.0), 1.0);
+
+        // Targets on a neighbor use its delta.
+        assert_eq!(infer_delta(10.0, 10.0, 20.0, 1.0, 2.0), 1.0);
+        assert_eq!(infer_delta(10.0, 20.0, 20.0, 1.0, 2.0), 2.0);
+    }
+
+    #[test]
+    fn infer_delta_between_neighbors() {
+        use util::variation::infer_delta;
+
+        assert_eq!(infer_delta(10.0, 15.0, 20.0, 2.0, 4.0), 3.0);
+        assert_eq!(infer_delta(20.0, 15.0, 10.0, 2.0, 4.0), 3.0);
+        assert_eq!(infer_delta(0.0, 25.0, 100.0, -4.0, 4.0), -2.0);
+    }
+
+    #[test]
+    fn infer_delta_equal_neighbors() {
+        use util::variation::infer_delta;
+
+        // Neighbors with the same coordinate & delta apply that delta regardless of the target.
+        assert_eq!(infer_delta(10.0, 10.0, 10.0, 3.0, 3.0), 3.0);
+        assert_eq!(infer_delta(10.0, 5.0, 10.0, 3.0, 3.0), 3.0);
+        assert_eq!(infer_delta(10.0, 15.0, 10.0, 3.0, 3.0), 3.0);
+
+        // With different deltas no delta is inferred.
+        assert_eq!(infer_delta(10.0, 10.0, 10.0, 3.0, 4.0), 0.0);
+        assert_eq!(infer_delta(10.0, 15.0, 10.0, 3.0, 4.0), 0.0);
+    }
+
+    #[test]
+    fn advance_falls_back_to_phantom_points() {
+        use parse::table_directory::TableDirectory;
+        use util::variation::{advance_width, normalize_axis_coords};
+
+        let bytes = include_bytes!("RobotoFlex.ttf");
+        let font = parse::test();
+        let glyph_id = glyph_id(&font, 'A');
+
+        // Remove the delta sets of the item variation data used by the glyph, so that it is no
+        // longer covered by HVAR.
+        let [outer_index, _] = font
+            .hvar_table()
+            .unwrap()
+            .advance_map
+            .as_ref()
+            .unwrap()
+            .map_data[glyph_id as usize];
+
+        let read_u32 =
+            |offset: usize| u32::from_be_bytes(bytes[offset..(offset + 4)].try_into().unwrap());
+        let hvar_offset = TableDirectory::try_parse(bytes, 0)
+            .unwrap()
+            .table_records
+            .iter()
+            .find(|record| record.table_tag == u32::from_be_bytes(*b"HVAR"))
+            .unwrap()
+            .offset as usize;
+        let store_offset = hvar_offset + read_u32(hvar_offset + 4) as usize;
+        let item_data_offset =
+            store_offset + read_u32(store_offset + 8 + (outer_index * 4)) as usize;
+
+        let mut partial = bytes.to_vec();
+        partial[item_data_offset..(item_data_offset + 2)].copy_from_slice(&[0, 0]);
+        let partial = parse::Font::from_bytes(partial).unwrap();
+
+        assert!(
+            partial.hvar_table().unwrap().item_variation_store.item_data[outer_index]
+                .delta_sets
+                .is_empty()
+        );
+
+        let mut varies = false;
+
+        for instance in font.fvar_table().unwrap().instances.iter() {
+            let mut coords = instance.coordinates.clone();
+            normalize_axis_coords(&font, &mut coords).unwrap();
+            let expected = advance_width(&font, glyph_id, &coords).unwrap();
+            let fallback = advance_width(&partial, glyph_id, &coords).unwrap();
+            assert!((expected - fallback).abs() < 0.01);
+            varies |= expected != 0.0;
+        }
+
+        assert!(varies);
+    }
+
+    #[test]
+    fn cpu_render_into() {
+        use raster::cpu::{CpuRasterErr, CpuRasterizer};
+
+        let font = parse::test();
+        let glyph_id = glyph_id(&font, 'o');
+        let glyph = raster::ScaledGlyph::evaluate(&font, None, false, glyph_id, 24.0).unwrap();
+        let (width, height) = (glyph.width as usize, glyph.height as usize);
+        let rasterizer = CpuRasterizer::new();
+        let bitmap = rasterizer.render(&glyph);
+
+        // The counter of the 'o' is empty while its sides are covered.
+        assert_eq!(bitmap[(height / 2) * width + (width / 2)], 0);
+        assert!(bitmap[(height / 2) * width..((height / 2) + 1) * width].contains(&255));
+
+        let stride = width + 7;
+        let mut canvas = vec![0; stride * (height + 5)];
+        rasterizer
+            .render_into(&glyph, &mut canvas, stride, 4, 3)
+            .unwrap();
+
+        for row in 0..height {
+            let start = ((row + 3) * stride) + 4;
+            assert_eq!(
+                &canvas[start..(start + width)],
+                &bitmap[(row * width)..((row + 1) * width)]
+            );
+        }
+
+        assert_eq!(
+            canvas.iter().map(|value| *value as u32).sum::<u32>(),
+            bitmap.iter().map(|value| *value as u32).sum::<u32>()
+        );
+
+        assert_eq!(
+            rasterizer.render_into(&glyph, &mut canvas, stride, 8, 0),
+            Err(CpuRasterErr::OutOfBounds)
+        );
+
+        assert_eq!(
+            rasterizer.render_into(&glyph, &mut canvas, stride, 0, 6),
+            Err(CpuRasterErr::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn segment_data_tolerance() {
+        let font = parse::test();
+        let glyph =
+            raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, 'o'), 96.0).unwrap();
+        let coarse = glyph.segment_data(1.0);
+        let fine = glyph.segment_data(0.01);
+        assert!(fine.len() > coarse.len());
+
+        // Segments of each contour stay connected end to end.
+        for segments in [&coarse, &fine] {
+            let connected = segments
+                .windows(2)
+                .filter(|pair| pair[0][2] == pair[1][0] && pair[0][3] == pair[1][1])
+                .count();
+
+            assert!(connected >= segments.len() - 2);
+        }
+
+        let glyph =
+            raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, ' '), 96.0).unwrap();
+        assert!(glyph
+            .segment_data(raster::DEFAULT_CURVE_TOLERANCE)
+            .is_empty());
+    }
+
+    #[test]
+    fn scaled_advance_matches_evaluate() {
+        let font = parse::test();
+        let mut instances = vec![None];
+
+        for instance in font.fvar_table().unwrap().instances.iter().step_by(3) {
+            let mut coords = instance.coordinates.clone();
+            util::variation::normalize_axis_coords(&font, &mut coords).unwrap();
+            instances.push(Some(coords));
+        }
+
+        for coords in instances.iter() {
+            for glyph_id in 0..font.maxp_table().num_glyphs {
+                for size in [11.0, 32.0] {
+                    let glyph = raster::ScaledGlyph::evaluate(
+                        &font,
+                        coords.as_deref(),
+                        true,
+                        glyph_id,
+                        size,
+                    );
+
+                    assert_eq!(
+                        font.scaled_advance(glyph_id, coords.as_deref(), size),
+                        glyph.map(|glyph| glyph.advance_w)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cmap_map_str() {
+        let font = parse::test();
+        let subtable = font.cmap_table().subtable(3, 1, None).unwrap();
+        let text = "The quick brown fox, 0123456789 ÀÉÎ zyx \u{FFFF}\u{1F600} abc";
+        let mut glyph_ids = vec![7];
+
+        let unmapped = subtable.map_str(text, &mut glyph_ids);
+        assert_eq!(glyph_ids.len(), text.chars().count() + 1);
+        assert_eq!(glyph_ids[0], 7);
+        assert_eq!(unmapped, 2);
+
+        for (c, glyph_id) in text.chars().zip(glyph_ids[1..].iter()) {
+            let expected = u16::try_from(c as u32)
+                .ok()
+                .and_then(|code| subtable.glyph_id_map.get(&code))
+                .copied()
+                .unwrap_or(0);
+
+            assert_eq!(*glyph_id, expected);
+        }
+    }
+
+    #[test]
+    fn cmap_format4_id_range_offset() {
+        fn subtable_bytes(id_range_offset: u16) -> Vec<u8> {
+            let header = [4, 38, 0, 4, 4, 1, 0];
+            let end_code = [0x43, 0xFFFF];
+            let reserved_pad = [0];
+            let start_code = [0x41, 0xFFFF];
+            let id_delta = [5, 1];
+            let id_range_offset = [id_range_offset, 0];
+            let glyph_id_array = [10, 0, 12];
+
+            [
+                &header[..],
+                &end_code,
+                &reserved_pad,
+                &start_code,
+                &id_delta,
+                &id_range_offset,
+                &glyph_id_array,
+            ]
+            .concat()
+            .into_iter()
+            .flat_map(u16::to_be_bytes)
+            .collect()
+        }
+
+        let subtable = parse::CmapSubtable::try_parse(&subtable_bytes(4), 0).unwrap();
+        assert_eq!(subtable.glyph_id_map.get(&0x41), Some(&15));
+        assert_eq!(subtable.glyph_id_map.get(&0x42), Some(&0));
+        assert_eq!(subtable.glyph_id_map.get(&0x43), Some(&17));
+
+        for id_range_offset in [6, 0x8000, 0xFFFE] {
+            let err =
+                parse::CmapSubtable::try_parse(&subtable_bytes(id_range_offset), 0).unwrap_err();
+            assert_eq!(err.kind, error::ImtErrorKind::Malformed);
+        }
+    }
+
+    #[test]
+    fn cmap_format4_glyph_id_array() {
+        // The second segment indexes glyphIdArray relative to its own idRangeOffset entry,
+        // which is two bytes further along than that of the first segment.
+        let header = [4, 46, 0, 6, 4, 1, 2];
+        let end_code = [0x31, 0x63, 0xFFFF];
+        let reserved_pad = [0];
+        let start_code = [0x30, 0x61, 0xFFFF];
+        let id_delta = [100, 0xFFFE, 1];
+        let id_range_offset = [0, 4, 0];
+        let glyph_id_array = [20, 0, 1];
+
+        let bytes: Vec<u8> = [
+            &header[..],
+            &end_code,
+            &reserved_pad,
+            &start_code,
+            &id_delta,
+            &id_range_offset,
+            &glyph_id_array,
+        ]
+        .concat()
+        .into_iter()
+        .flat_map(u16::to_be_bytes)
+        .collect();
+
+        let subtable = parse::CmapSubtable::try_parse(&bytes, 0).unwrap();
+        assert_eq!(subtable.glyph_id_map.get(&0x30), Some(&148));
+        assert_eq!(subtable.glyph_id_map.get(&0x31), Some(&149));
+        assert_eq!(subtable.glyph_id_map.get(&0x61), Some(&18));
+        assert_eq!(subtable.glyph_id_map.get(&0x62), Some(&0));
+        // idDelta is added modulo 65536.
+        assert_eq!(subtable.glyph_id_map.get(&0x63), Some(&0xFFFF));
+        assert_eq!(subtable.glyph_id_map.len(), 5);
+    }
+
+    #[test]
+    fn default_coords_match_no_coords() {
+        let font = parse::test();
+
+        let default_coords: Vec<f32> = font
+            .fvar_table()
+            .unwrap()
+            .axes
+            .iter()
+            .map(|axis| axis.default_value)
+            .collect();
+
+        assert!(util::variation::is_default_normalized(&[0.0; 4]));
+        assert!(!util::variation::is_default_normalized(&[0.0, 0.25]));
+
+        for c in ['a', 'g', 'W', '&'] {
+            let glyph_id = glyph_id(&font, c);
+            let base = raster::ScaledGlyph::evaluate(&font, None, false, glyph_id, 32.0).unwrap();
+            let default =
+                raster::ScaledGlyph::evaluate(&font, Some(&default_coords), false, glyph_id, 32.0)
+                    .unwrap();
+
+            assert_eq!(base.unique_id, default.unique_id);
+            assert_eq!(base.advance_w, default.advance_w);
+            assert_eq!(
+                base.segment_data(raster::DEFAULT_CURVE_TOLERANCE),
+                default.segment_data(raster::DEFAULT_CURVE_TOLERANCE)
+            );
+        }
+    }
+
+    #[test]
+    fn instruction_tables() {
+        let bytes = include_bytes!("RobotoFlex.ttf");
+        let font = parse::test();
+        assert!(font.prep_table().is_none());
+
+        let font = parse::Font::from_bytes_with_options(
+            bytes,
+            parse::FontOptions {
+                instructions: true,
+            },
+        )
+        .unwrap();
+
+        assert!(font.cvt_table().is_none());
+        assert!(font.fpgm_table().is_none());
+        assert_eq!(font.prep_table().unwrap().instructions.len(), 7);
+
+        let cvt = parse::CvtTable::try_parse(&[0x00, 0x01, 0xFF, 0xFE, 0x00], 0).unwrap();
+        assert_eq!(cvt.values, vec![1, -2]);
+    }
+
+    #[test]
+    fn memory_estimate() {
+        let bytes = include_bytes!("RobotoFlex.ttf");
+        let font = parse::test();
+        let estimate = font.memory_estimate();
+
+        let gvar_deltas: usize = font
+            .gvar_table()
+            .unwrap()
+            .glyph_variations
+            .values()
+            .flat_map(|variation| variation.tuples.iter())
+            .map(|tuple| tuple.deltas.len() * 4)
+            .sum();
+
+        assert!(estimate > gvar_deltas);
+        assert!(estimate < bytes.len() * 16);
+
+        let with_instructions = parse::Font::from_bytes_with_options(
+            bytes,
+            parse::FontOptions {
+                instructions: true,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(with_instructions.memory_estimate(), estimate + 7);
+    }
+
+    #[test]
+    fn selective_tables() {
+        use parse::TableSet;
+
+        let bytes = include_bytes!("RobotoFlex.ttf");
+        let full = parse::Font::from_bytes(bytes).unwrap();
+        assert_eq!(full.tables(), TableSet::default());
+
+        let browser =
+            parse::Font::from_bytes_selective(bytes, TableSet::NAME | TableSet::FVAR).unwrap();
+        assert_eq!(browser.postscript_name(), full.postscript_name());
+        assert!(browser.fvar_table().is_some());
+        assert!(browser.gvar_table().is_none());
+        assert!(browser.os2_table().is_none());
+        assert!(!browser.tables().contains(TableSet::GLYF));
+        assert!(browser.memory_estimate() < full.memory_estimate() / 10);
+
+        let render = parse::Font::from_bytes_selective(
+            bytes,
+            TableSet::CMAP | TableSet::HMTX | TableSet::GVAR,
+        )
+        .unwrap();
+        assert!(render.tables().contains(TableSet::GLYF));
+        assert!(render.gvar_table().is_some());
+        assert!(render.fvar_table().is_none());
+
+        let glyph_id = glyph_id(&render, 'a');
+        let a = raster::ScaledGlyph::evaluate(&render, None, false, glyph_id, 32.0).unwrap();
+        let b = raster::ScaledGlyph::evaluate(&full, None, false, glyph_id, 32.0).unwrap();
+        assert_eq!(a.advance_w, b.advance_w);
+        assert_eq!(
+            a.segment_data(raster::DEFAULT_CURVE_TOLERANCE),
+            b.segment_data(raster::DEFAULT_CURVE_TOLERANCE)
+        );
+
+        // Methods that need a table that was not requested don't panic.
+        assert!(matches!(
+            raster::ScaledGlyph::evaluate(&browser, None, false, glyph_id, 32.0),
+            Err(raster::ScaledGlyphErr::Missing)
+        ));
+        assert!(matches!(
+            browser.scaled_advance(glyph_id, None, 32.0),
+            Err(raster::ScaledGlyphErr::Missing)
+        ));
+        assert!(matches!(
+            raster::cpu::render_string_colored(
+                &raster::cpu::CpuRasterizer::new(),
+                &browser,
+                None,
+                32.0,
+                "a",
+                &[]
+            ),
+            Err(raster::ScaledGlyphErr::Missing)
+        ));
+        assert_eq!(browser.space_advance(None, 32.0), 8.0);
+        assert_eq!(browser.string_advance("a", &[], 32.0, false), 0.0);
+        assert_eq!(browser.metrics_glyph_id(glyph_id), glyph_id);
+        assert_eq!(browser.glyph_sidebearings(glyph_id, None), None);
+        assert_eq!(browser.glyphs_with_outlines().count(), 0);
+        assert!(!browser.is_monospaced());
+
+        let missing = |error: error::ImtError| (error.kind, error.source);
+        assert!(browser.try_name_table().is_ok());
+        assert_eq!(
+            missing(browser.try_cmap_table().unwrap_err()),
+            (
+                error::ImtErrorKind::MissingTable,
+                error::ImtErrorSource::CmapTable
+            )
+        );
+        assert_eq!(
+            missing(browser.try_hmtx_table().unwrap_err()),
+            (
+                error::ImtErrorKind::MissingTable,
+                error::ImtErrorSource::HmtxTable
+            )
+        );
+        assert_eq!(
+            missing(browser.try_loca_table().unwrap_err()),
+            (
+                error::ImtErrorKind::MissingTable,
+                error::ImtErrorSource::LocaTable
+            )
+        );
+        assert_eq!(
+            missing(browser.try_glyf_table().unwrap_err()),
+            (
+                error::ImtErrorKind::MissingTable,
+                error::ImtErrorSource::GlyfTable
+            )
+        );
+        assert_eq!(
+            missing(browser.glyph_count_with_outlines().unwrap_err()),
+            (
+                error::ImtErrorKind::MissingTable,
+                error::ImtErrorSource::GlyfTable
+            )
+        );
+        assert_eq!(
+            missing(render.try_name_table().unwrap_err()),
+            (
+                error::ImtErrorKind::MissingTable,
+                error::ImtErrorSource::NameTable
+            )
+        );
+        assert_eq!(
+            render.glyph_count_with_outlines().unwrap(),
+            full.glyph_count_with_outlines().unwrap()
+        );
+    }
+
+    #[test]
+    fn avar_version_2() {
+        let font = parse::test();
+        let avar = font.avar_table().unwrap();
+        assert_eq!(avar.major_version, 1);
+        assert!(avar.item_variation_store.is_none());
+
+        // One axis with a store that adds half of its own value when positive.
+        let bytes = [
+            &[0, 2, 0, 0, 0, 0, 0, 1][..],
+            &[0, 0],
+            &[0, 0, 0, 0, 0, 0, 0, 18],
+            &[0, 1, 0, 0, 0, 12, 0, 1, 0, 0, 0, 22],
+            &[0, 1, 0, 1, 0x00, 0x00, 0x40, 0x00, 0x40, 0x00],
+            &[0, 1, 0, 1, 0, 1, 0, 0, 0x20, 0x00],
+        ]
+        .concat();
+
+        let avar = parse::AvarTable::try_parse(&bytes, 0).unwrap();
+        assert_eq!(avar.major_version, 2);
+        assert!(avar.axis_index_map.is_none());
+        assert!(avar.item_variation_store.is_some());
+
+        for (coord, expected) in [(0.5, 0.75), (1.0, 1.0), (0.0, 0.0), (-0.5, -0.5)] {
+            let mut coords = [coord];
+            util::variation::avar_apply_store(&avar, &mut coords);
+            assert_eq!(coords[0], expected);
+        }
+
+        let mut bytes = bytes;
+        bytes[1] = 3;
+
+        assert_eq!(
+            parse::AvarTable::try_parse(&bytes, 0).unwrap_err().kind,
+            error::ImtErrorKind::UnexpectedVersion
+        );
+    }
+
+    #[test]
+    fn string_advance() {
+        let font = parse::test();
+        let text = "Hello, World!";
+        let coords = font.coords_from([(b"wght", 800.0)]).unwrap();
+        let mut normalized = coords.clone();
+        util::variation::normalize_axis_coords(&font, &mut normalized).unwrap();
+
+        for (coords, normalized) in [(None, &[][..]), (Some(&coords[..]), &normalized[..])] {
+            let expected: f32 = text
+                .chars()
+                .map(|c| {
+                    raster::ScaledGlyph::evaluate(&font, coords, false, glyph_id(&font, c), 24.0)
+                        .unwrap()
+                        .advance_w_f32
+                })
+                .sum();
+
+            let advance = font.string_advance(text, normalized, 24.0, false);
+            assert!((advance - expected).abs() < 0.01);
+        }
+
+        assert!(
+            font.string_advance(text, &normalized, 24.0, false)
+                > font.string_advance(text, &[], 24.0, false)
+        );
+        assert_eq!(font.string_advance("", &[], 24.0, false), 0.0);
+
+        // "To" is kerned by -150 units in the default instance.
+        let scaler = 24.0 / font.head_table().units_per_em as f32;
+        let kerned = font.string_advance("To", &[], 24.0, true);
+        let unkerned = font.string_advance("To", &[], 24.0, false);
+        assert!((kerned - (unkerned - (150.0 * scaler))).abs() < 0.01);
+
+        // The kerning of "To" varies with `wght`, so it differs from the default instance.
+        let kerned = font.string_advance("To", &normalized, 24.0, true);
+        let unkerned = font.string_advance("To", &normalized, 24.0, false);
+        assert!(unkerned - kerned > 0.0);
+        assert!(((unkerned - kerned) - (150.0 * scaler)).abs() > 0.01);
+    }
+
+    #[test]
+    fn coords_equivalent() {
+        let font = parse::test();
+        let o = glyph_id(&font, 'o');
+        let axis_count = font.gvar_table().unwrap().axis_count;
+        let default = vec![0.0; axis_count];
+        let mut bold = font.coords_from([(b"wght", 900.0)]).unwrap();
+        util::variation::normalize_axis_coords(&font, &mut bold).unwrap();
+
+        assert!(font.coords_equivalent(o, &default, &default));
+        assert!(font.coords_equivalent(o, &bold, &bold));
+        assert!(!font.coords_equivalent(o, &default, &bold));
+
+        // Glyphs without variation data, e.g. space, are the same for every instance.
+        assert!(font.coords_equivalent(glyph_id(&font, ' '), &default, &bold));
+
+        // Coordinates that don't cover every axis are never equivalent.
+        assert!(!font.coords_equivalent(o, &[], &[]));
+        assert!(!font.coords_equivalent(o, &default[1..], &default[1..]));
+        assert!(!font.coords_equivalent(glyph_id(&font, ' '), &[0.0], &[0.0]));
+        assert!(!font.coords_equivalent(o, &default, &default[1..]));
+    }
+
+    #[test]
+    fn pair_adjustments() {
+        let font = parse::test();
+        let glyph_ids: Vec<u16> = "AVWo".chars().map(|c| glyph_id(&font, c)).collect();
+        let adjustments = util::shaping::pair_adjustments(&font, &glyph_ids, b"kern", &[]).unwrap();
+        let x_advances: Vec<f32> = adjustments.iter().map(|adj| adj.x_advance).collect();
+
+        // A-V from a glyph pair, V-W is not kerned & W-o from a class pair.
+        assert_eq!(x_advances, [-87.0, 0.0, -31.0, 0.0]);
+
+        // Marks are skipped by the lookup, so T-o is kerned across U+0301.
+        let glyph_ids: Vec<u16> = "T\u{301}o".chars().map(|c| glyph_id(&font, c)).collect();
+        assert_eq!(font.gdef_table().unwrap().glyph_class(glyph_ids[1]), 3);
+        let adjustments = util::shaping::pair_adjustments(&font, &glyph_ids, b"kern", &[]).unwrap();
+        assert_eq!(adjustments[0].x_advance, -150.0);
+
+        assert!(util::shaping::pair_adjustments(&font, &glyph_ids, b"kern", &[0.0]).is_err());
+        assert!(
+            util::shaping::pair_adjustments(&font, &glyph_ids, b"abcd", &[])
+                .unwrap()
+                .iter()
+                .all(|adj| *adj == util::shaping::GlyphAdjustment::default())
+        );
+    }
+
+    #[test]
+    fn malformed_layout_tables() {
+        let features = [parse::Feature::LIGA, parse::Feature::KERN];
+        let truncated = |table_tag| parse::Font::from_bytes(truncated_table_bytes(table_tag, 8));
+
+        // Without `GSUB` "ffi" is not a ligature, but "To" is still kerned.
+        let font = truncated(parse::table_tag::GSUB).unwrap();
+        assert!(font.gsub_table().is_none());
+        let shaped = font.shape("ffiTo", &[], &features);
+        assert_eq!(shaped.len(), 5);
+        assert!(shaped[3].x_advance < font.shape("ffiTo", &[], &[])[3].x_advance);
+
+        // Without `GPOS` nothing is kerned.
+        let font = truncated(parse::table_tag::GPOS).unwrap();
+        assert!(font.gpos_table().is_none());
+        assert_eq!(
+            font.string_advance("To", &[], 20.0, true),
+            font.string_advance("To", &[], 20.0, false)
+        );
+
+        // Without `GDEF` kerning is applied without variations.
+        let font = truncated(parse::table_tag::GDEF).unwrap();
+        assert!(font.gdef_table().is_none());
+        let glyph_ids: Vec<u16> = "AV".chars().map(|c| glyph_id(&font, c)).collect();
+        let adjustments =
+            util::shaping::pair_adjustments(&font, &glyph_ids, b"kern", &[0.5; 13]).unwrap();
+        assert_eq!(adjustments[0].x_advance, -87.0);
+    }
+
+    #[test]
+    fn hidden_axes() {
+        let font = parse::test();
+        let fvar = font.fvar_table().unwrap();
+        let visible = font.variation_axes(false);
+        let all = font.variation_axes(true);
+
+        assert_eq!(all.len(), fvar.axes.len());
+        assert!(visible.len() < all.len());
+        assert!(visible.iter().all(|axis| !axis.hidden));
+        assert!(all.iter().any(|axis| axis.hidden && axis.tag == *b"XOPQ"));
+        assert!(visible.iter().any(|axis| axis.tag == *b"wght"));
+        assert!(all.iter().all(|axis| {
+            axis.name.is_some() && fvar.axes[axis.index].axis_tag.to_be_bytes() == axis.tag
+        }));
+
+        let defaults: Vec<f32> = fvar.axes.iter().map(|axis| axis.default_value).collect();
+
+        for coords in fvar.sample_grid(2, false) {
+            for (i, axis) in fvar.axes.iter().enumerate() {
+                if axis.hidden_axis() {
+                    assert_eq!(coords[i], defaults[i]);
+                }
+            }
+        }
+
+        // With hidden axes included, the first hidden axis is varied as long as the cap allows.
+        let first_hidden = fvar
+            .axes
+            .iter()
+            .position(|axis| axis.hidden_axis())
+            .unwrap();
+        assert!(fvar
+            .sample_grid(2, true)
+            .iter()
+            .any(|coords| coords[first_hidden] != defaults[first_hidden]));
+    }
+
+    #[test]
+    fn rasterizer_cpu_fallback() {
+        use raster::backend::{RasteredGlyph, Rasterizer};
+
+        #[cfg(feature = "gpu")]
+        assert!(!Rasterizer::new_auto(None).is_gpu());
+
+        let rasterizer = Rasterizer::new_cpu();
+        assert!(!rasterizer.is_gpu());
+
+        let font = parse::test();
+        let glyphs: Vec<_> = "Ag "
+            .chars()
+            .map(|c| {
+                raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, c), 24.0).unwrap()
+            })
+            .collect();
+
+        let rastered = rasterizer.process(&glyphs);
+        assert_eq!(rastered.len(), glyphs.len());
+        let cpu = raster::cpu::CpuRasterizer::new();
+
+        for (glyph, rastered) in glyphs.iter().zip(rastered.iter()) {
+            assert_eq!(rastered.unique_id(), glyph.unique_id);
+            assert_eq!(rastered.advance_w(), glyph.advance_w);
+            assert_eq!(
+                (rastered.width(), rastered.height()),
+                (glyph.width, glyph.height)
+            );
+
+            match rastered {
+                RasteredGlyph::Cpu(rastered) => assert_eq!(rastered.coverage, cpu.render(glyph)),
+                #[allow(unreachable_patterns)]
+                _ => panic!("expected the cpu backend"),
+            }
+        }
+    }
+
+    #[test]
+    fn render_mono() {
+        let font = parse::test();
+        let rasterizer = raster::cpu::CpuRasterizer::new();
+
+        for c in ['A', 'g', 'W', ' '] {
+            let glyph = raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, c), 19.0)
+                .unwrap();
+            let coverage = rasterizer.render(&glyph);
+            let mono = rasterizer.render_mono(&glyph);
+
+            assert_eq!(mono.stride, (glyph.width as usize).div_ceil(8));
+            assert_eq!(mono.data.len(), mono.stride * glyph.height as usize);
+
+            for y in 0..glyph.height {
+                for x in 0..glyph.width {
+                    let value = coverage[(y * glyph.width + x) as usize];
+                    assert_eq!(mono.get(x, y), value >= 128);
+                }
+
+                // Padding bits are left unset.
+                for x in glyph.width..(mono.stride as u32 * 8) {
+                    let byte = mono.data[(y as usize * mono.stride) + (x as usize / 8)];
+                    assert_eq!(byte & (0x80 >> (x % 8)), 0);
+                }
+            }
+
+            assert!(!mono.get(glyph.width, 0));
+            assert_eq!(c == ' ', mono.data.iter().all(|byte| *byte == 0));
+        }
+    }
+
+    #[test]
+    fn glyph_sidebearings() {
+        let font = parse::test();
+        assert!(font
+            .glyph_sidebearings(glyph_id(&font, ' '), None)
+            .is_none());
+
+        let mut coords = font.coords_from([(b"wght", 1000.0)]).unwrap();
+        util::variation::normalize_axis_coords(&font, &mut coords).unwrap();
+
+        for c in ['H', 'o', 'j', 'W'] {
+            let glyph_id = glyph_id(&font, c);
+            let outline = font.glyf_table().outlines.get(&glyph_id).unwrap();
+            let advance = font.hmtx_table().hor_metric[glyph_id as usize].advance_width as f32;
+
+            let (lsb, rsb) = font.glyph_sidebearings(glyph_id, None).unwrap();
+            assert_eq!(lsb as f32, outline.x_min);
+            assert_eq!(rsb as f32, advance - outline.x_max);
+
+            let (varied_lsb, varied_rsb) =
+                font.glyph_sidebearings(glyph_id, Some(&coords)).unwrap();
+            assert!((varied_lsb, varied_rsb) != (lsb, rsb));
+
+            let mut varied = outline.as_ref().clone();
+            util::variation::outline_apply_gvar(&font, glyph_id, &mut varied, &coords).unwrap();
+            let varied_advance =
+                advance + util::variation::advance_width(&font, glyph_id, &coords).unwrap();
+
+            assert!((varied_lsb as f32 - varied.x_min).abs() <= 1.0);
+            assert!(
+                (varied_lsb as f32 + (varied.x_max - varied.x_min) + varied_rsb as f32
+                    - varied_advance)
+                    .abs()
+                    <= 1.0
+            );
+        }
+
+        assert!(font
+            .glyph_sidebearings(glyph_id(&font, 'H'), Some(&[2.0; 13]))
+            .is_none());
+    }
+
+    #[test]
+    fn glyph_id_out_of_range() {
+        let font = parse::test();
+        let last = font.num_glyphs() - 1;
+
+        // The last glyph is beyond the last metric and shares its advance.
+        assert!(font.hmtx_table().hor_metric.len() <= last as usize);
+        assert_eq!(
+            font.hmtx_table().advance_width(last),
+            font.hmtx_table()
+                .hor_metric
+                .last()
+                .map(|metric| metric.advance_width)
+        );
+        assert!(raster::ScaledGlyph::evaluate(&font, None, false, last, 24.0).is_ok());
+        assert!(font.scaled_advance(last, None, 24.0).is_ok());
+
+        for glyph_id in [font.num_glyphs(), u16::MAX] {
+            assert_eq!(
+                raster::ScaledGlyph::evaluate(&font, None, false, glyph_id, 24.0).unwrap_err(),
+                raster::ScaledGlyphErr::Missing
+            );
+
+            assert_eq!(
+                font.scaled_advance(glyph_id, None, 24.0),
+                Err(raster::ScaledGlyphErr::Missing)
+            );
+
+            assert!(font.glyph_sidebearings(glyph_id, None).is_none());
+        }
+    }
+
+    /// A glyph of `size` by `size` pixels covering the triangle below its top-left to
+    /// bottom-right diagonal, with a square hole aligned to the pixel grid.
+    fn reference_glyph(size: u32) -> raster::ScaledGlyph {
+        use parse::glyf_table::OutlineRawPoint;
+
+        let s = size as f32;
+        let hole = [
+            [1.0, s - 1.0],
+            [3.0, s - 1.0],
+            [3.0, s - 3.0],
+            [1.0, s - 3.0],
+        ];
+
+        let mut outline = parse::Outline {
+            x_min: 0.0,
+            y_min: 0.0,
+            x_max: 0.0,
+            y_max: 0.0,
+            points: [[0.0, 0.0], [s, s], [0.0, s]]
+                .into_iter()
+                .chain(hole)
+                .map(|[x, y]| {
+                    OutlineRawPoint {
+                        c: 0,
+                        x: x / s,
+                        y: y / s,
+                        control: false,
+                    }
+                })
+                .collect(),
+            contours: vec![0..3, 3..7],
+            geometry: Vec::new(),
+        };
+
+        outline.rebuild().unwrap();
+
+        raster::ScaledGlyph {
+            width: size,
+            height: size,
+            bearing_x: 0,
+            bearing_y: 0,
+            advance_w: size as i16,
+            advance_w_f32: s,
+            outline: Some(outline),
+            unique_id: 0,
+        }
+    }
+
+    #[test]
+    fn supersampled_reference() {
+        let size = 8;
+        let glyph = reference_glyph(size);
+        let rasterizer = raster::cpu::CpuRasterizer::new();
+        let reference = rasterizer.render_supersampled(&glyph, 16);
+        let coverage = |x: u32, y: u32| reference[(y * size + x) as usize];
+
+        for y in 0..size {
+            for x in 0..size {
+                let in_hole = (1..3).contains(&x) && ((size - 3)..(size - 1)).contains(&y);
+
+                if x < y && !in_hole {
+                    assert_eq!(coverage(x, y), 255);
+                } else if x > y || in_hole {
+                    assert_eq!(coverage(x, y), 0);
+                } else {
+                    // 120 of the 256 samples fall strictly below the diagonal.
+                    assert_eq!(coverage(x, y), 120);
+                }
+            }
+        }
+
+        assert_eq!(
+            rasterizer.render_supersampled(&glyph, 0).len(),
+            reference.len()
+        );
+
+        // The fast path should match the reference within a tolerance. Curves are flattened within
+        // `DEFAULT_CURVE_TOLERANCE`, which moves edges by up to a tenth of a pixel.
+        let font = parse::test();
+        let mut glyphs = vec![glyph];
+
+        for c in ['a', 'g', 'S', '@'] {
+            glyphs.push(
+                raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, c), 17.0)
+                    .unwrap(),
+            );
+        }
+
+        for glyph in glyphs.iter() {
+            let fast = rasterizer.render(glyph);
+            let reference = rasterizer.render_supersampled(glyph, 16);
+
+            for (fast, reference) in fast.into_iter().zip(reference) {
+                assert!((fast as i32 - reference as i32).abs() <= 32);
+            }
+        }
+    }
+
+    #[test]
+    fn component_transform() {
+        use parse::ComponentTransform;
+
+        const WE_HAVE_A_SCALE: u16 = 0x0008;
+        const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+        const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+        let bytes = [0x20, 0x00, 0xC0, 0x00, 0xE0, 0x00, 0x40, 0x00];
+
+        let transform = ComponentTransform::try_parse(&bytes, 0, 0).unwrap();
+        assert_eq!(transform, ComponentTransform::None);
+        assert_eq!(transform.as_matrix(), [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(transform.byte_len(), 0);
+
+        let transform = ComponentTransform::try_parse(&bytes, 0, WE_HAVE_A_SCALE).unwrap();
+        assert_eq!(transform, ComponentTransform::Scale(0.5));
+        assert_eq!(transform.as_matrix(), [0.5, 0.0, 0.0, 0.5]);
+        assert_eq!(transform.byte_len(), 2);
+
+        let transform = ComponentTransform::try_parse(&bytes, 2, WE_HAVE_AN_X_AND_Y_SCALE).unwrap();
+        assert_eq!(transform.as_matrix(), [-1.0, 0.0, 0.0, -0.5]);
+        assert_eq!(transform.byte_len(), 4);
+
+        let transform = ComponentTransform::try_parse(&bytes, 0, WE_HAVE_A_TWO_BY_TWO).unwrap();
+        assert_eq!(transform.as_matrix(), [0.5, -1.0, -0.5, 1.0]);
+        assert_eq!(transform.byte_len(), 8);
+
+        assert_eq!(
+            ComponentTransform::try_parse(&bytes, 2, WE_HAVE_A_TWO_BY_TWO)
+                .unwrap_err()
+                .kind,
+            error::ImtErrorKind::Truncated
+        );
+    }
+
+    #[test]
+    fn varied_outline() {
+        let font = parse::test();
+        assert!(font.varied_outline(glyph_id(&font, ' '), None).is_none());
+        let glyph_id = glyph_id(&font, 'R');
+        let base = font.glyf_table().outlines.get(&glyph_id).unwrap();
+        assert_eq!(
+            font.varied_outline(glyph_id, None).unwrap().points,
+            base.points
+        );
+
+        let mut coords = font.coords_from([(b"wght", 1000.0)]).unwrap();
+        util::variation::normalize_axis_coords(&font, &mut coords).unwrap();
+
+        let varied = font.varied_outline(glyph_id, Some(&coords)).unwrap();
+        let mut expected = base.as_ref().clone();
+        util::variation::outline_apply_gvar(&font, glyph_id, &mut expected, &coords).unwrap();
+        assert_eq!(varied.points, expected.points);
+        assert_eq!(varied.geometry, expected.geometry);
+        assert!(varied.x_max - varied.x_min > base.x_max - base.x_min);
+
+        // Font units are kept, the scaled outline is normalized to the bitmap instead.
+        let scaled =
+            raster::ScaledGlyph::evaluate(&font, Some(&coords), true, glyph_id, 64.0).unwrap();
+        let scaler = 64.0 / font.head_table().units_per_em as f32;
+        assert!(((varied.x_max - varied.x_min) * scaler - scaled.width as f32).abs() <= 2.0);
+
+        assert!(font.varied_outline(glyph_id, Some(&[2.0; 13])).is_none());
+    }
+
+    #[test]
+    fn prepare_glyph() {
+        let font = parse::test();
+        let mut coords = font.coords_from([(b"wght", 700.0)]).unwrap();
+        util::variation::normalize_axis_coords(&font, &mut coords).unwrap();
+
+        let glyph_id = glyph_id(&font, 'g');
+        let prepared = font.prepare_glyph(glyph_id, Some(&coords), 32.0).unwrap();
+        let scaled =
+            raster::ScaledGlyph::evaluate(&font, Some(&coords), true, glyph_id, 32.0).unwrap();
+
+        assert_eq!(prepared.advance, scaled.advance_w);
+        assert_eq!(prepared.unique_id, scaled.unique_id);
+        // Descender of 'g' extends below the baseline.
+        assert!(prepared.bounds()[1] < 0);
+        assert_eq!(
+            prepared.bounds()[2] - prepared.bounds()[0],
+            scaled.width as i32
+        );
+
+        let rasterized = raster::cpu::CpuRasterizer::new().render(&prepared.into_scaled());
+        assert_eq!(
+            rasterized,
+            raster::cpu::CpuRasterizer::new().render(&scaled)
+        );
+    }
+
+    #[test]
+    fn shape() {
+        let font = parse::test();
+        let features = [parse::Feature::LIGA, parse::Feature::KERN];
+        let hmtx = font.hmtx_table();
+        let advance = |c: char| hmtx.advance_width(glyph_id(&font, c)).unwrap() as f32;
+
+        // Without features there is one glyph per char with the advance from `hmtx`.
+        let text = "ffi\u{e9} To";
+        let shaped = font.shape(text, &[], &[]);
+        assert_eq!(shaped.len(), 7);
+        assert_eq!(
+            shaped.iter().map(|glyph| glyph.cluster).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 5, 6, 7]
+        );
+        assert!(text.chars().zip(shaped.iter()).all(|(c, glyph)| {
+            glyph.glyph_id == glyph_id(&font, c) && glyph.x_advance == advance(c)
+        }));
+
+        // "ffi" forms a single ligature & "To" is kerned by -150 units.
+        let shaped = font.shape(text, &[], &features);
+        assert_eq!(shaped.len(), 5);
+        assert_eq!(
+            shaped.iter().map(|glyph| glyph.cluster).collect::<Vec<_>>(),
+            vec![0, 3, 5, 6, 7]
+        );
+        assert_eq!(shaped[0].glyph_id, 555);
+        assert_eq!(shaped[0].x_advance, hmtx.advance_width(555).unwrap() as f32);
+        assert_eq!(shaped[3].x_advance, advance('T') - 150.0);
+        assert_eq!(shaped[4].x_advance, advance('o'));
+
+        // Features are only applied when requested.
+        let shaped = font.shape("fi", &[], &[parse::Feature::KERN]);
+        assert_eq!(shaped.len(), 2);
+        let shaped = font.shape("To", &[], &[parse::Feature::LIGA]);
+        assert_eq!(shaped[0].x_advance, advance('T'));
+
+        // ZWNJ prevents the ligature & has no advance.
+        let shaped = font.shape("f\u{200c}i", &[], &features);
+        assert_eq!(shaped.len(), 3);
+        assert_eq!(shaped[1].glyph_id, glyph_id(&font, ' '));
+        assert_eq!(shaped[1].x_advance, 0.0);
+
+        // Advances are in font units with the `HVAR` delta, and kerning varies as well.
+        let mut coords = font.coords_from([(b"wght", 800.0)]).unwrap();
+        util::variation::normalize_axis_coords(&font, &mut coords).unwrap();
+        let shaped = font.shape("To", &coords, &[]);
+        let delta = util::variation::advance_width(&font, glyph_id(&font, 'T'), &coords).unwrap();
+        assert!(delta != 0.0);
+        assert_eq!(shaped[0].x_advance, advance('T') + delta);
+
+        let kerned = font.shape("To", &coords, &features);
+        assert!(kerned[0].x_advance < shaped[0].x_advance);
+        assert!(kerned[0].x_advance != shaped[0].x_advance - 150.0);
+
+        // Invalid coordinates are ignored.
+        assert_eq!(
+            font.shape("To", &[2.0], &features),
+            font.shape("To", &[], &features)
+        );
+    }
+
+    #[test]
+    fn default_instance_without_coords() {
+        let font = parse::test();
+        assert!(font.default_is_renderable());
+
+        let defaults = font
+            .fvar_table()
+            .unwrap()
+            .axes
+            .iter()
+            .map(|axis| axis.default_value)
+            .collect::<Vec<_>>();
+
+        let glyph_id = glyph_id(&font, 'R');
+        let without = raster::ScaledGlyph::evaluate(&font, None, true, glyph_id, 40.0).unwrap();
+        let with =
+            raster::ScaledGlyph::evaluate(&font, Some(&defaults), false, glyph_id, 40.0).unwrap();
+
+        assert_eq!(without.width, with.width);
+        assert_eq!(without.height, with.height);
+        assert_eq!(without.advance_w, with.advance_w);
+        assert_eq!(without.unique_id, with.unique_id);
+        assert_eq!(
+            without.outline.unwrap().points,
+            with.outline.unwrap().points
+        );
+
+        let no_glyf = parse::Font::from_bytes_selective(
+            include_bytes!("RobotoFlex.ttf"),
+            parse::TableSet::CMAP,
+        )
+        .unwrap();
+        assert!(!no_glyf.default_is_renderable());
+    }
+
+    #[test]
+    fn evaluate_with_source() {
+        let font = parse::test();
+        let mut coords = font.coords_from([(b"wdth", 151.0)]).unwrap();
+        util::variation::normalize_axis_coords(&font, &mut coords).unwrap();
+
+        let space_id = glyph_id(&font, ' ');
+        let glyph_id = glyph_id(&font, 'S');
+        let (scaled, source) =
+            raster::ScaledGlyph::evaluate_with_source(&font, Some(&coords), true, glyph_id, 24.0)
+                .unwrap();
+        let source = source.unwrap();
+
+        assert_eq!(
+            source.points,
+            font.varied_outline(glyph_id, Some(&coords)).unwrap().points
+        );
+        assert_eq!(source.points.len(), scaled.outline.unwrap().points.len());
+        assert!(source.x_max > 1.0);
+
+        let (space, source) =
+            raster::ScaledGlyph::evaluate_with_source(&font, None, true, space_id, 24.0).unwrap();
+        assert!(space.outline.is_none() && source.is_none());
+    }
+
+    #[test]
+    fn script_systems() {
+        let font = parse::test();
+        let systems = font.script_systems();
+
+        assert_eq!(
+            systems
+                .iter()
+                .map(|system| system.script)
+                .collect::<Vec<_>>(),
+            vec![u32::from_be_bytes(*b"DFLT"), u32::from_be_bytes(*b"latn")]
+        );
+        assert!(systems[1].languages.contains(&u32::from_be_bytes(*b"TRK ")));
+
+        let without = parse::Font::from_bytes_selective(
+            include_bytes!("RobotoFlex.ttf"),
+            parse::TableSet::CMAP,
+        )
+        .unwrap();
+        assert!(without.script_systems().is_empty());
+    }
+
+    #[test]
+    fn outline_to_mesh() {
+        let font = parse::test();
+
+        for c in ['o', 'B', '8', 'R'] {
+            let outline = font.varied_outline(glyph_id(&font, c), None).unwrap();
+            let mesh = outline.to_mesh(1.0);
+            let mut area = 0.0;
+
+            for triangle in mesh.indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+                let triangle_area =
+                    (((b[0] - a[0]) * (c[1] - a[1])) - ((b[1] - a[1]) * (c[0] - a[0]))) / 2.0;
+                assert!(triangle_area >= 0.0);
+                area += triangle_area;
+            }
+
+            // Counters are left open, so the mesh covers the area of the outline.
+            let expected = outline.signed_area().abs();
+            assert!(
+                (area - expected).abs() / expected < 0.01,
+                "{c}: {area} != {expected}"
+            );
+        }
+
+        let mut outline = font.varied_outline(glyph_id(&font, 'l'), None).unwrap();
+        outline.geometry.clear();
+        assert_eq!(outline.to_mesh(1.0), raster::mesh::Mesh::default());
+    }
+
+    /// A collection of RobotoFlex alone, returning the offset of its table directory.
+    fn single_font_collection() -> (Vec<u8>, usize) {
+        let font_bytes = include_bytes!("RobotoFlex.ttf");
+        let table_count = parse::TableDirectory::try_parse(font_bytes, 0)
+            .unwrap()
+            .table_records
+            .len();
+
+        // The table directory follows the header.
+        let mut bytes = b"ttcf\x00\x01\x00\x00\x00\x00\x00\x01\x00\x00\x00\x10".to_vec();
+        let offset = bytes.len();
+        bytes.extend_from_slice(font_bytes);
+
+        for i in 0..table_count {
+            let record_offset = offset + 12 + (i * 16) + 8;
+            let table_offset = u32::from_be_bytes(
+                bytes[record_offset..(record_offset + 4)]
+                    .try_into()
+                    .unwrap(),
+            );
+            bytes[record_offset..(record_offset + 4)]
+                .copy_from_slice(&(table_offset + offset as u32).to_be_bytes());
+        }
+
+        (bytes, offset)
+    }
+
+    #[test]
+    fn from_bytes_at_offset() {
+        let (bytes, offset) = single_font_collection();
+
+        assert_eq!(
+            parse::TTCHeader::try_parse(&bytes)
+                .unwrap()
+                .table_directory_offsets,
+            vec![offset as u32]
+        );
+
+        let font = parse::Font::from_bytes_at(&bytes, offset, parse::TableSet::default()).unwrap();
+        let expected = parse::test();
+        let glyph_id = glyph_id(&font, 'g');
+        assert_eq!(font.num_glyphs(), expected.num_glyphs());
+        assert_eq!(
+            font.varied_outline(glyph_id, None).unwrap().points,
+            expected.varied_outline(glyph_id, None).unwrap().points
+        );
+    }
+
+    #[test]
+    fn required_glyphs() {
+        let font = parse::test();
+        let percent = glyph_id(&font, '%');
+        let components = font.glyf_table().components.get(&percent).unwrap().clone();
+        assert!(!components.is_empty());
+
+        let required = font.required_glyphs("a%a");
+        assert!(required.contains(&glyph_id(&font, 'a')));
+        assert!(required.contains(&percent));
+        assert!(components
+            .iter()
+            .all(|component| required.contains(component)));
+        assert!(!required.contains(&0));
+
+        assert!(font.required_glyphs("a\u{10FFFF}").contains(&0));
+        assert!(font.required_glyphs("").is_empty());
+    }
+
+    #[test]
+    fn font_flavor() {
+        assert_eq!(parse::test().flavor(), parse::FontFlavor::TrueType);
+
+        // Apple's `true` tag is TrueType as well.
+        let mut bytes = include_bytes!("RobotoFlex.ttf").to_vec();
+        bytes[0..4].copy_from_slice(b"true");
+        let font = parse::Font::from_bytes(&bytes).unwrap();
+        assert_eq!(font.flavor(), parse::FontFlavor::TrueType);
+
+        let (bytes, offset) = single_font_collection();
+        let font = parse::Font::from_bytes_at(&bytes, offset, parse::TableSet::HMTX).unwrap();
+        assert_eq!(font.flavor(), parse::FontFlavor::Collection);
+    }
+
+    #[test]
+    fn cmap_subtable_fuzz() {
+        // Deterministic xorshift, so failures are reproducible.
+        let mut state = 0x2545f4914f6cdd1d_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+
+        let font_bytes = include_bytes!("RobotoFlex.ttf");
+        let cmap_offset = parse::TableDirectory::try_parse(font_bytes, 0)
+            .unwrap()
+            .table_records
+            .iter()
+            .find(|record| record.table_tag == parse::table_tag::CMAP)
+            .unwrap()
+            .offset as usize;
+
+        // The first encoding record of RobotoFlex is a format 4 subtable.
+        let subtable_offset = cmap_offset
+            + u32::from_be_bytes(
+                font_bytes[(cmap_offset + 8)..(cmap_offset + 12)]
+                    .try_into()
+                    .unwrap(),
+            ) as usize;
+        let subtable_length = u16::from_be_bytes(
+            font_bytes[(subtable_offset + 2)..(subtable_offset + 4)]
+                .try_into()
+                .unwrap(),
+        ) as usize;
+        let subtable = &font_bytes[subtable_offset..(subtable_offset + subtable_length)];
+        assert!(parse::CmapSubtable::try_parse(subtable, 0).is_ok());
+
+        for _ in 0..2000 {
+            let mut bytes = subtable.to_vec();
+
+            for _ in 0..((next() % 8) + 1) {
+                let index = next() % bytes.len();
+                bytes[index] = next() as u8;
+            }
+
+            if next() % 4 == 0 {
+                bytes.truncate(next() % bytes.len());
+            }
+
+            let _ = parse::CmapSubtable::try_parse(&bytes, 0);
+        }
+
+        for _ in 0..2000 {
+            let mut bytes = vec![0, 4];
+            bytes.extend((0..(next() % 64)).map(|_| next() as u8));
+            let _ = parse::CmapSubtable::try_parse(&bytes, 0);
+        }
+
+        // The largest segment count with idRangeOffset pointing past the end of the data.
+        let seg_count = 0x7fff_usize;
+        let mut bytes = vec![0, 4, 0, 0, 0, 0, 0xff, 0xfe, 0, 0, 0, 0, 0, 0];
+        bytes.resize(16 + (seg_count * 8), 0);
+        let end_codes = 14;
+        let start_codes = end_codes + (seg_count * 2) + 2;
+        let id_range_offsets = start_codes + (seg_count * 4);
+        bytes[end_codes..(end_codes + 2)].copy_from_slice(&[0, 1]);
+        bytes[id_range_offsets..(id_range_offsets + 2)].copy_from_slice(&[0xff, 0xff]);
+
+        // The final segment must end at 0xFFFF.
+        let last = (seg_count - 1) * 2;
+        bytes[(end_codes + last)..(end_codes + last + 2)].copy_from_slice(&[0xff, 0xff]);
+        bytes[(start_codes + last)..(start_codes + last + 2)].copy_from_slice(&[0xff, 0xff]);
+
+        assert_eq!(
+            parse::CmapSubtable::try_parse(&bytes, 0).unwrap_err().kind,
+            error::ImtErrorKind::Malformed
+        );
+        assert!(parse::CmapSubtable::try_parse(&bytes, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn glyph_count_with_outlines() {
+        let font = parse::test();
+        let count = font.glyph_count_with_outlines().unwrap();
+        assert!(count > 0);
+        assert!(count < font.num_glyphs() as usize);
+        assert!(!font
+            .glyf_table()
+            .outlines
+            .contains_key(&glyph_id(&font, ' ')));
+    }
+
+    #[test]
+    fn replacement_glyph() {
+        let font = parse::test();
+        let replacement = font.replacement_glyph();
+        assert_ne!(replacement, 0);
+        assert_eq!(replacement, glyph_id(&font, '\u{FFFD}'));
+    }
+
+    #[test]
+    fn shared_outlines() {
+        // `gvar` is excluded as the variations of a glyph don't apply to the data of another.
+        let tables = parse::TableSet::CMAP | parse::TableSet::HMTX | parse::TableSet::GLYF;
+        let font =
+            parse::Font::from_bytes_selective(include_bytes!("RobotoFlex.ttf"), tables).unwrap();
+        assert_eq!(font.glyf_table().shared_outline_count(), 0);
+
+        let table_offset = |table_tag| {
+            parse::TableDirectory::try_parse(include_bytes!("RobotoFlex.ttf"), 0)
+                .unwrap()
+                .table_records
+                .iter()
+                .find(|record| record.table_tag == table_tag)
+                .unwrap()
+                .offset as usize
+        };
+
+        let (loca_offset, glyf_offset) = (
+            table_offset(parse::table_tag::LOCA),
+            table_offset(parse::table_tag::GLYF),
+        );
+
+        let short = font.head_table().index_to_loc_format == 0;
+        let entry_offset = |i: usize| loca_offset + (i * if short { 2 } else { 4 });
+        let read_loca = |bytes: &[u8], i: usize| -> usize {
+            let at = entry_offset(i);
+
+            if short {
+                u16::from_be_bytes([bytes[at], bytes[at + 1]]) as usize * 2
+            } else {
+                u32::from_be_bytes(bytes[at..(at + 4)].try_into().unwrap()) as usize
+            }
+        };
+
+        let write_loca = |bytes: &mut [u8], i: usize, offset: usize| {
+            let at = entry_offset(i);
+
+            if short {
+                bytes[at..(at + 2)].copy_from_slice(&((offset / 2) as u16).to_be_bytes());
+            } else {
+                bytes[at..(at + 4)].copy_from_slice(&(offset as u32).to_be_bytes());
+            }
+        };
+
+        // The glyph data of the last glyph with an outline is followed by that of no other glyph,
+        // so its range can change without affecting the others.
+        let bytes = include_bytes!("RobotoFlex.ttf").to_vec();
+        let num_glyphs = font.num_glyphs() as usize;
+        let last = (0..num_glyphs)
+            .rev()
+            .find(|i| read_loca(&bytes, *i) != read_loca(&bytes, i + 1))
+            .unwrap();
+
+        // Another glyph whose data fits in place of that of the last one.
+        let last_start = read_loca(&bytes, last);
+        let length = |i: usize| read_loca(&bytes, i + 1) - read_loca(&bytes, i);
+        let other = (0..last)
+            .find(|i| length(*i) != 0 && length(*i) <= length(last))
+            .unwrap();
+        let [other_start, other_end] = [read_loca(&bytes, other), read_loca(&bytes, other + 1)];
+
+        let with_last = |start: usize, end: usize, data: Option<usize>| {
+            let mut bytes = bytes.clone();
+
+            if let Some(data_start) = data {
+                bytes.copy_within(
+                    (glyf_offset + other_start)..(glyf_offset + other_end),
+                    glyf_offset + data_start,
+                );
+            }
+
+            write_loca(&mut bytes, last, start);
+
+            for i in (last + 1)..=num_glyphs {
+                write_loca(&mut bytes, i, end);
+            }
+
+            parse::Font::from_bytes_selective(&bytes, tables).unwrap()
+        };
+
+        // The last glyph points at the glyph data of the other.
+        let shared = with_last(other_start, other_end, None);
+        let outlines = &shared.glyf_table().outlines;
+        assert!(std::sync::Arc::ptr_eq(
+            &outlines[&(other as u16)],
+            &outlines[&(last as u16)]
+        ));
+        assert_eq!(shared.glyf_table().shared_outline_count(), 1);
+
+        // The last glyph has a copy of the glyph data of the other, so its outline is equal but
+        // not shared. The difference is the memory saved.
+        let copied = with_last(
+            last_start,
+            last_start + (other_end - other_start),
+            Some(last_start),
+        );
+        let outlines = &copied.glyf_table().outlines;
+        assert_eq!(
+            outlines[&(other as u16)].geometry,
+            outlines[&(last as u16)].geometry
+        );
+        assert_eq!(copied.glyf_table().shared_outline_count(), 0);
+        assert!(shared.memory_estimate() < copied.memory_estimate());
+
+        // Only the start is equal to that of the other, so the data isn't known to be the same.
+        let overlapping = with_last(other_start, other_end + 2, None);
+        assert_eq!(overlapping.glyf_table().shared_outline_count(), 0);
+    }
+
+    /// RobotoFlex with the glyph of `a` pointing at the glyph data of `o`, without `gvar`.
+    fn font_with_a_as_o() -> parse::Font {
+        let tables = parse::TableSet::CMAP | parse::TableSet::HMTX | parse::TableSet::GLYF;
+        let font =
+            parse::Font::from_bytes_selective(include_bytes!("RobotoFlex.ttf"), tables).unwrap();
+        let o = glyph_id(&font, 'o') as usize;
+        let a = glyph_id(&font, 'a') as usize;
+        let loca_offset = parse::TableDirectory::try_parse(include_bytes!("RobotoFlex.ttf"), 0)
+            .unwrap()
+            .table_records
+            .iter()
+            .find(|record| record.table_tag == parse::table_tag::LOCA)
+            .unwrap()
+            .offset as usize;
+
+        let mut bytes = include_bytes!("RobotoFlex.ttf").to_vec();
+        let entry_size = match font.head_table().index_to_loc_format {
+            0 => 2,
+            _ => 4,
+        };
+
+        bytes.copy_within(
+            (loca_offset + (o * entry_size))..(loca_offset + ((o + 1) * entry_size)),
+            loca_offset + (a * entry_size),
+        );
+
+        parse::Font::from_bytes_selective(&bytes, tables).unwrap()
+    }
+
+    /// `RobotoFlex.ttf` with `modify` applied to the bytes, given the offset of the table record
+    /// of `table_tag` & the offset of the table itself.
+    fn modified_font_bytes(
+        table_tag: u32,
+        modify: impl FnOnce(&mut [u8], usize, usize),
+    ) -> Vec<u8> {
+        let mut bytes = include_bytes!("RobotoFlex.ttf").to_vec();
+        let table_directory = parse::TableDirectory::try_parse(&bytes, 0).unwrap();
+        let record_index = table_directory
+            .table_records
+            .iter()
+            .position(|record| record.table_tag == table_tag)
+            .unwrap();
+
+        let table_offset = table_directory.table_records[record_index].offset as usize;
+        modify(&mut bytes, 12 + (record_index * 16), table_offset);
+        bytes
+    }
+
+    /// `RobotoFlex.ttf` with the length of the table of `table_tag` reduced to `length`.
+    fn truncated_table_bytes(table_tag: u32, length: u32) -> Vec<u8> {
+        modified_font_bytes(table_tag, |bytes, record_offset, _| {
+            bytes[(record_offset + 12)..(record_offset + 16)]
+                .copy_from_slice(&length.to_be_bytes());
+        })
+    }
+
+    #[test]
+    fn malformed_os2_post() {
+        let font =
+            parse::Font::from_bytes(truncated_table_bytes(parse::table_tag::OS2, 8)).unwrap();
+        assert!(font.os2_table().is_none());
+        assert!(font.post_table().is_some());
+        assert_eq!(font.style_sort_key(), (400, false));
+
+        let bytes = modified_font_bytes(parse::table_tag::OS2, |bytes, _, table_offset| {
+            bytes[table_offset..(table_offset + 2)].copy_from_slice(&9_u16.to_be_bytes());
+        });
+
+        let font = parse::Font::from_bytes(&bytes).unwrap();
+        assert!(font.os2_table().is_none());
+
+        let font =
+            parse::Font::from_bytes(truncated_table_bytes(parse::table_tag::POST, 16)).unwrap();
+        assert!(font.post_table().is_none());
+        assert!(font.os2_table().is_some());
+
+        let bytes = modified_font_bytes(parse::table_tag::POST, |bytes, _, table_offset| {
+            bytes[table_offset..(table_offset + 4)].copy_from_slice(&0x00040000_u32.to_be_bytes());
+        });
+
+        let font = parse::Font::from_bytes(&bytes).unwrap();
+        assert!(font.post_table().is_none());
+        assert!(font.line_height(&[], 20.0) > 0.0);
+    }
+
+    /// `RobotoFlex.ttf` with the `STAT` table relabeled as `table_tag`, which it doesn't have,
+    /// and its length set to `length`. `STAT` is not a valid table of any other kind.
+    fn relabeled_stat_bytes(table_tag: u32, length: u32) -> Vec<u8> {
+        modified_font_bytes(parse::tag(b"STAT"), |bytes, record_offset, _| {
+            bytes[record_offset..(record_offset + 4)].copy_from_slice(&table_tag.to_be_bytes());
+            bytes[(record_offset + 12)..(record_offset + 16)]
+                .copy_from_slice(&length.to_be_bytes());
+        })
+    }
+
+    /// `RobotoFlex.ttf` with the `STAT` table replaced by `table` labeled as `table_tag`. `table`
+    /// must not be longer than `STAT`.
+    fn font_bytes_with_table(table_tag: u32, table: &[u8]) -> Vec<u8> {
+        modified_font_bytes(parse::tag(b"STAT"), |bytes, record_offset, table_offset| {
+            bytes[record_offset..(record_offset + 4)].copy_from_slice(&table_tag.to_be_bytes());
+            bytes[(record_offset + 12)..(record_offset + 16)]
+                .copy_from_slice(&(table.len() as u32).to_be_bytes());
+            bytes[table_offset..(table_offset + table.len())].copy_from_slice(table);
+        })
+    }
+
+    #[test]
+    fn malformed_mvar() {
+        let mvar = parse::tag(b"MVAR");
+        let length = parse::TableDirectory::try_parse(include_bytes!("RobotoFlex.ttf"), 0)
+            .unwrap()
+            .table_records
+            .iter()
+            .find(|record| record.table_tag == parse::tag(b"STAT"))
+            .unwrap()
+            .length;
+
+        for length in [length, 8] {
+            let font = parse::Font::from_bytes(relabeled_stat_bytes(mvar, length)).unwrap();
+            assert!(font.mvar_table().is_none());
+            assert_eq!(
+                font.line_height(&[0.5; 13], 20.0),
+                font.line_height(&[], 20.0),
+            );
+        }
+
+        // No value records and an item variation store without regions over `axis_count` axes.
+        let table = |axis_count: u16| -> Vec<u8> {
+            [1, 0, 0, 8, 0, 12, 1, 0, 8, 0, axis_count, 0]
+                .into_iter()
+                .flat_map(u16::to_be_bytes)
+                .collect()
+        };
+
+        let font = parse::Font::from_bytes(font_bytes_with_table(mvar, &table(13))).unwrap();
+        assert!(font.mvar_table().is_some());
+
+        // The item variation store disagrees with the 13 axes of `fvar`.
+        let font = parse::Font::from_bytes(font_bytes_with_table(mvar, &table(12))).unwrap();
+        assert!(font.mvar_table().is_none());
+    }
+
+    #[test]
+    fn malformed_base() {
+        use parse::base_table::baseline_tag;
+
+        let base = parse::tag(b"BASE");
+        let words = |words: &[u16]| words.iter().flat_map(|word| word.to_be_bytes()).collect();
+
+        // Version 1.0 with a horizontal axis defining `ideo` at -120 & `romn` at 0 for `DFLT`.
+        let table: Vec<u8> = [
+            words(&[1, 0, 8, 0, 4, 14, 2]),
+            b"ideoromn".to_vec(),
+            words(&[1]),
+            b"DFLT".to_vec(),
+            words(&[8, 6, 0, 0, 1, 2, 8, 12, 1, -120_i16 as u16, 1, 0]),
+        ]
+        .concat();
+
+        let latn = parse::tag(b"latn");
+        let font = parse::Font::from_bytes(font_bytes_with_table(base, &table)).unwrap();
+        assert!(font.base_table().is_some());
+        assert_eq!(font.baseline_offset(baseline_tag::IDEO, latn), Some(-120));
+        assert_eq!(font.baseline_offset(baseline_tag::ROMN, latn), Some(0));
+
+        // Truncated header.
+        let font = parse::Font::from_bytes(font_bytes_with_table(base, &table[..6])).unwrap();
+        assert!(font.base_table().is_none());
+
+        // Unsupported version.
+        let mut malformed = table.clone();
+        malformed[1] = 2;
+        let font = parse::Font::from_bytes(font_bytes_with_table(base, &malformed)).unwrap();
+        assert!(font.base_table().is_none());
+
+        // Coordinate count not matching the baseline tag count.
+        let mut malformed = table.clone();
+        malformed[39] = 1;
+        let font = parse::Font::from_bytes(font_bytes_with_table(base, &malformed)).unwrap();
+        assert!(font.base_table().is_none());
+        assert_eq!(font.baseline_offset(baseline_tag::IDEO, latn), None);
+    }
+
+    /// A 20x20 glyph whose outline is the given polyline, in `0..=1` with `Y` down.
+    fn polyline_glyph(points: &[[f32; 2]]) -> raster::ScaledGlyph {
+        let point = |[x, y]: [f32; 2]| {
+            parse::OutlinePoint {
+                x,
+                y,
+            }
+        };
+
+        raster::ScaledGlyph {
+            width: 20,
+            height: 20,
+            bearing_x: 0,
+            bearing_y: 0,
+            advance_w: 24,
+            advance_w_f32: 24.0,
+            outline: Some(parse::Outline {
+                x_min: 0.0,
+                y_min: 0.0,
+                x_max: 1.0,
+                y_max: 1.0,
+                points: Vec::new(),
+                contours: Vec::new(),
+                geometry: points
+                    .windows(2)
+                    .map(|pair| {
+                        parse::OutlineGeometry::Segment {
+                            p1: point(pair[0]),
+                            p2: point(pair[1]),
+                        }
+                    })
+                    .collect(),
+            }),
+            unique_id: 0,
+        }
+    }
+
+    #[test]
+    fn stroke() {
+        use raster::stroke::{Join, StrokeStyle};
+
+        let rasterizer = raster::cpu::CpuRasterizer::new();
+        let square = polyline_glyph(&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.0, 0.0]]);
+        let style = |width, join| {
+            StrokeStyle {
+                width,
+                join,
+            }
+        };
+
+        // The image grows by half the width on each side, the advance is unchanged.
+        for width in [2.0, 6.0] {
+            let stroked = square.stroke(&style(width, Join::Miter)).unwrap();
+            assert_eq!(stroked.width, 20 + width as u32);
+            assert_eq!(stroked.height, 20 + width as u32);
+            assert_eq!(stroked.bearing_x, -(width as i16 / 2));
+            assert_eq!(stroked.bearing_y, -(width as i16 / 2));
+            assert_eq!(stroked.advance_w, square.advance_w);
+        }
+
+        assert!(square.stroke(&style(0.0, Join::Miter)).is_none());
+        assert!(square.stroke(&style(f32::NAN, Join::Miter)).is_none());
+
+        // The stroke is a band, so the inside of a closed contour is not filled.
+        let stroked = square.stroke(&style(6.0, Join::Miter)).unwrap();
+        let coverage = rasterizer.render(&stroked);
+        let at = |coverage: &[u8], x: usize, y: usize| coverage[(y * 26) + x];
+        assert_eq!(at(&coverage, 13, 13), 0);
+        assert_eq!(at(&coverage, 13, 1), 255);
+        assert_eq!(at(&coverage, 1, 13), 255);
+
+        // The outer 3x3 pixels of a corner are filled by miter, round & bevel in that order.
+        let corner = |join| -> u32 {
+            let coverage = rasterizer.render(&square.stroke(&style(6.0, join)).unwrap());
+            (0..3)
+                .flat_map(|y| (0..3).map(move |x| (x, y)))
+                .map(|(x, y)| at(&coverage, x, y) as u32)
+                .sum()
+        };
+
+        let [miter, round, bevel] = [
+            corner(Join::Miter),
+            corner(Join::Round),
+            corner(Join::Bevel),
+        ];
+        assert!(miter > 9 * 250);
+        assert!(miter > round && round > bevel);
+        assert!(bevel < 6 * 255);
+
+        // A contour that isn't closed is closed by the stroke.
+        let open = polyline_glyph(&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]);
+        let stroked = open.stroke(&style(2.0, Join::Miter)).unwrap();
+        let coverage = rasterizer.render(&stroked);
+        let x = (10 - stroked.bearing_x) as usize;
+        let y = (10 - (20 - (stroked.bearing_y + stroked.height as i16))) as usize;
+        assert_eq!(coverage[(y * stroked.width as usize) + x], 255);
+
+        // The ends of a single line are capped by the join.
+        let line = polyline_glyph(&[[0.2, 0.5], [0.8, 0.5]]);
+
+        for (join, cap) in [(Join::Miter, 0), (Join::Bevel, 0), (Join::Round, 3)] {
+            let stroked = line.stroke(&style(6.0, join)).unwrap();
+            assert_eq!(stroked.width, 12 + (cap * 2));
+            assert_eq!(stroked.height, 6);
+            assert_eq!(stroked.bearing_x, 4 - cap as i16);
+        }
+
+        // Curves are flattened to a tolerance in pixels, so larger glyphs use more segments.
+        let font = parse::test();
+        let segments = |size| {
+            raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, 'o'), size)
+                .unwrap()
+                .stroke(&style(1.0, Join::Bevel))
+                .unwrap()
+                .outline
+                .unwrap()
+                .geometry
+                .len()
+        };
+        assert!(segments(200.0) > segments(12.0));
+    }
+
+    #[test]
+    fn unique_id_per_font() {
+        let font = parse::test();
+        let other = font_with_a_as_o();
+        assert_ne!(font.id(), other.id());
+        assert_eq!(font.clone().id(), font.id());
+
+        let glyph_id = glyph_id(&font, 'a');
+        let a = raster::ScaledGlyph::evaluate(&font, None, true, glyph_id, 24.0).unwrap();
+        let b = raster::ScaledGlyph::evaluate(&other, None, true, glyph_id, 24.0).unwrap();
+        assert_ne!(a.outline.unwrap().points, b.outline.unwrap().points);
+        assert_ne!(a.unique_id, b.unique_id);
+
+        let cloned = raster::ScaledGlyph::evaluate(&font.clone(), None, true, glyph_id, 24.0);
+        assert_eq!(cloned.unwrap().unique_id, a.unique_id);
+    }
+
+    #[test]
+    fn default_ignorable() {
+        assert!(layout::is_default_ignorable('\u{200B}'));
+        assert!(layout::is_default_ignorable('\u{FEFF}'));
+        assert!(!layout::is_default_ignorable(' '));
+        assert!(!layout::is_default_ignorable('a'));
+
+        let font = parse::test();
+        let text = "a\u{200B}b";
+
+        assert_eq!(
+            font.string_advance(text, &[], 24.0, false),
+            font.string_advance("ab", &[], 24.0, false)
+        );
+
+        let shaped = font.shape(text, &[], &[]);
+        assert_eq!(shaped.len(), 3);
+        assert_eq!(shaped[1].glyph_id, glyph_id(&font, ' '));
+        assert_eq!(shaped[1].x_advance, 0.0);
+        assert_eq!(shaped[2].cluster, 4);
+
+        assert!(!font.required_glyphs(text).contains(&0));
+        assert!(font.required_glyphs("\u{FEFF}").is_empty());
+    }
+
+    #[test]
+    fn avar_segment_maps() {
+        let font = parse::test();
+        let fvar = font.fvar_table().unwrap();
+        let avar = font.avar_table().unwrap();
+        assert_eq!(avar.segment_maps.len(), fvar.axes.len());
+
+        // The segment map of `wght` is the identity, while `opsz` is mapped non-linearly.
+        let opsz = fvar
+            .axes
+            .iter()
+            .position(|axis| axis.axis_tag == u32::from_be_bytes(*b"opsz"))
+            .unwrap();
+        assert!(avar.segment_maps[opsz].axis_value_maps.len() > 3);
+
+        let axis = &fvar.axes[opsz];
+        let mut coords: Vec<f32> = fvar.axes.iter().map(|axis| axis.default_value).collect();
+        coords[opsz] = 72.0;
+        util::variation::normalize_axis_coords(&font, &mut coords).unwrap();
+
+        let linear = (72.0 - axis.default_value) / (axis.max_value - axis.default_value);
+        assert!((coords[opsz] - linear).abs() > 0.1);
+
+        // Interpolated between the neighboring maps.
+        let maps = &avar.segment_maps[opsz].axis_value_maps;
+        let k = maps
+            .iter()
+            .rposition(|map| map.from_coord < linear)
+            .unwrap();
+        let t = (linear - maps[k].from_coord) / (maps[k + 1].from_coord - maps[k].from_coord);
+        let expected = maps[k].to_coord + (t * (maps[k + 1].to_coord - maps[k].to_coord));
+        assert!((coords[opsz] - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn trim_run_sidebearings() {
+        let font = parse::test();
+        let glyphs: Vec<_> = ".H."
+            .chars()
+            .map(|c| {
+                raster::ScaledGlyph::evaluate(&font, None, true, glyph_id(&font, c), 48.0).unwrap()
+            })
+            .collect();
+
+        // The period has large sidebearings on both sides.
+        let period = &glyphs[0];
+        let (leading, trailing) = layout::trim_run_sidebearings(&glyphs[..1]);
+        assert!(leading > 2.0 && trailing > 2.0);
+        assert_eq!(leading, period.bearing_x as f32);
+        assert!((leading + period.width as f32 + trailing - period.advance_w_f32).abs() < 1e-4);
+
+        let (run_leading, run_trailing) = layout::trim_run_sidebearings(&glyphs);
+        assert_eq!((run_leading, run_trailing), (leading, trailing));
+        assert_eq!(layout::trim_run_sidebearings(&[]), (0.0, 0.0));
+    }
+
+    #[test]
+    fn hvar_weight_advance() {
+        let font = parse::test();
+        assert!(font.hvar_table().is_some());
+        let glyph_id = glyph_id(&font, 'H');
+
+        let [thin, black] = [100.0, 1000.0].map(|weight| {
+            let mut coords = font.coords_from([(b"wght", weight)]).unwrap();
+            util::variation::normalize_axis_coords(&font, &mut coords).unwrap();
+            assert_ne!(
+                util::variation::advance_width(&font, glyph_id, &coords).unwrap(),
+                0.0
+            );
+
+            raster::ScaledGlyph::evaluate(&font, Some(&coords), true, glyph_id, 48.0)
+                .unwrap()
+                .advance_w_f32
+        });
+
+        assert!(black > thin);
+    }
+
+    #[test]
+    fn typographic_family() {
+        let font = parse::test();
+        let mut name_table = font.name_table().clone();
+
+        // RobotoFlex only has the legacy family names.
+        assert_eq!(name_table.typographic_family(), Some("Roboto Flex"));
+        assert_eq!(name_table.typographic_subfamily(), Some("Regular"));
+
+        for (name_id, name) in [(16, "Roboto Flex"), (17, "Condensed Bold")] {
+            name_table.name_records.push(parse::NameRecord {
+                platform_id: 3,
+                encoding_id: 1,
+                language_id: 0x0409,
+                name_id,
+                name: name.to_string(),
+            });
+        }
+
+        assert_eq!(name_table.typographic_family(), Some("Roboto Flex"));
+        assert_eq!(name_table.typographic_subfamily(), Some("Condensed Bold"));
+    }
+
+    #[test]
+    fn composite_glyphs() {
+        use parse::ComponentPlacement;
+
+        let font = parse::test();
+        let e = glyph_id(&font, 'e');
+        let e_acute = glyph_id(&font, '\u{e9}');
+        let composite = font.glyf_table().composites.get(&e_acute).unwrap();
+        assert_eq!(composite.len(), 2);
+        assert_eq!(composite[0].glyph_id, e);
+
+        let outlines = &font.glyf_table().outlines;
+        let e_outline = outlines.get(&e).unwrap();
+        let acute_outline = outlines.get(&composite[1].glyph_id).unwrap();
+        let outline = outlines.get(&e_acute).unwrap();
+        assert_eq!(
+            outline.points.len(),
+            e_outline.points.len() + acute_outline.points.len()
+        );
+        assert_eq!(
+            outline.contours.len(),
+            e_outline.contours.len() + acute_outline.contours.len()
+        );
+        assert_eq!(
+            outline.points[..e_outline.points.len()],
+            e_outline.points[..]
+        );
+        assert!(outline.y_max > e_outline.y_max);
+
+        // The acute is placed by an offset without a transform.
+        let [x, y] = match composite[1].placement {
+            ComponentPlacement::Offset {
+                x,
+                y,
+            } => [x, y],
+            _ => unreachable!(),
+        };
+
+        for (point, acute_point) in outline.points[e_outline.points.len()..]
+            .iter()
+            .zip(acute_outline.points.iter())
+        {
+            assert_eq!(point.x, acute_point.x + x);
+            assert_eq!(point.y, acute_point.y + y);
+            assert_eq!(point.control, acute_point.control);
+        }
+
+        // Components are varied along with the offsets of the composite.
+        assert!(font.glyph_varies(e_acute));
+        let mut coords = font.coords_from([(b"wght", 900.0)]).unwrap();
+        util::variation::normalize_axis_coords(&font, &mut coords).unwrap();
+        let varied = font.varied_outline(e_acute, Some(&coords)).unwrap();
+        let varied_e = font.varied_outline(e, Some(&coords)).unwrap();
+        assert_eq!(varied.points.len(), outline.points.len());
+        assert_ne!(varied.points, outline.points);
+        assert!((varied.x_max - varied.x_min) > (outline.x_max - outline.x_min));
+
+        let e_delta = varied.points[0].x - varied_e.points[0].x;
+
+        for (point, e_point) in varied.points.iter().zip(varied_e.points.iter()) {
+            assert!((point.x - e_point.x - e_delta).abs() < 1e-3);
+        }
+
+        let glyph =
+            raster::ScaledGlyph::evaluate(&font, Some(&coords), true, e_acute, 24.0).unwrap();
+        assert!(glyph.outline.is_some());
+        assert!(glyph.height > 0);
+    }
+
+    #[test]
+    fn varied_outline_cache() {
+        use std::sync::Arc;
+
+        use util::variation::VariedOutlineCache;
+
+        let uncached = parse::test();
+        let mut font = uncached.clone();
+        let cache = Arc::new(VariedOutlineCache::new(2));
+        font.set_varied_outline_cache(Some(cache.clone()));
+
+        let a = glyph_id(&font, 'a');
+        let b = glyph_id(&font, 'b');
+        let [wide, narrow] = [[0.5; 13], [-0.5; 13]];
+
+        // A miss computes & inserts the outline.
+        let varied = font.varied_outline(a, Some(&wide)).unwrap();
+        assert_eq!(
+            varied.geometry,
+            uncached.varied_outline(a, Some(&wide)).unwrap().geometry
+        );
+        assert_eq!(cache.len(), 1);
+
+        // A hit returns the cached outline without inserting another.
+        let other = font.varied_outline(b, Some(&wide)).unwrap();
+        cache.insert(font.id(), a, &wide, other.clone());
+        assert_eq!(
+            font.varied_outline(a, Some(&wide)).unwrap().geometry,
+            other.geometry
+        );
+        assert_eq!(cache.len(), 2);
+
+        // The default instance is not cached.
+        font.varied_outline(a, None).unwrap();
+        font.varied_outline(a, Some(&[0.0; 13])).unwrap();
+        assert_eq!(cache.len(), 2);
+
+        // Once full the oldest outline is evicted.
+        font.varied_outline(a, Some(&narrow)).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(font.id(), a, &wide).is_none());
+        assert!(cache.get(font.id(), b, &wide).is_some());
+        assert!(cache.get(font.id(), a, &narrow).is_some());
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(
+            font.varied_outline(a, Some(&wide)).unwrap().geometry,
+            varied.geometry
+        );
+
+        // Fonts sharing the cache don't receive each other's outlines.
+        let mut a_as_o = font_with_a_as_o();
+        a_as_o.set_varied_outline_cache(Some(cache.clone()));
+        assert_ne!(
+            a_as_o.varied_outline(a, Some(&wide)).unwrap().geometry,
+            varied.geometry
+        );
+
+        let disabled = VariedOutlineCache::new(0);
+        disabled.insert(font.id(), a, &wide, varied);
+        assert!(disabled.is_empty());
+    }
+
+    #[test]
+    fn composite_variations() {
+        let font = parse::test();
+        let e_acute = glyph_id(&font, '\u{e9}');
+        let mut coords = font.coords_from([(b"wght", 900.0)]).unwrap();
+        util::variation::normalize_axis_coords(&font, &mut coords).unwrap();
+        let default = vec![0.0; coords.len()];
+
+        // The deltas of a composite move its components, not its points.
+        let mut outline = parse::Outline::clone(font.glyf_table().outlines.get(&e_acute).unwrap());
+        util::variation::outline_apply_gvar(&font, e_acute, &mut outline, &coords).unwrap();
+        assert_eq!(
+            outline.points,
+            font.varied_outline(e_acute, Some(&coords)).unwrap().points
+        );
+
+        // Remove the tuples of the composite itself, so only its components vary.
+        let gvar_offset = parse::TableDirectory::try_parse(include_bytes!("RobotoFlex.ttf"), 0)
+            .unwrap()
+            .table_records
+            .iter()
+            .find(|record| record.table_tag == parse::table_tag::GVAR)
+            .unwrap()
+            .offset as usize;
+
+        let mut bytes = include_bytes!("RobotoFlex.ttf").to_vec();
+        let read_u16 = |offset: usize| u16::from_be_bytes([bytes[offset], bytes[offset + 1]]);
+        let read_u32 =
+            |offset: usize| u32::from_be_bytes(bytes[offset..(offset + 4)].try_into().unwrap());
+
+        let data_array_offset = gvar_offset + read_u32(gvar_offset + 16) as usize;
+        let data_offset = data_array_offset
+            + match read_u16(gvar_offset + 14) & 1 {
+                1 => read_u32(gvar_offset + 20 + (e_acute as usize * 4)) as usize,
+                _ => read_u16(gvar_offset + 20 + (e_acute as usize * 2)) as usize * 2,
+            };
+
+        bytes[data_offset..(data_offset + 2)].copy_from_slice(&[0, 0]);
+        let modified = parse::Font::from_bytes(&bytes).unwrap();
+
+        assert!(modified
+            .gvar_table()
+            .unwrap()
+            .glyph_variations
+            .get(&e_acute)
+            .map(|variation| variation.tuples.is_empty())
+            .unwrap_or(true));
+
+        assert!(modified.glyph_varies(e_acute));
+        assert!(!modified.coords_equivalent(e_acute, &default, &coords));
+        assert!(modified.coords_equivalent(e_acute, &coords, &coords));
+        assert_ne!(
+            modified
+                .varied_outline(e_acute, Some(&coords))
+                .unwrap()
+                .points,
+            modified
+                .varied_outline(e_acute, Some(&default))
+                .unwrap()
+                .points
+        );
+    }
+
+    #[test]
+    fn composite_cycle() {
+        // Two composites of one component each that reference each other, and one of itself.
+        let mut bytes = Vec::new();
+
+        for glyph_index in [1_u16, 0, 2] {
+            bytes.extend_from_slice(&(-1_i16).to_be_bytes());
+            bytes.extend_from_slice(&[0; 8]);
+            // ARG_1_AND_2_ARE_WORDS | ARGS_ARE_XY_VALUES
+            bytes.extend_from_slice(&0x0003_u16.to_be_bytes());
+            bytes.extend_from_slice(&glyph_index.to_be_bytes());
+            bytes.extend_from_slice(&[0; 4]);
+        }
+
+        let loca = parse::LocaTable {
+            offsets: vec![0, 18, 36, 54],
+        };
+
+        let glyf = parse::GlyfTable::try_parse(&bytes, 0, &loca).unwrap();
+        assert_eq!(glyf.composites.len(), 3);
+        assert!(glyf.outlines.is_empty());
+    }
+
+    #[test]
+    fn empty_glyf() {
+        let font = parse::test();
+        assert!(font.has_outlines());
+
+        let mut bytes = include_bytes!("RobotoFlex.ttf").to_vec();
+
+        let loca_record = parse::TableDirectory::try_parse(&bytes, 0)
+            .unwrap()
+            .table_records
+            .into_iter()
+            .find(|record| record.table_tag == parse::table_tag::LOCA)
+            .unwrap();
+
+        let start = loca_record.offset as usize;
+        let end = start + loca_record.length as usize;
+        bytes[start..end].fill(0);
+
+        let font = parse::Font::from_bytes(&bytes).unwrap();
+        assert!(!font.has_outlines());
+        assert_eq!(font.glyph_count_with_outlines().unwrap(), 0);
+
+        // Metrics are still available.
+        let glyph_id = glyph_id(&font, 'H');
+        assert!(font.hmtx_table().advance_width(glyph_id).unwrap() > 0);
+        let glyph = raster::ScaledGlyph::evaluate(&font, None, true, glyph_id, 32.0).unwrap();
+        assert!(glyph.outline.is_none());
+        assert!(glyph.advance_w_f32 > 0.0);
+    }
+
+    #[test]
+    fn style_sort_key() {
+        let font = parse::test();
+        assert_eq!(font.style_sort_key(), (400, false));
+
+        let font = parse::Font::from_bytes_selective(
+            include_bytes!("RobotoFlex.ttf"),
+            parse::TableSet::CMAP,
+        )
+        .unwrap();
+        assert!(font.os2_table().is_none());
+        assert_eq!(font.style_sort_key(), (400, false));
+    }
+
+    #[test]
+    fn uniform_layout() {
+        use layout::uniform::{uniform_layout, UniformLayoutParams};
+        use layout::{ImtBody, ImtHoriAlign, ImtHoriBehav, ImtVertAlign, ImtVertBehav};
+
+        let font = parse::test();
+
+        let glyphs = "Hi there"
+            .chars()
+            .map(|c| {
+                raster::ScaledGlyph::evaluate(&font, None, true, glyph_id(&font, c), 20.0).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let line_width = *layout::pen_positions(&glyphs, true).last().unwrap();
+        let line_height = font.line_height(&[], 20.0).round() as i32;
+
+        let layout = |body: ImtBody, hori_behav, hori_align, vert_align| {
+            uniform_layout(UniformLayoutParams {
+                font: &font,
+                size: 20.0,
+                body,
+                hori_behav,
+                hori_align,
+                vert_behav: ImtVertBehav::None,
+                vert_align,
+                glyphs: &glyphs,
+                fractional_advance: true,
+                max_space_stretch: layout::uniform::DEFAULT_MAX_SPACE_STRETCH,
+            })
+        };
+
+        let body = ImtBody {
+            x: 10,
+            y: 20,
+            width: 200,
+            height: 100,
+        };
+
+        let left = layout(
+            body.clone(),
+            ImtHoriBehav::None,
+            ImtHoriAlign::Left,
+            ImtVertAlign::Top,
+        );
+        assert_eq!(left.glyphs.len(), glyphs.len());
+        assert_eq!(left.glyphs[0].x, 10 + glyphs[0].bearing_x as i32);
+        assert_eq!(left.overflow.left, 0);
+        assert_eq!(left.overflow.right, line_width - 200);
+        assert_eq!(left.overflow.top, 0);
+        assert_eq!(left.overflow.bottom, line_height - 100);
+
+        // Glyphs are placed above the baseline of the first line.
+        let h_top = left.glyphs[0].y;
+        assert!(h_top > 20 && h_top + (left.glyphs[0].height as i32) < 20 + line_height);
+
+        let center = layout(
+            body.clone(),
+            ImtHoriBehav::None,
+            ImtHoriAlign::Center,
+            ImtVertAlign::Center,
+        );
+        let shift = (200 - line_width) / 2;
+        assert_eq!(center.glyphs[0].x, left.glyphs[0].x + shift);
+        assert_eq!(center.overflow.left, -shift);
+        assert!((center.overflow.left - center.overflow.right).abs() <= 1);
+        assert!((center.overflow.top - center.overflow.bottom).abs() <= 1);
+
+        let right = layout(
+            body.clone(),
+            ImtHoriBehav::None,
+            ImtHoriAlign::Right,
+            ImtVertAlign::Bottom,
+        );
+        assert_eq!(right.glyphs[0].x, left.glyphs[0].x + 200 - line_width);
+        assert_eq!(right.overflow.right, 0);
+        assert_eq!(right.overflow.bottom, 0);
+        assert_eq!(right.glyphs[0].y, left.glyphs[0].y + 100 - line_height);
+
+        // A single line wider than the body overflows on both sides when centered.
+        let narrow = ImtBody {
+            width: 40,
+            ..body.clone()
+        };
+
+        let overflow = layout(
+            narrow.clone(),
+            ImtHoriBehav::None,
+            ImtHoriAlign::Center,
+            ImtVertAlign::Top,
+        );
+        assert!(overflow.overflow.left > 0 && overflow.overflow.right > 0);
+        assert_eq!(
+            overflow.overflow.left + overflow.overflow.right,
+            line_width - 40
+        );
+
+        // Unless shifted, then it only overflows on the right.
+        let shifted = layout(
+            narrow.clone(),
+            ImtHoriBehav::Shift,
+            ImtHoriAlign::Center,
+            ImtVertAlign::Top,
+        );
+        assert_eq!(shifted.overflow.left, 0);
+        assert_eq!(shifted.overflow.right, line_width - 40);
+
+        // Wrapping places each word on its own line.
+        let word_width = *layout::pen_positions(&glyphs[3..], true).last().unwrap();
+
+        let wrapped = layout(
+            ImtBody {
+                width: word_width as u32,
+                ..body.clone()
+            },
+            ImtHoriBehav::Regular,
+            ImtHoriAlign::Left,
+            ImtVertAlign::Top,
+        );
+        assert_eq!(
+            wrapped.glyphs[3].x,
+            wrapped.glyphs[0].x + glyphs[3].bearing_x as i32 - glyphs[0].bearing_x as i32
+        );
+        assert_eq!(wrapped.glyphs[3].y, left.glyphs[3].y + line_height);
+        assert_eq!(wrapped.overflow.bottom, (line_height * 2) - 100);
+
+        // Without a width the body is a point that lines are aligned to.
+        let point = ImtBody {
+            x: 10,
+            y: 20,
+            width: 0,
+            height: 0,
+        };
+
+        let right = layout(
+            point,
+            ImtHoriBehav::Regular,
+            ImtHoriAlign::Right,
+            ImtVertAlign::Top,
+        );
+        assert_eq!(right.glyphs[0].x, left.glyphs[0].x - line_width);
+        assert_eq!(
+            right.overflow,
+            layout::ImtOverflow {
+                left: 0,
+                right: 0,
+                top: 0,
+                bottom: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn justify_space_stretch() {
+        use layout::uniform::{uniform_layout, UniformLayoutParams};
+        use layout::{ImtBody, ImtHoriAlign, ImtHoriBehav, ImtVertAlign, ImtVertBehav};
+
+        let font = parse::test();
+
+        let glyphs = "ab cd ef"
+            .chars()
+            .map(|c| {
+                raster::ScaledGlyph::evaluate(&font, None, true, glyph_id(&font, c), 20.0).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        // Wide enough for the first two words, leaving space to justify.
+        let width = *layout::pen_positions(&glyphs[..5], true).last().unwrap() + 12;
+
+        let layout = |max_space_stretch| {
+            uniform_layout(UniformLayoutParams {
+                font: &font,
+                size: 20.0,
+                body: ImtBody {
+                    x: 0,
+                    y: 0,
+                    width: width as u32,
+                    height: 0,
+                },
+                hori_behav: ImtHoriBehav::Regular,
+                hori_align: ImtHoriAlign::Justify,
+                vert_behav: ImtVertBehav::None,
+                vert_align: ImtVertAlign::Top,
+                glyphs: &glyphs,
+                fractional_advance: true,
+                max_space_stretch,
+            })
+        };
+
+        let positions = layout::pen_positions(&glyphs[..5], true);
+        let natural_x = |i: usize| positions[i] + glyphs[i].bearing_x as i32;
+
+        // Without a cap the space takes all of the extra width.
+        let uncapped = layout(f32::INFINITY);
+        assert_eq!(uncapped.overflow.right, 0);
+        assert_eq!(uncapped.glyphs[1].x, natural_x(1));
+        assert_eq!(uncapped.glyphs[3].x, natural_x(3) + 12);
+        assert_eq!(uncapped.glyphs[4].x, natural_x(4) + 12);
+
+        // With the default cap a space grows by at most its own advance, the rest is placed
+        // between every glyph of the line.
+        let capped = layout(layout::uniform::DEFAULT_MAX_SPACE_STRETCH);
+        let space = glyphs[2].advance_w_f32;
+        let glyph_extra = (12.0 - space) / 4.0;
+        assert!(space < 12.0);
+        assert_eq!(capped.overflow.right, 0);
+        assert_eq!(
+            capped.glyphs[1].x,
+            natural_x(1) + glyph_extra.round() as i32
+        );
+        assert_eq!(
+            capped.glyphs[3].x,
+            natural_x(3) + (space + (glyph_extra * 3.0)).round() as i32
+        );
+        assert_eq!(capped.glyphs[4].x, natural_x(4) + 12);
+
+        // The last line is not justified.
+        for layout in [&uncapped, &capped] {
+            assert_eq!(layout.glyphs[6].x, glyphs[6].bearing_x as i32);
+            assert_eq!(
+                layout.glyphs[7].x,
+                glyphs[6].advance_w_f32.round() as i32 + glyphs[7].bearing_x as i32
+            );
+        }
+    }
+
+    #[test]
+    fn error_display() {
+        let error = error::ImtError {
+            kind: error::ImtErrorKind::Malformed,
+            source: error::ImtErrorSource::GvarTable,
+        };
+
+        assert_eq!(error.to_string(), "malformed data in GvarTable");
+
+        let boxed: Box<dyn std::error::Error> = Box::new(error);
+        assert_eq!(boxed.to_string(), "malformed data in GvarTable");
+
+        assert_eq!(
+            util::ImtUtilError::InvalidCoords.to_string(),
+            "invalid coordinates"
+        );
+        assert_eq!(
+            raster::ScaledGlyphErr::Missing.to_string(),
+            "glyph data is missing"
+        );
+        assert_eq!(
+            raster::cpu::CpuRasterErr::OutOfBounds.to_string(),
+            "glyph does not fit within the buffer"
+        );
+    }
+
+    #[test]
+    fn glyph_for_char() {
+        let font = parse::test();
+        assert_eq!(font.glyph_for_char('A'), Some(glyph_id(&font, 'A')));
+        assert_eq!(
+            font.glyph_for_char('\u{FFFD}'),
+            Some(glyph_id(&font, '\u{FFFD}'))
+        );
+
+        // Unmapped characters, including those beyond the BMP, are `None` rather than `.notdef`.
+        assert_eq!(font.glyph_for_char('\u{E000}'), None);
+        assert_eq!(font.glyph_for_char('\u{1F600}'), None);
+
+        assert_eq!(
+            font.glyph_ids_for_str("a\u{1F600}b"),
+            [Some(glyph_id(&font, 'a')), None, Some(glyph_id(&font, 'b'))]
+        );
+    }
+
+    #[test]
+    fn contour_bounds() {
+        let font = parse::test();
+        let outline = font.glyf_table().outlines[&glyph_id(&font, 'i')].as_ref();
+        let bounds = outline.contour_bounds();
+        assert_eq!(bounds.len(), outline.contours.len());
+        assert_eq!(bounds.len(), 2);
+
+        // The dot is above the stem.
+        let (stem, dot) = if bounds[0].1 < bounds[1].1 {
+            (bounds[0], bounds[1])
+        } else {
+            (bounds[1], bounds[0])
+        };
+
+        assert!(dot.1 > stem.3);
+
+        // Together the contours span the outline.
+        let union = bounds.iter().fold(bounds[0], |a, b| {
+            (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3))
+        });
+
+        assert_eq!(
+            union,
+            (outline.x_min, outline.y_min, outline.x_max, outline.y_max)
+        );
+    }
+
+    #[test]
+    fn zero_h_metrics() {
+        let mut bytes = include_bytes!("RobotoFlex.ttf").to_vec();
+
+        let hhea_offset = parse::TableDirectory::try_parse(&bytes, 0)
+            .unwrap()
+            .table_records
+            .iter()
+            .find(|record| record.table_tag == parse::table_tag::HHEA)
+            .unwrap()
+            .offset as usize;
+
+        // numberOfHMetrics
+        bytes[(hhea_offset + 34)..(hhea_offset + 36)].copy_from_slice(&[0, 0]);
+
+        assert_eq!(
+            parse::Font::from_bytes(&bytes).unwrap_err(),
+            error::ImtError {
+                kind: error::ImtErrorKind::Malformed,
+                source: error::ImtErrorSource::HmtxTable,
+            }
+        );
+    }
+
+    #[test]
+    fn variation_axis_count_mismatch() {
+        let mut bytes = include_bytes!("RobotoFlex.ttf").to_vec();
+
+        let gvar_offset = parse::TableDirectory::try_parse(&bytes, 0)
+            .unwrap()
+            .table_records
+            .iter()
+            .find(|record| record.table_tag == parse::table_tag::GVAR)
+            .unwrap()
+            .offset as usize;
+
+        // axisCount, one less than fvar
+        bytes[(gvar_offset + 4)..(gvar_offset + 6)].copy_from_slice(&[0, 12]);
+
+        assert_eq!(
+            parse::Font::from_bytes(&bytes).unwrap_err(),
+            error::ImtError {
+                kind: error::ImtErrorKind::Malformed,
+                source: error::ImtErrorSource::GvarTable,
+            }
+        );
+    }
+
+    /// A queue of a Vulkan device for the tests of the gpu rasterizer.
+    ///
+    /// These tests are `#[ignore]`, run them with `cargo test --features gpu -- --ignored`.
+    /// Without a device they fail instead of passing without testing anything.
+    #[cfg(feature = "gpu")]
+    fn gpu_queue() -> std::sync::Arc<vulkano::device::Queue> {
+        try_gpu_queue().expect("a Vulkan device supporting storage image extended formats")
+    }
+
+    #[cfg(feature = "gpu")]
+    fn try_gpu_queue() -> Option<std::sync::Arc<vulkano::device::Queue>> {
+        use vulkano::device::{Device, DeviceCreateInfo, Features, QueueCreateInfo, QueueFlags};
+        use vulkano::instance::{Instance, InstanceCreateInfo};
+        use vulkano::VulkanLibrary;
+
+        let instance = Instance::new(
+            VulkanLibrary::new().ok()?,
+            InstanceCreateInfo {
+                enumerate_portability: true,
+                ..Default::default()
+            },
+        )
+        .ok()?;
+
+        let (physical_device, queue_family_index) =
+            instance
+                .enumerate_physical_devices()
+                .ok()?
+                .filter(|physical_device| {
+                    physical_device
+                        .supported_features()
+                        .shader_storage_image_extended_formats
+                })
+                .find_map(|physical_device| {
+                    let queue_family_index =
+                        physical_device.queue_family_properties().iter().position(
+                            |properties| properties.queue_flags.intersects(QueueFlags::COMPUTE),
+                        )?;
+
+                    Some((physical_device, queue_family_index as u32))
+                })?;
+
+        let (_device, mut queues) = Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                enabled_features: Features {
+                    shader_storage_image_extended_formats: true,
+                    ..Features::empty()
+                },
+                queue_create_infos: vec![QueueCreateInfo {
+                    queue_family_index,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        )
+        .ok()?;
+
+        queues.next()
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    #[ignore = "requires a Vulkan device, run with --ignored"]
+    fn gpu_downscale_linear() {
+        use parse::{OutlineGeometry, OutlinePoint};
+
+        let queue = gpu_queue();
+
+        let rasterizer = raster::gpu::GpuRasterizer::with_options(
+            queue,
+            raster::gpu::GpuRasterizerOptions {
+                skip_hinting: true,
+                ..Default::default()
+            },
+        );
+
+        // Covers the top two of the four sample rows across the full width of the pixel.
+        let corners = [
+            [-0.05, -0.125],
+            [1.05, -0.125],
+            [1.05, 0.375],
+            [-0.05, 0.375],
+        ];
+        let geometry = (0..4)
+            .map(|i| {
+                OutlineGeometry::Segment {
+                    p1: OutlinePoint {
+                        x: corners[i][0],
+                        y: corners[i][1],
+                    },
+                    p2: OutlinePoint {
+                        x: corners[(i + 1) % 4][0],
+                        y: corners[(i + 1) % 4][1],
+                    },
+                }
+            })
+            .collect();
+
+        let glyph = raster::ScaledGlyph {
+            width: 1,
+            height: 1,
+            bearing_x: 0,
+            bearing_y: 0,
+            advance_w: 1,
+            advance_w_f32: 1.0,
+            outline: Some(parse::Outline {
+                x_min: 0.0,
+                y_min: 0.0,
+                x_max: 1.0,
+                y_max: 1.0,
+                points: Vec::new(),
+                contours: Vec::new(),
+                geometry,
+            }),
+            unique_id: 0,
+        };
+
+        let rastered = rasterizer.process(&[glyph]).pop().unwrap();
+        let pixel = rastered.read_bitmap(&rasterizer)[0];
+
+        for value in &pixel[..3] {
+            assert!((127..=128).contains(value), "{:?}", pixel);
+        }
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    #[ignore = "requires a Vulkan device, run with --ignored"]
+    fn gpu_antialias_disabled() {
+        let queue = gpu_queue();
+
+        let font = parse::test();
+        let glyph =
+            raster::ScaledGlyph::evaluate(&font, None, true, glyph_id(&font, 'o'), 24.0).unwrap();
+
+        for skip_hinting in [false, true] {
+            let rasterizer = raster::gpu::GpuRasterizer::with_options(
+                queue.clone(),
+                raster::gpu::GpuRasterizerOptions {
+                    skip_hinting,
+                    ..Default::default()
+                },
+            );
+
+            let aliased = rasterizer
+                .process_with_antialias(&[glyph.clone()], false)
+                .pop()
+                .unwrap()
+                .read_bitmap(&rasterizer);
+
+            assert!(aliased
+                .iter()
+                .all(|pixel| pixel[..3].iter().all(|value| *value == 0 || *value == 255)));
+            assert!(aliased.iter().any(|pixel| pixel[0] == 255));
+
+            let antialiased = rasterizer
+                .process(&[glyph.clone()])
+                .pop()
+                .unwrap()
+                .read_bitmap(&rasterizer);
+
+            assert!(antialiased
+                .iter()
+                .any(|pixel| pixel[..3].iter().any(|value| *value != 0 && *value != 255)));
+        }
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    #[ignore = "requires a Vulkan device, run with --ignored"]
+    fn gpu_ray_jitter() {
+        use parse::{OutlineGeometry, OutlinePoint};
+
+        let queue = gpu_queue();
+
+        // Thin bars with a period that does not align with the sample grid.
+        let mut geometry = Vec::new();
+
+        for i in 0..23 {
+            let x1 = i as f32 / 23.0;
+            let x2 = x1 + 0.3 / 23.0;
+            let corners = [[x1, 0.0], [x2, 0.0], [x2, 1.0], [x1, 1.0]];
+
+            for j in 0..4 {
+                geometry.push(OutlineGeometry::Segment {
+                    p1: OutlinePoint {
+                        x: corners[j][0],
+                        y: corners[j][1],
+                    },
+                    p2: OutlinePoint {
+                        x: corners[(j + 1) % 4][0],
+                        y: corners[(j + 1) % 4][1],
+                    },
+                });
+            }
+        }
+
+        let glyph = raster::ScaledGlyph {
+            width: 16,
+            height: 16,
+            bearing_x: 0,
+            bearing_y: 0,
+            advance_w: 16,
+            advance_w_f32: 16.0,
+            outline: Some(parse::Outline {
+                x_min: 0.0,
+                y_min: 0.0,
+                x_max: 1.0,
+                y_max: 1.0,
+                points: Vec::new(),
+                contours: Vec::new(),
+                geometry,
+            }),
+            unique_id: 0,
+        };
+
+        let coverage = |ray_jitter| {
+            let rasterizer = raster::gpu::GpuRasterizer::with_options(
+                queue.clone(),
+                raster::gpu::GpuRasterizerOptions {
+                    skip_hinting: true,
+                    ray_jitter,
+                    ..Default::default()
+                },
+            );
+
+            rasterizer
+                .process(&[glyph.clone()])
+                .pop()
+                .unwrap()
+                .read_bitmap(&rasterizer)
+        };
+
+        let regular = coverage(false);
+        let jittered = coverage(true);
+        assert_ne!(regular, jittered);
+        assert_eq!(jittered, coverage(true));
+
+        // Jitter moves samples, but should not change the overall coverage significantly.
+        let sum = |bitmap: &[[u8; 4]]| {
+            bitmap
+                .iter()
+                .map(|pixel| pixel[..3].iter().map(|value| *value as f32).sum::<f32>())
+                .sum::<f32>()
+        };
+
+        let expected = 0.3 * 255.0 * 16.0 * 16.0 * 3.0;
+        assert!((sum(&jittered) - expected).abs() < expected * 0.1);
+        assert!((sum(&regular) - expected).abs() < expected * 0.5);
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    fn glyph_handles() {
+        let mut slots = raster::gpu::pool::HandleSlots::default();
+        let a = slots.insert(1);
+        let b = slots.insert(2);
+        assert_eq!(slots.get(a), Some(&1));
+        assert_eq!(slots.get(b), Some(&2));
+        assert_eq!(slots.len(), 2);
+
+        // The slot of a removed value is reused, but not by its handle.
+        assert_eq!(slots.remove(a), Some(1));
+        assert_eq!(slots.remove(a), None);
+        let c = slots.insert(3);
+        assert_ne!(a, c);
+        assert_eq!(slots.get(a), None);
+        assert_eq!(slots.remove(a), None);
+        assert_eq!(slots.get(c), Some(&3));
+        assert_eq!(slots.len(), 2);
+
+        // Handles remain invalid after clearing, even once slots are reused.
+        slots.clear();
+        assert_eq!(slots.len(), 0);
+        let d = slots.insert(4);
+        let e = slots.insert(5);
+        assert!([b, c].iter().all(|handle| slots.get(*handle).is_none()));
+        assert_eq!(slots.get(d), Some(&4));
+        assert_eq!(slots.get(e), Some(&5));
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    #[ignore = "requires a Vulkan device, run with --ignored"]
+    fn gpu_segment_cache() {
+        let queue = gpu_queue();
+
+        let font = parse::test();
+        let glyphs: Vec<_> = ['a', 'b', 'c']
+            .into_iter()
+            .map(|c| {
+                raster::ScaledGlyph::evaluate(&font, None, true, glyph_id(&font, c), 24.0).unwrap()
+            })
+            .collect();
+
+        let uncached = raster::gpu::GpuRasterizer::new(queue.clone());
+        let cached = raster::gpu::GpuRasterizer::with_options(
+            queue,
+            raster::gpu::GpuRasterizerOptions {
+                segment_cache_capacity: 2,
+                ..Default::default()
+            },
+        );
+
+        let expected: Vec<_> = uncached
+            .process(&glyphs)
+            .into_iter()
+            .map(|rastered| rastered.read_bitmap(&uncached))
+            .collect();
+        assert_eq!(uncached.segment_cache_len(), 0);
+
+        // The second pass uses the cached segment data, which must produce identical bitmaps.
+        for _ in 0..2 {
+            let bitmaps: Vec<_> = cached
+                .process(&glyphs)
+                .into_iter()
+                .map(|rastered| rastered.read_bitmap(&cached))
+                .collect();
+            assert_eq!(bitmaps, expected);
+            assert_eq!(cached.segment_cache_len(), 2);
+        }
+
+        cached.clear_segment_cache();
+        assert_eq!(cached.segment_cache_len(), 0);
+
+        // Glyphs of another font with the same glyph id and size are not served from the cache.
+        let other = font_with_a_as_o();
+        let glyph_id = glyph_id(&font, 'a');
+        let a = raster::ScaledGlyph::evaluate(&font, None, true, glyph_id, 24.0).unwrap();
+        let o = raster::ScaledGlyph::evaluate(&other, None, true, glyph_id, 24.0).unwrap();
+        let expected: Vec<_> = uncached
+            .process(&[a.clone(), o.clone()])
+            .into_iter()
+            .map(|rastered| rastered.read_bitmap(&uncached))
+            .collect();
+        assert_ne!(expected[0], expected[1]);
+
+        for (glyph, expected) in [a, o].into_iter().zip(expected) {
+            let rastered = cached.process(&[glyph]).remove(0);
+            assert_eq!(rastered.read_bitmap(&cached), expected);
+        }
+
+        assert_eq!(cached.segment_cache_len(), 2);
+    }
+
+    #[test]
+    fn space_advance() {
+        let font = parse::test();
+        let space = glyph_id(&font, ' ');
+        let mut coords = vec![0.0; font.fvar_table().unwrap().axes.len()];
+        coords[0] = 0.75;
+
+        for coords in [None, Some(coords.as_slice())] {
+            let scaled = raster::ScaledGlyph::evaluate(&font, coords, true, space, 27.0).unwrap();
+            let advance = font.space_advance(coords, 27.0);
+            assert!((advance - scaled.advance_w_f32).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn outline_path_events() {
+        use parse::PathEvent;
+
+        let font = parse::test();
+
+        for c in ['o', 'B', '8', 'e'] {
+            let outline = font.glyf_table().outlines.get(&glyph_id(&font, c)).unwrap();
+
+            let events: Vec<PathEvent> = outline.path_events().collect();
+            let mut contours = 0;
+            let mut drawn = 0;
+            let mut start = None;
+            let mut current = None;
+
+            for event in events {
+                match event {
+                    PathEvent::MoveTo {
+                        to,
+                    } => {
+                        assert!(start.is_none());
+                        contours += 1;
+                        start = Some(to.clone());
+                        current = Some(to);
+                    },
+                    PathEvent::LineTo {
+                        to,
+                    }
+                    | PathEvent::QuadTo {
+                        to, ..
+                    } => {
+                        assert!(start.is_some());
+                        drawn += 1;
+                        current = Some(to);
+                    },
+                    PathEvent::Close => {
+                        assert_eq!(start.take(), current.take());
+                    },
+                }
+            }
+
+            assert!(start.is_none());
+            assert_eq!(contours, outline.contours.len());
+            assert_eq!(drawn, outline.geometry.len());
+        }
+    }
+
+    #[test]
+    fn render_string_colored() {
+        use raster::cpu::{render_string_colored, CpuRasterizer};
+
+        let font = parse::test();
+        let rasterizer = CpuRasterizer::new();
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+
+        let rendered =
+            render_string_colored(&rasterizer, &font, None, 32.0, "ll", &[red, blue]).unwrap();
+        assert_eq!(
+            rendered.pixels.len(),
+            (rendered.width * rendered.height) as usize
+        );
+
+        let half = rendered.width as usize / 2;
+        let mut channels = [[0_u32; 4]; 2];
+
+        for (i, pixel) in rendered.pixels.iter().enumerate() {
+            let side = usize::from(i % rendered.width as usize >= half);
+
+            for (sum, value) in channels[side].iter_mut().zip(pixel.iter()) {
+                *sum += *value as u32;
+            }
+        }
+
+        assert!(channels[0][0] > 0 && channels[0][2] == 0);
+        assert!(channels[1][2] > 0 && channels[1][0] == 0);
+        assert_eq!(channels[0][0], channels[0][3]);
+        assert_eq!(channels[1][2], channels[1][3]);
+
+        // The last color is repeated for the remaining glyphs.
+        let rendered =
+            render_string_colored(&rasterizer, &font, None, 32.0, "l l", &[red]).unwrap();
+        assert!(rendered
+            .pixels
+            .iter()
+            .all(|pixel| pixel[0] == pixel[3] && pixel[2] == 0));
+        assert!(rendered.pixels.iter().any(|pixel| pixel[3] == 255));
+
+        // Default ignorable characters are skipped, but still take a color.
+        // U+E0001 LANGUAGE TAG is not mapped, so it would otherwise render as `.notdef`.
+        let tag = '\u{E0001}';
+        let plain =
+            render_string_colored(&rasterizer, &font, None, 32.0, "ll", &[red, blue]).unwrap();
+        let ignored = render_string_colored(
+            &rasterizer,
+            &font,
+            None,
+            32.0,
+            &format!("l{tag}l"),
+            &[red, red, blue],
+        )
+        .unwrap();
+
+        assert_eq!(ignored.width, plain.width);
+        assert_eq!(ignored.height, plain.height);
+        assert_eq!(ignored.pixels, plain.pixels);
+    }
+
+    #[test]
+    fn render_string_shadowed() {
+        use raster::cpu::{
+            render_string_colored, render_string_shadowed, CpuRasterizer, ShadowStyle,
+        };
+
+        let font = parse::test();
+        let rasterizer = CpuRasterizer::new();
+        let black = [0, 0, 0, 255];
+        let plain = render_string_colored(&rasterizer, &font, None, 32.0, "l", &[black]).unwrap();
+
+        // A hard shadow offset to the bottom right.
+        let shadow = ShadowStyle {
+            dx: 3,
+            dy: 2,
+            blur: 0,
+            color: [255, 0, 0, 255],
+        };
+
+        let rendered =
+            render_string_shadowed(&rasterizer, &font, None, 32.0, "l", &[black], &shadow).unwrap();
+        assert_eq!(rendered.width, plain.width + 3);
+        assert_eq!(rendered.height, plain.height + 2);
+        assert_eq!(rendered.baseline, plain.baseline);
+
+        // The text is unchanged and on top of the shadow.
+        for y in 0..plain.height {
+            for x in 0..plain.width {
+                let text = plain.pixels[(y * plain.width + x) as usize];
+                let out = rendered.pixels[(y * rendered.width + x) as usize];
+
+                if text[3] == 255 {
+                    assert_eq!(out, text);
+                }
+            }
+        }
+
+        // The shadow is visible beyond the text.
+        assert!(rendered.pixels.contains(&[255, 0, 0, 255]));
+
+        // A blurred shadow enlarges the image on every side and softens its edges.
+        let shadow = ShadowStyle {
+            dx: 0,
+            dy: 0,
+            blur: 2,
+            color: [0, 0, 255, 128],
+        };
+
+        let rendered =
+            render_string_shadowed(&rasterizer, &font, None, 32.0, "l", &[black], &shadow).unwrap();
+        assert_eq!(rendered.width, plain.width + 4);
+        assert_eq!(rendered.height, plain.height + 4);
+        assert_eq!(rendered.baseline, plain.baseline + 2);
+        assert_eq!(rendered.pixels[0], [0, 0, 0, 0]);
+
+        let row = (rendered.height / 2 * rendered.width) as usize;
+        let edge = rendered.pixels[row..(row + rendered.width as usize)]
+            .iter()
+            .find(|pixel| pixel[3] != 0)
+            .unwrap();
+        assert!(edge[2] > 0 && edge[2] < 128);
+        assert_eq!(edge[0], 0);
+
+        // Default ignorable characters don't cast a shadow.
+        let ignored = render_string_shadowed(
+            &rasterizer,
+            &font,
+            None,
+            32.0,
+            "\u{E0001}l",
+            &[black],
+            &shadow,
+        )
+        .unwrap();
+        assert_eq!(ignored.pixels, rendered.pixels);
+    }
+
+    #[test]
+    fn grapheme_clusters() {
+        let text = "e\u{301}x\u{302}\u{323} a";
+        let clusters = layout::grapheme_clusters(text);
+        let clusters: Vec<&str> = clusters.into_iter().map(|range| &text[range]).collect();
+        assert_eq!(clusters, ["e\u{301}", "x\u{302}\u{323}", " ", "a"]);
+
+        assert_eq!(layout::grapheme_clusters("\u{301}e"), [0..2, 2..3]);
+        assert!(layout::grapheme_clusters("").is_empty());
+    }
+
+    #[test]
+    fn head_style() {
+        let font = parse::test();
+        assert_eq!(font.lowest_rec_ppem(), 9);
+        assert!(!font.is_bold());
+        assert!(!font.is_italic());
+    }
+
+    #[test]
+    fn pixel_perfect_hinting() {
+        let font = parse::test();
+        let rasterizer = raster::cpu::CpuRasterizer::new();
+
+        for c in ['H', 'E', 'L', 'T'] {
+            let glyph_id = glyph_id(&font, c);
+            let mut glyph =
+                raster::ScaledGlyph::evaluate(&font, None, false, glyph_id, 13.0 * 3.0).unwrap();
+            let unhinted = rasterizer.render(&glyph);
+            assert!(unhinted.iter().any(|value| *value != 0 && *value != 255));
+
+            let unique_id = glyph.unique_id;
+            glyph
+                .apply_hinting(raster::Hinting::PixelPerfect {
+                    scale: 3,
+                })
+                .unwrap();
+
+            assert_ne!(glyph.unique_id, unique_id);
+            assert_eq!(glyph.width % 3, 0);
+            assert_eq!(glyph.height % 3, 0);
+            assert_eq!(glyph.bearing_x % 3, 0);
+            assert_eq!(glyph.bearing_y % 3, 0);
+            assert_eq!(glyph.advance_w % 3, 0);
+
+            let hinted = rasterizer.render(&glyph);
+            assert!(hinted.iter().all(|value| *value == 0 || *value == 255));
+            assert!(hinted.contains(&255));
+        }
+
+        // Points of curved glyphs are fitted as well, only the curves between them are partially
+        // covered.
+        for c in ['o', 'S'] {
+            let mut glyph =
+                raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, c), 39.0)
+                    .unwrap();
+            glyph
+                .apply_hinting(raster::Hinting::PixelPerfect {
+                    scale: 3,
+                })
+                .unwrap();
+
+            let outline = glyph.outline.as_ref().unwrap();
+            assert!(outline.geometry.iter().any(|geometry| geometry.is_curve()));
+            assert!(outline.points.iter().all(|point| {
+                let x = glyph.bearing_x as f32 + (point.x * glyph.width as f32);
+                let y = glyph.bearing_y as f32 + ((1.0 - point.y) * glyph.height as f32);
+                (x / 3.0 - (x / 3.0).round()).abs() < 1e-3
+                    && (y / 3.0 - (y / 3.0).round()).abs() < 1e-3
+            }));
+            assert_eq!(glyph.width % 3, 0);
+            assert_eq!(glyph.height % 3, 0);
+            assert!(rasterizer.render(&glyph).contains(&255));
+        }
+
+        // An outline without points is left as is.
+        let mut empty =
+            raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, 'l'), 39.0).unwrap();
+        let outline = empty.outline.as_mut().unwrap();
+        outline.points.clear();
+        outline.contours.clear();
+        outline.geometry.clear();
+        let (width, height) = (empty.width, empty.height);
+        empty
+            .apply_hinting(raster::Hinting::PixelPerfect {
+                scale: 3,
+            })
+            .unwrap();
+        assert_eq!((empty.width, empty.height), (width, height));
+        assert_eq!(empty.advance_w % 3, 0);
+
+        // A malformed outline is an error and leaves the glyph unchanged.
+        let mut malformed =
+            raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, 'l'), 40.0).unwrap();
+        let contours = &mut malformed.outline.as_mut().unwrap().contours;
+        contours.truncate(1);
+        contours[0].end = 2;
+        let (unique_id, advance_w_f32) = (malformed.unique_id, malformed.advance_w_f32);
+        assert_eq!(
+            malformed.apply_hinting(raster::Hinting::PixelPerfect {
+                scale: 3,
+            }),
+            Err(raster::ScaledGlyphErr::Malformed)
+        );
+        assert_eq!(malformed.unique_id, unique_id);
+        assert_eq!(malformed.advance_w_f32, advance_w_f32);
+    }
+
+    #[test]
+    fn gvar_shared_tuples() {
+        let font = parse::test();
+        let gvar = font.gvar_table().unwrap();
+        let count = gvar.shared_tuple_count();
+        assert!(count > 0);
+        assert_eq!(gvar.shared_tuples.len(), count * gvar.axis_count);
+        assert!(gvar.shared_tuple(count).is_none());
+
+        for i in 0..count {
+            let tuple = gvar.shared_tuple(i).unwrap();
+            assert_eq!(tuple.len(), gvar.axis_count);
+            assert!(tuple.iter().all(|coord| (-1.0..=1.0).contains(coord)));
+        }
+
+        // Every shared tuple should be referenced as the peak of some glyph's tuple.
+        let referenced = (0..count)
+            .filter(|i| {
+                let shared = gvar.shared_tuple(*i).unwrap();
+
+                gvar.glyph_variations
+                    .values()
+                    .flat_map(|variation| variation.tuples.iter())
+                    .any(|tuple| tuple.peak == shared)
+            })
+            .count();
+
+        assert_eq!(referenced, count);
+    }
+
+    #[test]
+    fn gvar_missing_glyph_variation() {
+        let font = parse::test();
+        let gvar = font.gvar_table().unwrap();
+
+        let unvaried: Vec<u16> = font
+            .glyf_table()
+            .outlines
+            .keys()
+            .copied()
+            .filter(|glyph_id| !gvar.glyph_variations.contains_key(glyph_id))
+            .collect();
+
+        assert!(!unvaried.is_empty());
+
+        let normalized = vec![0.5; gvar.axis_count];
+
+        let max_coords: Vec<f32> = font
+            .fvar_table()
+            .unwrap()
+            .axes
+            .iter()
+            .map(|axis| axis.max_value)
+            .collect();
+
+        for glyph_id in unvaried {
+            let mut outline = font
+                .glyf_table()
+                .outlines
+                .get(&glyph_id)
+                .unwrap()
+                .as_ref()
+                .clone();
+            let points = outline.points.clone();
+            util::variation::outline_apply_gvar(&font, glyph_id, &mut outline, &normalized)
+                .unwrap();
+            assert_eq!(outline.points, points);
+
+            let base = raster::ScaledGlyph::evaluate(&font, None, false, glyph_id, 32.0).unwrap();
+            let varied =
+                raster::ScaledGlyph::evaluate(&font, Some(&max_coords), false, glyph_id, 32.0)
+                    .unwrap();
+
+            assert_eq!(
+                base.segment_data(raster::DEFAULT_CURVE_TOLERANCE),
+                varied.segment_data(raster::DEFAULT_CURVE_TOLERANCE)
+            );
+        }
+    }
+
+    #[test]
+    fn coord_builder_missing_axis() {
+        use util::variation::{CoordBuilder, MissingAxisPolicy};
+
+        let font = parse::test();
+        let axes = &font.fvar_table().unwrap().axes;
+        let wght = axes
+            .iter()
+            .position(|axis| axis.axis_tag == u32::from_be_bytes(*b"wght"))
+            .unwrap();
+
+        let builder = CoordBuilder::new(&font)
+            .set(b"wght", 700.0)
+            .set(b"ABCD", 1.0);
+        let coords = builder.build().unwrap();
+        assert_eq!(coords.len(), axes.len());
+        assert_eq!(coords[wght], 700.0);
+
+        for (i, axis) in axes.iter().enumerate() {
+            if i != wght {
+                assert_eq!(coords[i], axis.default_value);
+            }
+        }
+
+        assert_eq!(
+            builder.on_missing(MissingAxisPolicy::Error).build(),
+            Err(util::ImtUtilError::UnknownAxis)
+        );
+
+        assert_eq!(
+            font.coords_from([(b"ABCD", 1.0)]),
+            Err(util::ImtUtilError::UnknownAxis)
+        );
+    }
+
+    #[test]
+    fn vertical_center_baseline() {
+        let font = parse::test();
+        let size = 40.0;
+        let box_height = 64.0;
+        let baseline = layout::vertical_center_baseline(&font, &[], size, box_height);
+
+        // The ink of a capital letter should be centered within the box.
+        let glyph =
+            raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, 'H'), size).unwrap();
+        let top = baseline - (glyph.bearing_y as f32 + glyph.height as f32);
+        let bottom = baseline - glyph.bearing_y as f32;
+        assert!((top - (box_height - bottom)).abs() <= 2.0);
+    }
+
+    #[test]
+    fn outline_self_intersection() {
+        use parse::glyf_table::OutlineRawPoint;
+
+        let font = parse::test();
+        let outline = font
+            .glyf_table()
+            .outlines
+            .get(&glyph_id(&font, 'o'))
+            .unwrap();
+        assert!(!outline.is_self_intersecting());
+
+        let mut bow_tie = outline.as_ref().clone();
+        bow_tie.points = [[0.0, 0.0], [100.0, 100.0], [100.0, 0.0], [0.0, 100.0]]
+            .into_iter()
+            .map(|[x, y]| {
+                OutlineRawPoint {
+                    c: 0,
+                    x,
+                    y,
+                    control: false,
+                }
+            })
+            .collect();
+        bow_tie.contours.clear();
+        bow_tie.contours.push(0..4);
+        bow_tie.rebuild().unwrap();
+        assert!(bow_tie.is_self_intersecting());
+    }
+
+    #[test]
+    fn degenerate_outline() {
+        use parse::glyf_table::OutlineRawPoint;
+
+        let font = parse::test();
+        let outline = font
+            .glyf_table()
+            .outlines
+            .get(&glyph_id(&font, 'o'))
+            .unwrap();
+        assert!(outline.signed_area().abs() > 1.0);
+
+        let mut square = outline.as_ref().clone();
+        square.points = [[0.0, 0.0], [0.0, 100.0], [100.0, 100.0], [100.0, 0.0]]
+            .into_iter()
+            .map(|[x, y]| {
+                OutlineRawPoint {
+                    c: 0,
+                    x,
+                    y,
+                    control: false,
+                }
+            })
+            .collect();
+        square.contours.clear();
+        square.contours.push(0..4);
+        square.rebuild().unwrap();
+        assert_eq!(square.signed_area(), -10000.0);
+
+        // Turning a corner into a control point removes half of the triangle it cuts off.
+        square.points[2].control = true;
+        square.rebuild().unwrap();
+        assert!((square.signed_area() + 10000.0 - (5000.0 / 3.0)).abs() < 0.01);
+
+        let mut line = square.clone();
+        line.points.iter_mut().for_each(|point| {
+            point.y = 0.0;
+            point.control = false;
+        });
+        line.rebuild().unwrap();
+        assert_eq!(line.signed_area(), 0.0);
+        assert_eq!(line.enclosed_area(), 0.0);
+
+        // Two disjoint squares wound in opposite directions, e.g. from mirrored components.
+        let mut mirrored = square.clone();
+        mirrored.points = [
+            [0.0, 0.0],
+            [0.0, 100.0],
+            [100.0, 100.0],
+            [100.0, 0.0],
+            [200.0, 0.0],
+            [300.0, 0.0],
+            [300.0, 100.0],
+            [200.0, 100.0],
+        ]
+        .into_iter()
+        .map(|[x, y]| {
+            OutlineRawPoint {
+                c: 0,
+                x,
+                y,
+                control: false,
+            }
+        })
+        .collect();
+        mirrored.contours = vec![0..4, 4..8];
+        mirrored.rebuild().unwrap();
+        assert_eq!(mirrored.signed_area(), 0.0);
+        assert_eq!(mirrored.enclosed_area(), 20000.0);
+        assert!((outline.enclosed_area() - outline.signed_area().abs()).abs() > 1.0);
+
+        // Collapse every point of 'l' onto the origin, as a repeated flag without coordinates.
+        let mut bytes = include_bytes!("RobotoFlex.ttf").to_vec();
+        let read_u16 = |bytes: &[u8], i: usize| u16::from_be_bytes([bytes[i], bytes[i + 1]]);
+        let read_u32 = |bytes: &[u8], i: usize| {
+            u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+        };
+
+        let glyf_offset = (0..read_u16(&bytes, 4) as usize)
+            .map(|i| 12 + (i * 16))
+            .find(|record| &bytes[*record..(*record + 4)] == b"glyf")
+            .map(|record| read_u32(&bytes, record + 8) as usize)
+            .unwrap();
+
+        let glyph_id = glyph_id(&font, 'l');
+        let offset = glyf_offset + font.loca_table().offsets[glyph_id as usize] as usize;
+        let number_of_contours = read_u16(&bytes, offset) as usize;
+        let end_pts_end = offset + 10 + (number_of_contours * 2);
+        let number_of_points = read_u16(&bytes, end_pts_end - 2) as usize + 1;
+        let flags_offset = end_pts_end + 2 + read_u16(&bytes, end_pts_end) as usize;
+        bytes[flags_offset] = 0x39;
+        bytes[flags_offset + 1] = (number_of_points - 1) as u8;
+
+        let degenerate = parse::Font::from_bytes(bytes).unwrap();
+        assert_eq!(
+            degenerate
+                .glyf_table()
+                .outlines
+                .get(&glyph_id)
+                .unwrap()
+                .signed_area(),
+            0.0
+        );
+
+        let glyph =
+            raster::ScaledGlyph::evaluate(&degenerate, None, false, glyph_id, 32.0).unwrap();
+        assert!(glyph.outline.is_none());
+        assert_eq!((glyph.width, glyph.height), (0, 0));
+        assert!(glyph.advance_w > 0);
+    }
+
+    #[test]
+    fn glyphs_with_outlines() {
+        let font = parse::test();
+        let glyph_ids = font
+            .glyphs_with_outlines()
+            .map(|(glyph_id, _)| glyph_id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(glyph_ids.len(), font.glyf_table().outlines.len());
+        assert!(glyph_ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(glyph_ids.contains(&glyph_id(&font, 'o')));
+        assert!(!glyph_ids.contains(&glyph_id(&font, ' ')));
+    }
+
+    #[test]
+    fn fractional_advance_drift() {
+        let font = parse::test();
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(5);
+        let glyphs = text
+            .chars()
+            .take(200)
+            .map(|c| {
+                raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, c), 13.5).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let positions = layout::pen_positions(&glyphs, true);
+        assert_eq!(positions.len(), 201);
+        let mut exact = 0.0_f64;
+
+        for (glyph, position) in glyphs.iter().zip(positions[1..].iter()) {
+            exact += glyph.advance_w_f32 as f64;
+            assert!((*position as f64 - exact).abs() < 1.0);
+        }
+
+        // Accumulating rounded advances drifts far more over the same line.
+        let rounded = layout::pen_positions(&glyphs, false);
+        assert!((rounded[200] as f64 - exact).abs() > 10.0);
+    }
+
+    #[test]
+    fn use_my_metrics() {
+        let font = parse::test();
+        let base = glyph_id(&font, 'A');
+        let composite = glyph_id(&font, 'À');
+        assert_eq!(
+            font.glyf_table().use_my_metrics.get(&composite),
+            Some(&base)
+        );
+        assert_eq!(font.metrics_glyph_id(composite), base);
+        assert_eq!(font.metrics_glyph_id(base), base);
+
+        let mut coords = font.fvar_table().unwrap().instances[0].coordinates.clone();
+        util::variation::normalize_axis_coords(&font, &mut coords).unwrap();
+
+        for coords in [None, Some(coords.as_slice())] {
+            // The composite uses the metrics of `A` and its outline has the same width, so both
+            // advance by the same amount.
+            let expected = raster::ScaledGlyph::evaluate(&font, coords, true, base, 20.0)
+                .unwrap()
+                .advance_w_f32;
+            let glyph =
+                raster::ScaledGlyph::evaluate(&font, coords, true, composite, 20.0).unwrap();
+            assert!(glyph.outline.is_some());
+            assert!((glyph.advance_w_f32 - expected).abs() < 0.001);
+            assert_eq!(
+                font.scaled_advance(composite, coords, 20.0),
+                Ok(glyph.advance_w)
+            );
+        }
+    }
+
+    #[test]
+    fn scanline_coverage() {
+        use raster::cpu::CpuRasterizer;
+
+        let font = parse::test();
+
+        for c in ['o', 'A', 'a', 'g'] {
+            let glyph = raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, c), 27.0)
+                .unwrap();
+            let width = glyph.width as usize;
+            let bitmap = CpuRasterizer::new().render(&glyph);
+            let mut total = 0.0;
+
+            for y in 0..glyph.height {
+                let coverage = glyph.scanline_coverage(y);
+                assert_eq!(coverage.len(), width);
+
+                // The CPU rasterizer samples each row vertically, so only roughly agrees.
+                for (x, value) in coverage.iter().enumerate() {
+                    let sampled = bitmap[(y as usize * width) + x] as f32 / 255.0;
+                    assert!((0.0..=1.0).contains(value));
+                    assert!((value - sampled).abs() < 0.1);
+                }
+
+                total += coverage.iter().sum::<f32>();
+            }
+
+            let sampled_total = bitmap
+                .iter()
+                .map(|value| *value as f32 / 255.0)
+                .sum::<f32>();
+            assert!((total - sampled_total).abs() / sampled_total < 0.01);
+        }
+
+        let glyph =
+            raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, 'o'), 27.0).unwrap();
+        assert!(glyph.scanline_coverage(glyph.height).is_empty());
+    }
+
+    #[test]
+    fn gvar_fuzz() {
+        use parse::table_directory::TableDirectory;
+        use parse::GvarTable;
+
+        let bytes = include_bytes!("RobotoFlex.ttf");
+        let font = parse::test();
+        let record = TableDirectory::try_parse(bytes, 0)
+            .unwrap()
+            .table_records
+            .into_iter()
+            .find(|record| record.table_tag == u32::from_be_bytes(*b"gvar"))
+            .unwrap();
+        let gvar = &bytes[(record.offset as usize)..((record.offset + record.length) as usize)];
+        let mut state = 0x2545F4914F6CDD1D_u64;
+
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        // Random data following a valid header.
+        for _ in 0..2000 {
+            let len = 20 + (next() % 512) as usize;
+            let mut data: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+            data[0..4].copy_from_slice(&[0, 1, 0, 0]);
+            data[4..6].copy_from_slice(&[0, (next() % 4) as u8]);
+            let _ = GvarTable::try_parse(&data, 0, font.glyf_table());
+        }
+
+        // Real data with a few bytes corrupted.
+        for _ in 0..32 {
+            let mut data = gvar.to_vec();
+
+            for _ in 0..16 {
+                let i = 4 + (next() as usize % (data.len() - 4));
+                data[i] = next() as u8;
+            }
+
+            let _ = GvarTable::try_parse(&data, 0, font.glyf_table());
+        }
+    }
+
+    #[test]
+    fn pdf_embedding_names() {
+        let font = parse::test();
+        assert_eq!(font.postscript_name(), Some("RobotoFlex-Regular"));
+        assert_eq!(font.unique_font_id(), Some("Google:Roboto Regular:2017"));
+        assert_eq!(font.subset_tag_seed(), 0xC08A8FC6);
+    }
+
+    #[test]
+    fn matching_instance() {
+        use util::variation::normalize_axis_coords;
+
+        let font = parse::test();
+        let normalized = |weight: f32| {
+            let mut coords = font.coords_from([(b"wght", weight)]).unwrap();
+            normalize_axis_coords(&font, &mut coords).unwrap();
+            coords
+        };
+
+        // Instance 3 is Regular and 6 is Bold.
+        assert_eq!(font.matching_instance(&normalized(400.0), 0.0), Some(3));
+        assert_eq!(font.matching_instance(&normalized(700.0), 0.0), Some(6));
+        assert_eq!(font.matching_instance(&normalized(680.0), 0.0), None);
+        // Every instance is within tolerance, the closest is returned.
+        assert_eq!(font.matching_instance(&normalized(680.0), 2.0), Some(6));
+        // RobotoFlex has 13 axes.
+        assert_eq!(font.matching_instance(&[0.0; 12], 2.0), None);
+    }
+
+    #[test]
+    fn cmap_subtable_language() {
+        use parse::cmap_table::{CmapSubtable, EncodingRecord};
+
+        let font = parse::test();
+        let mut cmap = font.cmap_table().clone();
+
+        // RobotoFlex only has the language independent Unicode BMP subtables.
+        let windows = cmap.subtable(3, 1, None).unwrap();
+        assert_eq!(windows.glyph_id_map.len(), 826);
+        assert!(core::ptr::eq(
+            cmap.subtable(3, 1, Some(2)).unwrap(),
+            windows
+        ));
+        assert!(cmap.subtable(1, 0, None).is_none());
+
+        // Macintosh Roman subtables, independent and specific to German (language ID 2).
+        for (language, glyph_id) in [(0, 1), (3, 2)] {
+            cmap.encoding_records.push(EncodingRecord {
+                platform_id: 1,
+                encoding_id: 0,
+                subtable: CmapSubtable {
+                    language,
+                    glyph_id_map: [(0x41, glyph_id)].into_iter().collect(),
+                },
+            });
+        }
+
+        let glyph_id = |language| cmap.subtable(1, 0, language).unwrap().glyph_id_map[&0x41];
+        assert_eq!(glyph_id(None), 1);
+        assert_eq!(glyph_id(Some(2)), 2);
+        assert_eq!(glyph_id(Some(0)), 1);
+
+        // The language of subtables other than Macintosh ones is not a Macintosh language ID.
+        cmap.encoding_records.insert(
+            0,
+            EncodingRecord {
+                platform_id: 3,
+                encoding_id: 1,
+                subtable: CmapSubtable {
+                    language: 3,
+                    glyph_id_map: [(0x41, 4)].into_iter().collect(),
+                },
+            },
+        );
+
+        assert_eq!(cmap.subtable(3, 1, None).unwrap().glyph_id_map[&0x41], 4);
+        assert_eq!(cmap.subtable(3, 1, Some(2)).unwrap().glyph_id_map[&0x41], 4);
+    }
+
+    #[test]
+    fn sample_grid() {
+        let fvar = parse::test().fvar_table().unwrap().clone();
+        let defaults: Vec<f32> = fvar.axes.iter().map(|axis| axis.default_value).collect();
+        assert_eq!(fvar.sample_grid(1, true), vec![defaults.clone()]);
+
+        // wght, wdth, opsz, GRAD & slnt are visible.
+        let grid = fvar.sample_grid(3, false);
+        assert_eq!(grid.len(), 243);
+        assert_eq!(grid[0][..5], [100.0, 25.0, 8.0, -200.0, -10.0]);
+        assert_eq!(grid[1][..5], [100.0, 25.0, 8.0, -200.0, -5.0]);
+        assert_eq!(grid[242][..5], [1000.0, 151.0, 144.0, 150.0, 0.0]);
+
+        // 5^5 exceeds the cap, so slnt is left at its default.
+        let grid = fvar.sample_grid(5, false);
+        assert_eq!(grid.len(), 625);
+        assert!(grid.len() <= parse::fvar_table::SAMPLE_GRID_LIMIT);
+        assert!(grid.iter().all(|coords| coords[4] == defaults[4]));
+    }
+
+    #[test]
+    fn coords_from() {
+        let font = parse::test();
+        let mut expected: Vec<f32> = font
+            .fvar_table()
+            .unwrap()
+            .axes
+            .iter()
+            .map(|axis| axis.default_value)
+            .collect();
+        assert_eq!(font.coords_from([]).unwrap(), expected);
+
+        // wght is the first axis and slnt the fifth.
+        expected[0] = 800.0;
+        expected[4] = -10.0;
+        assert_eq!(
+            font.coords_from([(b"wght", 800.0), (b"slnt", -10.0)])
+                .unwrap(),
+            expected
+        );
+
+        assert_eq!(
+            font.coords_from([(b"wght", 800.0), (b"ital", 1.0)]),
+            Err(util::ImtUtilError::UnknownAxis)
+        );
+    }
+
+    #[test]
+    fn coords_for_instance_name() {
+        let font = parse::test();
+        let bold_italic = &font.fvar_table().unwrap().instances[16];
+        assert_eq!(
+            bold_italic.coordinates[..5],
+            [700.0, 100.0, 14.0, 0.0, -10.0]
+        );
+
+        assert_eq!(
+            font.coords_for_instance_name("bold ITALIC", false),
+            Some(bold_italic.coordinates.clone())
+        );
+
+        let mut normalized = bold_italic.coordinates.clone();
+        util::variation::normalize_axis_coords(&font, &mut normalized).unwrap();
+        assert_eq!(
+            font.coords_for_instance_name("Bold Italic", true),
+            Some(normalized)
+        );
+
+        assert_eq!(font.coords_for_instance_name("Bold Oblique", false), None);
+    }
+
+    #[test]
+    fn has_dsig() {
+        assert!(!parse::test().has_dsig());
+
+        // The contents of `DSIG` are not parsed, only its presence matters.
+        let dsig = parse::tag(b"DSIG");
+        let font = parse::Font::from_bytes(relabeled_stat_bytes(dsig, 8)).unwrap();
+        assert!(font.has_dsig());
+    }
+
+    #[test]
+    fn hostile_layout_tables() {
+        use parse::layout_table::{LayoutTable, LookupSubtable};
+
+        let words = |words: &[u16]| -> Vec<u8> {
+            words.iter().flat_map(|word| word.to_be_bytes()).collect()
+        };
+
+        // A single lookup of `lookup_type` with its subtable at offset 22.
+        let table = |lookup_type: u16, subtable: &[u8]| {
+            [
+                words(&[1, 0, 0, 0, 10, 1, 4, lookup_type, 0, 1, 8]),
+                subtable.to_vec(),
+            ]
+            .concat()
+        };
+
+        // Pair adjustment by 65535 x 65535 classes, both covering every glyph.
+        let pair_pos = |value_format_1: u16| {
+            let class_def = [2, 1, 0, 0xFFFF, 1];
+            let subtable = [
+                &[
+                    2,
+                    16,
+                    value_format_1,
+                    0,
+                    26,
+                    26,
+                    0xFFFF,
+                    0xFFFF,
+                    2,
+                    1,
+                    0,
+                    0xFFFF,
+                    0,
+                ][..],
+                &class_def,
+            ]
+            .concat();
+            LayoutTable::try_parse(
+                &table(2, &words(&subtable)),
+                0,
+                error::ImtErrorSource::GposTable,
+            )
+        };
+
+        // Without values nothing is allocated and the subtable is skipped.
+        let gpos = pair_pos(0).unwrap();
+        assert!(matches!(
+            gpos.lookups[0].subtables[..],
+            [LookupSubtable::Unsupported]
+        ));
+        assert_eq!(
+            pair_pos(0x0004).unwrap_err().kind,
+            error::ImtErrorKind::Truncated
+        );
+
+        // Single substitution by a delta of 2 with 65535 ranges covering every glyph.
+        let mut subtable = words(&[1, 6, 2, 2, 0xFFFF]);
+
+        for _ in 0..0xFFFF {
+            subtable.extend(words(&[0, 0xFFFF, 0]));
+        }
+
+        let gsub =
+            LayoutTable::try_parse(&table(1, &subtable), 0, error::ImtErrorSource::GsubTable)
+                .unwrap();
+
+        match &gsub.lookups[0].subtables[0] {
+            LookupSubtable::SingleSubst(single_subst) => {
+                assert_eq!(single_subst.substitute(1234), Some(1236));
+                assert_eq!(single_subst.substitute(0xFFFF), Some(1));
+            },
+            _ => panic!("expected a single substitution"),
+        }
+    }
+
+    #[test]
+    fn cpu_render_large_curves() {
+        // Curves are flattened to a pixel tolerance, so large glyphs stay close to the reference.
+        let font = parse::test();
+        let glyph =
+            raster::ScaledGlyph::evaluate(&font, None, false, glyph_id(&font, 'O'), 400.0).unwrap();
+        let rasterizer = raster::cpu::CpuRasterizer::new();
+        let fast = rasterizer.render(&glyph);
+        let reference = rasterizer.render_supersampled(&glyph, 4);
+
+        for (fast, reference) in fast.into_iter().zip(reference) {
+            assert!((fast as i32 - reference as i32).abs() <= 48);
+        }
+    }
+
+    #[test]
+    fn cmap_unsupported_subtable() {
+        let mut bytes = include_bytes!("RobotoFlex.ttf").to_vec();
+        let glyph_a = glyph_id(&parse::test(), 'A');
+        let cmap_offset = parse::TableDirectory::try_parse(&bytes, 0)
+            .unwrap()
+            .table_records
+            .iter()
+            .find(|record| record.table_tag == parse::table_tag::CMAP)
+            .unwrap()
+            .offset as usize;
+
+        // Turn the first encoding record into a (3, 10) subtable at offset zero, which is read as
+        // an unsupported format 0 subtable from the version of `cmap`.
+        let record = cmap_offset + 4;
+        bytes[record..(record + 2)].copy_from_slice(&3_u16.to_be_bytes());
+        bytes[(record + 2)..(record + 4)].copy_from_slice(&10_u16.to_be_bytes());
+        bytes[(record + 4)..(record + 8)].copy_from_slice(&0_u32.to_be_bytes());
+
+        let font = parse::Font::from_bytes(&bytes).unwrap();
+        assert_eq!(font.cmap_table().encoding_records.len(), 1);
+        assert!(font.cmap_table().subtable(3, 10, None).is_none());
+        assert_eq!(font.glyph_for_char('A'), Some(glyph_a));
+    }
 }
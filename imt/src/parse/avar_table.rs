@@ -1,5 +1,7 @@
+use alloc::vec::Vec;
+
 use crate::error::*;
-use crate::parse::{read_f2dot14, read_u16};
+use crate::parse::{read_f2dot14, read_u16, read_u32, DeltaSetIndexMap, ItemVariationStore};
 
 const TRUNCATED: ImtError = ImtError {
     kind: ImtErrorKind::Truncated,
@@ -11,11 +13,19 @@ const MALFORMED: ImtError = ImtError {
     source: ImtErrorSource::AvarTable,
 };
 
+/// Corresponds to the `avar` table.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/avar>
 #[derive(Debug, Clone)]
 pub struct AvarTable {
     pub major_version: u16,
     pub minor_version: u16,
     pub segment_maps: Vec<SegmentMap>,
+    /// Maps axes to delta-set indexes of `item_variation_store`. (Version 2)
+    ///
+    /// When absent axes use the outer index of zero and their axis index as the inner index.
+    pub axis_index_map: Option<DeltaSetIndexMap>,
+    /// Deltas applied to the normalized coordinates after the segment maps. (Version 2)
+    pub item_variation_store: Option<ItemVariationStore>,
 }
 
 #[derive(Debug, Clone)]
@@ -38,7 +48,7 @@ impl AvarTable {
         let major_version = read_u16(bytes, table_offset);
         let minor_version = read_u16(bytes, table_offset + 2);
 
-        if major_version != 1 || minor_version != 0 {
+        if (major_version != 1 && major_version != 2) || minor_version != 0 {
             return Err(ImtError {
                 kind: ImtErrorKind::UnexpectedVersion,
                 source: ImtErrorSource::AvarTable,
@@ -80,10 +90,41 @@ impl AvarTable {
             segment_map_offset += 2 + (position_map_count * 4);
         }
 
+        let mut axis_index_map = None;
+        let mut item_variation_store = None;
+
+        if major_version == 2 {
+            if segment_map_offset + 8 > bytes.len() {
+                return Err(TRUNCATED);
+            }
+
+            axis_index_map = match read_u32(bytes, segment_map_offset) {
+                0 => None,
+                offset => {
+                    Some(DeltaSetIndexMap::try_parse(
+                        bytes,
+                        offset as usize + table_offset,
+                    )?)
+                },
+            };
+
+            item_variation_store = match read_u32(bytes, segment_map_offset + 4) {
+                0 => None,
+                offset => {
+                    Some(ItemVariationStore::try_parse(
+                        bytes,
+                        offset as usize + table_offset,
+                    )?)
+                },
+            };
+        }
+
         let avar = Self {
             major_version,
             minor_version,
             segment_maps,
+            axis_index_map,
+            item_variation_store,
         };
 
         for segment_map in avar.segment_maps.iter() {
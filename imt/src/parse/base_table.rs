@@ -0,0 +1,282 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::error::*;
+use crate::parse::{read_i16, read_u16, read_u32};
+
+const TRUNCATED: ImtError = ImtError {
+    kind: ImtErrorKind::Truncated,
+    source: ImtErrorSource::BaseTable,
+};
+
+const MALFORMED: ImtError = ImtError {
+    kind: ImtErrorKind::Malformed,
+    source: ImtErrorSource::BaseTable,
+};
+
+/// Corresponds to the `BASE` table.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/base>
+///
+/// # Notes
+/// - Only the horizontal axis is parsed.
+/// - Device tables and the item variation store of version 1.1 are not parsed.
+#[derive(Debug, Clone)]
+pub struct BaseTable {
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub horizontal: Option<BaseAxis>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BaseAxis {
+    /// Baseline tags such as `baseline_tag::ROMN` sorted alphabetically.
+    pub baseline_tags: Vec<u32>,
+    /// Keyed by script tag.
+    pub scripts: BTreeMap<u32, BaseScript>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BaseScript {
+    /// Index into `baseline_tags` of the baseline used by the script.
+    pub default_baseline_index: Option<usize>,
+    /// Coordinates in font units of each baseline. Length equal to that of `baseline_tags` of
+    /// `BaseAxis` when present, otherwise empty.
+    pub base_coords: Vec<i16>,
+    pub default_min_max: Option<MinMax>,
+    /// Keyed by language system tag.
+    pub lang_sys_min_max: BTreeMap<u32, MinMax>,
+}
+
+/// The extents in font units of the glyphs of a script or language system.
+///
+/// # Notes
+/// - Feature specific extents are not parsed.
+#[derive(Debug, Clone)]
+pub struct MinMax {
+    pub min: Option<i16>,
+    pub max: Option<i16>,
+}
+
+impl BaseTable {
+    pub fn try_parse(bytes: &[u8], table_offset: usize) -> Result<Self, ImtError> {
+        if table_offset + 8 > bytes.len() {
+            return Err(TRUNCATED);
+        }
+
+        let major_version = read_u16(bytes, table_offset);
+        let minor_version = read_u16(bytes, table_offset + 2);
+
+        if major_version != 1 || minor_version > 1 {
+            return Err(ImtError {
+                kind: ImtErrorKind::UnexpectedVersion,
+                source: ImtErrorSource::BaseTable,
+            });
+        }
+
+        let horizontal = match read_u16(bytes, table_offset + 4) {
+            0 => None,
+            offset => Some(BaseAxis::try_parse(bytes, table_offset + offset as usize)?),
+        };
+
+        Ok(Self {
+            major_version,
+            minor_version,
+            horizontal,
+        })
+    }
+}
+
+impl BaseAxis {
+    pub fn try_parse(bytes: &[u8], table_offset: usize) -> Result<Self, ImtError> {
+        if table_offset + 4 > bytes.len() {
+            return Err(TRUNCATED);
+        }
+
+        let tag_list_offset = read_u16(bytes, table_offset) as usize;
+        let script_list_offset = read_u16(bytes, table_offset + 2) as usize;
+
+        // Read BaseTagList
+
+        let mut baseline_tags = Vec::new();
+
+        if tag_list_offset != 0 {
+            let tag_list_offset = table_offset + tag_list_offset;
+
+            if tag_list_offset + 2 > bytes.len() {
+                return Err(TRUNCATED);
+            }
+
+            let tag_count = read_u16(bytes, tag_list_offset) as usize;
+
+            if tag_list_offset + 2 + (tag_count * 4) > bytes.len() {
+                return Err(TRUNCATED);
+            }
+
+            for i in 0..tag_count {
+                baseline_tags.push(read_u32(bytes, tag_list_offset + 2 + (i * 4)));
+            }
+        }
+
+        // Read BaseScriptList
+
+        if script_list_offset == 0 {
+            return Err(MALFORMED);
+        }
+
+        let script_list_offset = table_offset + script_list_offset;
+
+        if script_list_offset + 2 > bytes.len() {
+            return Err(TRUNCATED);
+        }
+
+        let script_count = read_u16(bytes, script_list_offset) as usize;
+
+        if script_list_offset + 2 + (script_count * 6) > bytes.len() {
+            return Err(TRUNCATED);
+        }
+
+        let mut scripts = BTreeMap::new();
+
+        for i in 0..script_count {
+            let record_offset = script_list_offset + 2 + (i * 6);
+            let script_tag = read_u32(bytes, record_offset);
+            let script_offset = script_list_offset + read_u16(bytes, record_offset + 4) as usize;
+
+            scripts.insert(
+                script_tag,
+                BaseScript::try_parse(bytes, script_offset, baseline_tags.len())?,
+            );
+        }
+
+        Ok(Self {
+            baseline_tags,
+            scripts,
+        })
+    }
+}
+
+impl BaseScript {
+    pub fn try_parse(
+        bytes: &[u8],
+        table_offset: usize,
+        baseline_count: usize,
+    ) -> Result<Self, ImtError> {
+        if table_offset + 6 > bytes.len() {
+            return Err(TRUNCATED);
+        }
+
+        let base_values_offset = read_u16(bytes, table_offset) as usize;
+        let default_min_max_offset = read_u16(bytes, table_offset + 2) as usize;
+        let lang_sys_count = read_u16(bytes, table_offset + 4) as usize;
+
+        // Read BaseValues
+
+        let mut default_baseline_index = None;
+        let mut base_coords = Vec::new();
+
+        if base_values_offset != 0 {
+            let base_values_offset = table_offset + base_values_offset;
+
+            if base_values_offset + 4 > bytes.len() {
+                return Err(TRUNCATED);
+            }
+
+            let default_index = read_u16(bytes, base_values_offset) as usize;
+            let coord_count = read_u16(bytes, base_values_offset + 2) as usize;
+
+            if coord_count != baseline_count || default_index >= coord_count {
+                return Err(MALFORMED);
+            }
+
+            if base_values_offset + 4 + (coord_count * 2) > bytes.len() {
+                return Err(TRUNCATED);
+            }
+
+            for i in 0..coord_count {
+                let coord_offset =
+                    base_values_offset + read_u16(bytes, base_values_offset + 4 + (i * 2)) as usize;
+                base_coords.push(read_base_coord(bytes, coord_offset)?);
+            }
+
+            default_baseline_index = Some(default_index);
+        }
+
+        // Read MinMax's
+
+        let default_min_max = match default_min_max_offset {
+            0 => None,
+            offset => Some(MinMax::try_parse(bytes, table_offset + offset)?),
+        };
+
+        if table_offset + 6 + (lang_sys_count * 6) > bytes.len() {
+            return Err(TRUNCATED);
+        }
+
+        let mut lang_sys_min_max = BTreeMap::new();
+
+        for i in 0..lang_sys_count {
+            let record_offset = table_offset + 6 + (i * 6);
+            let lang_sys_tag = read_u32(bytes, record_offset);
+            let min_max_offset = table_offset + read_u16(bytes, record_offset + 4) as usize;
+            lang_sys_min_max.insert(lang_sys_tag, MinMax::try_parse(bytes, min_max_offset)?);
+        }
+
+        Ok(Self {
+            default_baseline_index,
+            base_coords,
+            default_min_max,
+            lang_sys_min_max,
+        })
+    }
+}
+
+impl MinMax {
+    pub fn try_parse(bytes: &[u8], table_offset: usize) -> Result<Self, ImtError> {
+        if table_offset + 4 > bytes.len() {
+            return Err(TRUNCATED);
+        }
+
+        let min = match read_u16(bytes, table_offset) {
+            0 => None,
+            offset => Some(read_base_coord(bytes, table_offset + offset as usize)?),
+        };
+
+        let max = match read_u16(bytes, table_offset + 2) {
+            0 => None,
+            offset => Some(read_base_coord(bytes, table_offset + offset as usize)?),
+        };
+
+        Ok(Self {
+            min,
+            max,
+        })
+    }
+}
+
+/// Read the coordinate of a `BaseCoord` table. All formats share the same leading fields.
+fn read_base_coord(bytes: &[u8], offset: usize) -> Result<i16, ImtError> {
+    if offset + 4 > bytes.len() {
+        return Err(TRUNCATED);
+    }
+
+    match read_u16(bytes, offset) {
+        1..=3 => Ok(read_i16(bytes, offset + 2)),
+        _ => Err(MALFORMED),
+    }
+}
+
+/// Common baseline tags used by the `BASE` table.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/baselinetags>
+pub mod baseline_tag {
+    use crate::parse::tag;
+    /// Hanging baseline used by scripts such as Devanagari and Tibetan.
+    pub const HANG: u32 = tag(b"hang");
+    /// Bottom of the ideographic em-box.
+    pub const IDEO: u32 = tag(b"ideo");
+    /// Top of the ideographic em-box.
+    pub const IDTP: u32 = tag(b"idtp");
+    /// Math characters are centered on this baseline.
+    pub const MATH: u32 = tag(b"math");
+    /// Baseline used by Latin, Greek, Cyrillic and similar scripts.
+    pub const ROMN: u32 = tag(b"romn");
+}
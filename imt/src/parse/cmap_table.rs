@@ -1,4 +1,5 @@
-use std::collections::BTreeMap;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
 
 use crate::error::*;
 
@@ -34,11 +35,16 @@ impl CmapTable {
         let mut encoding_records = Vec::with_capacity(num_tables as usize);
 
         for table_i in 0..(num_tables as usize) {
-            encoding_records.push(EncodingRecord::try_parse(
-                bytes,
-                base_offset,
-                base_offset + 4 + (table_i * 8),
-            )?);
+            match EncodingRecord::try_parse(bytes, base_offset, base_offset + 4 + (table_i * 8)) {
+                Ok(encoding_record) => encoding_records.push(encoding_record),
+                // Subtables of formats that aren't parsed (e.g. format 12) are skipped, so that
+                // the remaining subtables of the font can still be used.
+                Err(ImtError {
+                    kind: ImtErrorKind::FormatNotSupported,
+                    ..
+                }) => (),
+                Err(e) => return Err(e),
+            }
         }
 
         Ok(Self {
@@ -46,6 +52,49 @@ impl CmapTable {
             encoding_records,
         })
     }
+
+    /// Find the subtable for the provided platform and encoding.
+    ///
+    /// # Notes
+    /// - `language` is a Macintosh language ID and only applies to Macintosh (platform 1)
+    ///   subtables, subtables of other platforms are always language-independent. When provided,
+    ///   a subtable specific to that language is preferred over a language-independent one.
+    ///   Subtables specific to other languages are never returned.
+    /// - When `language` is `None` only language-independent subtables are returned.
+    pub fn subtable(
+        &self,
+        platform_id: u16,
+        encoding_id: u16,
+        language: Option<u16>,
+    ) -> Option<&CmapSubtable> {
+        let mut independent = None;
+
+        for record in self.encoding_records.iter() {
+            if record.platform_id != platform_id || record.encoding_id != encoding_id {
+                continue;
+            }
+
+            let record_language = match platform_id {
+                1 => record.subtable.mac_language_id(),
+                _ => None,
+            };
+
+            match record_language {
+                Some(record_language) => {
+                    if Some(record_language) == language {
+                        return Some(&record.subtable);
+                    }
+                },
+                None => {
+                    if independent.is_none() {
+                        independent = Some(&record.subtable);
+                    }
+                },
+            }
+        }
+
+        independent
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -92,13 +141,82 @@ impl EncodingRecord {
 
 #[derive(Debug, Clone)]
 pub struct CmapSubtable {
+    /// For Macintosh subtables this is the Macintosh language ID plus one, or zero when the
+    /// subtable is not language-specific. For all other platforms it is zero.
     pub language: u16,
     pub glyph_id_map: BTreeMap<u16, u16>,
 }
 
 impl CmapSubtable {
+    /// The Macintosh language ID of this subtable if it is language-specific.
+    ///
+    /// # Notes
+    /// - Only meaningful for Macintosh (platform 1) subtables, as other platforms don't define
+    ///   languages for `cmap`.
+    pub fn mac_language_id(&self) -> Option<u16> {
+        self.language.checked_sub(1)
+    }
+
+    /// Map every `char` of `s` to a glyph id, appending them to `out`.
+    ///
+    /// Returns the amount of `char`'s that are not mapped. These are mapped to glyph id zero
+    /// (`.notdef`) so that `out` always gains one glyph id per `char`.
+    ///
+    /// # Notes
+    /// - Lookups continue from the previous `char` when it is close by, so runs of nearby
+    ///   codepoints (e.g. text of a single script) avoid searching the map from the root.
+    pub fn map_str(&self, s: &str, out: &mut Vec<u16>) -> usize {
+        const MAX_ADVANCE: usize = 8;
+
+        let mut unmapped = 0;
+        let mut cursor = self.glyph_id_map.range(..);
+        // The first entry of the map at or after `last`.
+        let mut current: Option<(&u16, &u16)> = None;
+        let mut last = None;
+        out.reserve(s.len());
+
+        for c in s.chars() {
+            let code = match u16::try_from(c as u32) {
+                Ok(ok) => ok,
+                Err(_) => {
+                    out.push(0);
+                    unmapped += 1;
+                    continue;
+                },
+            };
+
+            let resumable = matches!(last, Some(last) if last <= code);
+
+            if resumable {
+                for _ in 0..MAX_ADVANCE {
+                    match current {
+                        Some((key, _)) if *key < code => current = cursor.next(),
+                        _ => break,
+                    }
+                }
+            }
+
+            if !resumable || matches!(current, Some((key, _)) if *key < code) {
+                cursor = self.glyph_id_map.range(code..);
+                current = cursor.next();
+            }
+
+            last = Some(code);
+
+            match current {
+                Some((key, glyph_id)) if *key == code => out.push(*glyph_id),
+                _ => {
+                    out.push(0);
+                    unmapped += 1;
+                },
+            }
+        }
+
+        unmapped
+    }
+
     pub fn try_parse(bytes: &[u8], base_offset: usize) -> Result<Self, ImtError> {
-        if base_offset + 2 > bytes.len() {
+        if bytes.len() < 2 || base_offset > bytes.len() - 2 {
             return Err(ImtError {
                 kind: ImtErrorKind::Truncated,
                 source: ImtErrorSource::CmapSubtable,
@@ -109,7 +227,7 @@ impl CmapSubtable {
 
         match format {
             4 => {
-                if base_offset + 14 > bytes.len() {
+                if bytes.len() < 14 || base_offset > bytes.len() - 14 {
                     return Err(ImtError {
                         kind: ImtErrorKind::Truncated,
                         source: ImtErrorSource::CmapSubtable,
@@ -208,7 +326,7 @@ impl CmapSubtable {
                 }
 
                 let mut glyph_id_map = BTreeMap::new();
-                let mut previous_code = 0;
+                let mut previous_code: Option<u16> = None;
 
                 for i in 0..segments.len() {
                     let mut s = segments[i].start_code;
@@ -221,8 +339,14 @@ impl CmapSubtable {
                         });
                     }
 
-                    if s <= previous_code {
-                        s = previous_code + 1;
+                    // Overlapping segments only map codes not mapped by a previous segment.
+                    if let Some(previous_code) = previous_code {
+                        if s <= previous_code {
+                            s = match previous_code.checked_add(1) {
+                                Some(s) => s,
+                                None => continue,
+                            };
+                        }
                     }
 
                     if s > e {
@@ -235,22 +359,27 @@ impl CmapSubtable {
                                 ((code as i32 + segments[i].id_delta as i32) & 0xFFFF) as u16;
                             glyph_id_map.insert(code, glyph_id);
                         } else {
-                            // NOTE: This is magic
-                            let glyph_id_offset = 2
-                                + id_range_offset_offset
-                                + ((i
-                                    + (((code - segments[i].start_code)
-                                        + segments[i].id_range_offset)
-                                        as usize
-                                        / 2))
-                                    * 2);
-
-                            if glyph_id_offset + 2 > bytes.len() {
-                                return Err(ImtError {
+                            // idRangeOffset is a byte offset from the idRangeOffset entry
+                            // itself into glyphIdArray, which directly follows the
+                            // idRangeOffset array. The glyph id for `code` is the u16 at:
+                            //   &idRangeOffset[i] + idRangeOffset[i] + 2 * (code - startCode[i])
+                            let glyph_id_offset = ((code - segments[i].start_code) as usize)
+                                .checked_mul(2)
+                                .and_then(|offset| {
+                                    offset.checked_add(segments[i].id_range_offset as usize)
+                                })
+                                .and_then(|offset| offset.checked_add(i * 2))
+                                .and_then(|offset| offset.checked_add(id_range_offset_offset))
+                                .filter(|offset| {
+                                    offset
+                                        .checked_add(2)
+                                        .map(|end| end <= bytes.len())
+                                        .unwrap_or(false)
+                                })
+                                .ok_or(ImtError {
                                     kind: ImtErrorKind::Malformed,
                                     source: ImtErrorSource::CmapSubtable,
-                                });
-                            }
+                                })?;
 
                             let glyph_id_value = u16::from_be_bytes(
                                 bytes[glyph_id_offset..(glyph_id_offset + 2)]
@@ -258,13 +387,19 @@ impl CmapSubtable {
                                     .unwrap(),
                             );
 
+                            // A zero in glyphIdArray is the missing glyph; idDelta isn't applied.
+                            if glyph_id_value == 0 {
+                                glyph_id_map.insert(code, 0);
+                                continue;
+                            }
+
                             let glyph_id = ((glyph_id_value as i32 + segments[i].id_delta as i32)
                                 & 0xFFFF) as u16;
                             glyph_id_map.insert(code, glyph_id);
                         }
                     }
 
-                    previous_code = segments[i].end_code;
+                    previous_code = Some(e);
                 }
 
                 Ok(CmapSubtable {
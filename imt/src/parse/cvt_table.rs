@@ -0,0 +1,35 @@
+use alloc::vec::Vec;
+
+use crate::error::*;
+use crate::parse::read_i16;
+
+/// Corresponds to the `cvt ` table.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/cvt>
+///
+/// # Notes
+/// - Only parsed when `FontOptions::instructions` is enabled.
+#[derive(Debug, Clone)]
+pub struct CvtTable {
+    /// Control values referenced by instructions, in font units.
+    pub values: Vec<i16>,
+}
+
+impl CvtTable {
+    pub fn try_parse(bytes: &[u8], table_offset: usize) -> Result<Self, ImtError> {
+        if table_offset > bytes.len() {
+            return Err(ImtError {
+                kind: ImtErrorKind::Truncated,
+                source: ImtErrorSource::CvtTable,
+            });
+        }
+
+        // The table has no header, its length determines the amount of values.
+        let count = (bytes.len() - table_offset) / 2;
+
+        Ok(Self {
+            values: (0..count)
+                .map(|i| read_i16(bytes, table_offset + (i * 2)))
+                .collect(),
+        })
+    }
+}
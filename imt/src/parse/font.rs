@@ -1,24 +1,233 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
 use crate::parse::*;
+use crate::raster::{PreparedGlyph, ScaledGlyph, ScaledGlyphErr};
+use crate::util::pixel::{f32_to_dimension, pixel_bounds};
+use crate::util::shaping::{apply_substitutions, pair_adjustments, ClusterGlyph};
+use crate::util::unicode::is_default_ignorable;
+#[cfg(feature = "std")]
+use crate::util::variation::VariedOutlineCache;
+use crate::util::variation::{
+    advance_width, gvar_component_deltas, gvar_point_deltas, is_default_normalized, metric_delta,
+    normalize_axis_coords, outline_apply_gvar, tuple_scaler, CoordBuilder, MissingAxisPolicy,
+};
+use crate::util::{float, ImtUtilError};
+
+// TODO: Not currently parsed in RobotoFlex: STAT, gasp
+// TODO: Only single & ligature substitution lookups of GSUB and pair adjustment lookups of GPOS
+//       are parsed.
+
+/// Options used when parsing a `Font`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FontOptions {
+    /// Parse the `cvt `, `fpgm` and `prep` tables.
+    ///
+    /// These contain the TrueType hinting instructions and their control values. They are not
+    /// used by this crate, but are made available for an external interpreter.
+    pub instructions: bool,
+}
+
+/// A set of tables to parse with `Font::from_bytes_selective`.
+///
+/// The `head`, `hhea` & `maxp` tables are always parsed as other tables depend on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TableSet(u32);
+
+impl TableSet {
+    pub const ALL: Self = Self((1 << 18) - 1);
+    pub const AVAR: Self = Self(1 << 6);
+    pub const BASE: Self = Self(1 << 11);
+    pub const CMAP: Self = Self(1 << 0);
+    pub const CVT: Self = Self(1 << 12);
+    pub const FPGM: Self = Self(1 << 13);
+    pub const FVAR: Self = Self(1 << 4);
+    /// Glyph classes & the variations of `GPOS` values.
+    pub const GDEF: Self = Self(1 << 17);
+    /// Also parses the `loca` table.
+    pub const GLYF: Self = Self(1 << 3);
+    /// Implies `GDEF` as it classifies glyphs & contains the variations of values.
+    pub const GPOS: Self = Self(1 << 16);
+    pub const GSUB: Self = Self(1 << 15);
+    /// Implies `GLYF` as variations are only parsed for glyphs with outlines.
+    pub const GVAR: Self = Self(1 << 5);
+    pub const HMTX: Self = Self(1 << 1);
+    pub const HVAR: Self = Self(1 << 7);
+    pub const MVAR: Self = Self(1 << 10);
+    pub const NAME: Self = Self(1 << 2);
+    pub const OS2: Self = Self(1 << 8);
+    pub const POST: Self = Self(1 << 9);
+    pub const PREP: Self = Self(1 << 14);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Check if every table of `other` is within this set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// Every table except `cvt `, `fpgm` & `prep`, these are what `Font::from_bytes` parses.
+impl Default for TableSet {
+    fn default() -> Self {
+        Self(Self::ALL.0 & !(Self::CVT.0 | Self::FPGM.0 | Self::PREP.0))
+    }
+}
+
+impl core::ops::BitOr for TableSet {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl core::ops::BitOrAssign for TableSet {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+/// The kind of font data a `Font` was parsed from, see `Font::flavor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FontFlavor {
+    /// TrueType outlines. (`0x00010000` or `true`)
+    TrueType,
+    /// CFF outlines. (`OTTO`)
+    ///
+    /// Parsing these is not yet supported, so a `Font` is never of this flavor for now.
+    OpenTypeCff,
+    /// A font within a collection, see `Font::from_bytes_at`. (`ttcf`)
+    Collection,
+}
+
+/// A variation axis as presented to users, see `Font::variation_axes`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariationAxis {
+    /// Index of the axis within the coordinates of the font.
+    pub index: usize,
+    pub tag: [u8; 4],
+    /// The name of the axis from the `name` table.
+    pub name: Option<String>,
+    pub min_value: f32,
+    pub default_value: f32,
+    pub max_value: f32,
+    /// The designer has requested the axis not be exposed to users.
+    pub hidden: bool,
+}
+
+/// An OpenType layout feature applied by `Font::shape`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Feature(pub [u8; 4]);
+
+impl Feature {
+    /// Pair kerning from `GPOS`.
+    pub const KERN: Self = Self(*b"kern");
+    /// Standard ligatures from `GSUB`.
+    pub const LIGA: Self = Self(*b"liga");
+}
+
+/// A script and its language systems declared by `GSUB` or `GPOS`, see `Font::script_systems`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptSystem {
+    /// Script tag, e.g. `latn`.
+    pub script: u32,
+    /// Language system tags, e.g. `TRK `, sorted. The default language system is not included.
+    pub languages: Vec<u32>,
+}
+
+/// A glyph positioned by `Font::shape`.
+///
+/// Advances and offsets are in font units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapedGlyph {
+    pub glyph_id: u16,
+    /// Amount to advance the pen location after this glyph.
+    pub x_advance: f32,
+    /// Offset of the glyph from the pen location, e.g. for marks.
+    pub x_offset: f32,
+    /// Offset of the glyph from the baseline, `Y` up.
+    pub y_offset: f32,
+    /// Byte index of the first `char` of the text this glyph was produced from.
+    pub cluster: usize,
+}
 
-// TODO: Not currently parsed in RobotoFlex: GDEF, GPOS, GSUB, OS/2, STAT, gasp, post, prep
+/// The `Font::id` of the next parsed font.
+static NEXT_FONT_ID: AtomicUsize = AtomicUsize::new(0);
 
 #[derive(Debug, Clone)]
 pub struct Font {
-    cmap: CmapTable,
+    id: u64,
+    cmap: Option<CmapTable>,
     head: HeadTable,
     hhea: HheaTable,
-    hmtx: HmtxTable,
+    hmtx: Option<HmtxTable>,
     maxp: MaxpTable,
-    name: NameTable,
-    glyf: GlyfTable,
+    name: Option<NameTable>,
+    loca: Option<LocaTable>,
+    glyf: Option<GlyfTable>,
     fvar: Option<FvarTable>,
     gvar: Option<GvarTable>,
     avar: Option<AvarTable>,
     hvar: Option<HvarTable>,
+    os2: Option<Os2Table>,
+    post: Option<PostTable>,
+    mvar: Option<MvarTable>,
+    base: Option<BaseTable>,
+    gsub: Option<LayoutTable>,
+    gpos: Option<LayoutTable>,
+    gdef: Option<GdefTable>,
+    cvt: Option<CvtTable>,
+    fpgm: Option<FpgmTable>,
+    prep: Option<PrepTable>,
+    has_dsig: bool,
+    tables: TableSet,
+    flavor: FontFlavor,
+    #[cfg(feature = "std")]
+    varied_outline_cache: Option<Arc<VariedOutlineCache>>,
 }
 
 impl Font {
     pub fn from_bytes<B: AsRef<[u8]>>(bytes: B) -> Result<Self, ImtError> {
+        Self::from_bytes_with_options(bytes, FontOptions::default())
+    }
+
+    pub fn from_bytes_with_options<B: AsRef<[u8]>>(
+        bytes: B,
+        options: FontOptions,
+    ) -> Result<Self, ImtError> {
+        let mut tables = TableSet::default();
+
+        if options.instructions {
+            tables |= TableSet::CVT | TableSet::FPGM | TableSet::PREP;
+        }
+
+        Self::from_bytes_selective(bytes, tables)
+    }
+
+    /// Parse only the provided tables, reducing the cost of parsing when only some are needed.
+    ///
+    /// For example a font browser may only need `TableSet::NAME | TableSet::FVAR`, while
+    /// rendering needs `TableSet::CMAP | TableSet::HMTX | TableSet::GLYF`.
+    ///
+    /// # Notes
+    /// - Tables that are not requested are not parsed, their accessors return `None`.
+    /// - Accessors of required tables, e.g. `glyf_table`, panic if the table was not requested.
+    ///   Use `tables` to check which tables were requested.
+    /// - Other methods do not panic. Those returning a `Result` error when a table they need was
+    ///   not requested, e.g. `ScaledGlyph::evaluate` without `glyf` returns
+    ///   `ScaledGlyphErr::Missing`. The remaining treat the table as empty.
+    /// - Requested tables that are required by the specification still error when missing.
+    pub fn from_bytes_selective<B: AsRef<[u8]>>(
+        bytes: B,
+        tables: TableSet,
+    ) -> Result<Self, ImtError> {
         let bytes = bytes.as_ref();
 
         match TTCHeader::try_parse(bytes) {
@@ -34,7 +243,43 @@ impl Font {
             },
         }
 
-        let table_directory = TableDirectory::try_parse(bytes, 0)?;
+        Self::from_bytes_at(bytes, 0, tables)
+    }
+
+    /// Parse a font whose table directory is located at `offset` within `bytes`.
+    ///
+    /// This allows parsing a font of a collection, where `offset` is one of
+    /// `TTCHeader::table_directory_offsets`, or a font located within a larger buffer.
+    ///
+    /// # Notes
+    /// - As within a collection, the offsets of tables are relative to the start of `bytes`, not
+    ///   to `offset`. A font that was embedded as a whole, with offsets relative to its own start,
+    ///   should be sliced out of the buffer and parsed with `from_bytes_selective` instead.
+    /// - `bytes` is not checked for a collection header.
+    pub fn from_bytes_at<B: AsRef<[u8]>>(
+        bytes: B,
+        offset: usize,
+        mut tables: TableSet,
+    ) -> Result<Self, ImtError> {
+        let bytes = bytes.as_ref();
+
+        if tables.contains(TableSet::GVAR) {
+            tables |= TableSet::GLYF;
+        }
+
+        if tables.contains(TableSet::GPOS) {
+            tables |= TableSet::GDEF;
+        }
+
+        let table_directory = TableDirectory::try_parse(bytes, offset)?;
+
+        let flavor = if bytes.get(0..4) == Some(b"ttcf") {
+            FontFlavor::Collection
+        } else if table_directory.sfnt_version == tag(b"OTTO") {
+            FontFlavor::OpenTypeCff
+        } else {
+            FontFlavor::TrueType
+        };
 
         // TODO: Verify Table Checksums
 
@@ -50,6 +295,17 @@ impl Font {
         let mut gvar_table_index = None;
         let mut avar_table_index = None;
         let mut hvar_table_index = None;
+        let mut os2_table_index = None;
+        let mut post_table_index = None;
+        let mut mvar_table_index = None;
+        let mut base_table_index = None;
+        let mut gsub_table_index = None;
+        let mut gpos_table_index = None;
+        let mut gdef_table_index = None;
+        let mut cvt_table_index = None;
+        let mut fpgm_table_index = None;
+        let mut prep_table_index = None;
+        let mut has_dsig = false;
 
         for (i, table_record) in table_directory.table_records.iter().enumerate() {
             match table_record.table_tag {
@@ -65,31 +321,46 @@ impl Font {
                 table_tag::GVAR => gvar_table_index = Some(i),
                 table_tag::AVAR => avar_table_index = Some(i),
                 table_tag::HVAR => hvar_table_index = Some(i),
+                table_tag::OS2 => os2_table_index = Some(i),
+                table_tag::POST => post_table_index = Some(i),
+                table_tag::MVAR => mvar_table_index = Some(i),
+                table_tag::BASE => base_table_index = Some(i),
+                table_tag::GSUB => gsub_table_index = Some(i),
+                table_tag::GPOS => gpos_table_index = Some(i),
+                table_tag::GDEF => gdef_table_index = Some(i),
+                table_tag::DSIG => has_dsig = true,
+                table_tag::CVT => cvt_table_index = Some(i),
+                table_tag::FPGM => fpgm_table_index = Some(i),
+                table_tag::PREP => prep_table_index = Some(i),
                 _ => (),
             }
         }
 
-        let cmap = match cmap_table_index {
-            Some(table_index) => {
-                let table_record = &table_directory.table_records[table_index];
-                let start = table_record.offset as usize;
-                let end = start + table_record.length as usize;
+        let cmap = if tables.contains(TableSet::CMAP) {
+            Some(match cmap_table_index {
+                Some(table_index) => {
+                    let table_record = &table_directory.table_records[table_index];
+                    let start = table_record.offset as usize;
+                    let end = start + table_record.length as usize;
 
-                if end > bytes.len() {
+                    if end > bytes.len() {
+                        return Err(ImtError {
+                            kind: ImtErrorKind::Truncated,
+                            source: ImtErrorSource::CmapTable,
+                        });
+                    }
+
+                    CmapTable::try_parse(&bytes[start..end], 0)?
+                },
+                None => {
                     return Err(ImtError {
-                        kind: ImtErrorKind::Truncated,
+                        kind: ImtErrorKind::MissingTable,
                         source: ImtErrorSource::CmapTable,
-                    });
-                }
-
-                CmapTable::try_parse(&bytes[start..end], 0)?
-            },
-            None => {
-                return Err(ImtError {
-                    kind: ImtErrorKind::MissingTable,
-                    source: ImtErrorSource::CmapTable,
-                })
-            },
+                    })
+                },
+            })
+        } else {
+            None
         };
 
         let head = match head_table_index {
@@ -161,8 +432,117 @@ impl Font {
             },
         };
 
-        let name = match name_table_index {
-            Some(table_index) => {
+        let name = if tables.contains(TableSet::NAME) {
+            Some(match name_table_index {
+                Some(table_index) => {
+                    let table_record = &table_directory.table_records[table_index];
+                    let start = table_record.offset as usize;
+                    let end = start + table_record.length as usize;
+
+                    if end > bytes.len() {
+                        return Err(ImtError {
+                            kind: ImtErrorKind::Truncated,
+                            source: ImtErrorSource::NameTable,
+                        });
+                    }
+
+                    NameTable::try_parse(&bytes[start..end], 0)?
+                },
+                None => {
+                    return Err(ImtError {
+                        kind: ImtErrorKind::MissingTable,
+                        source: ImtErrorSource::NameTable,
+                    })
+                },
+            })
+        } else {
+            None
+        };
+
+        let hmtx = if tables.contains(TableSet::HMTX) {
+            Some(match hmtx_table_index {
+                Some(table_index) => {
+                    let table_record = &table_directory.table_records[table_index];
+                    let start = table_record.offset as usize;
+                    let end = start + table_record.length as usize;
+
+                    if end > bytes.len() {
+                        return Err(ImtError {
+                            kind: ImtErrorKind::Truncated,
+                            source: ImtErrorSource::HmtxTable,
+                        });
+                    }
+
+                    HmtxTable::try_parse(&bytes[start..end], 0, &maxp, &hhea)?
+                },
+                None => {
+                    return Err(ImtError {
+                        kind: ImtErrorKind::MissingTable,
+                        source: ImtErrorSource::HmtxTable,
+                    })
+                },
+            })
+        } else {
+            None
+        };
+
+        let loca = if tables.contains(TableSet::GLYF) {
+            Some(match loca_table_index {
+                Some(table_index) => {
+                    let table_record = &table_directory.table_records[table_index];
+                    let start = table_record.offset as usize;
+                    let end = start + table_record.length as usize;
+
+                    if end > bytes.len() {
+                        return Err(ImtError {
+                            kind: ImtErrorKind::Truncated,
+                            source: ImtErrorSource::LocaTable,
+                        });
+                    }
+
+                    LocaTable::try_parse(&bytes[start..end], 0, &head, &maxp)?
+                },
+                None => {
+                    return Err(ImtError {
+                        kind: ImtErrorKind::MissingTable,
+                        source: ImtErrorSource::LocaTable,
+                    })
+                },
+            })
+        } else {
+            None
+        };
+
+        let glyf = if tables.contains(TableSet::GLYF) {
+            Some(match glyf_table_index {
+                Some(table_index) => {
+                    let table_record = &table_directory.table_records[table_index];
+                    let start = table_record.offset as usize;
+                    let end = start + table_record.length as usize;
+
+                    if end > bytes.len() {
+                        return Err(ImtError {
+                            kind: ImtErrorKind::Truncated,
+                            source: ImtErrorSource::GlyfTable,
+                        });
+                    }
+
+                    // When every offset of loca is equal this has no outlines, see `has_outlines`.
+                    GlyfTable::try_parse(&bytes[start..end], 0, loca.as_ref().unwrap())?
+                },
+                None => {
+                    return Err(ImtError {
+                        kind: ImtErrorKind::MissingTable,
+                        source: ImtErrorSource::GlyfTable,
+                    })
+                },
+            })
+        } else {
+            None
+        };
+
+        let fvar = match fvar_table_index {
+            Some(table_index) if tables.contains(TableSet::FVAR) => {
                 let table_record = &table_directory.table_records[table_index];
                 let start = table_record.offset as usize;
                 let end = start + table_record.length as usize;
@@ -170,22 +550,17 @@ impl Font {
                 if end > bytes.len() {
                     return Err(ImtError {
                         kind: ImtErrorKind::Truncated,
-                        source: ImtErrorSource::NameTable,
+                        source: ImtErrorSource::FvarTable,
                     });
                 }
 
-                NameTable::try_parse(&bytes[start..end], 0)?
-            },
-            None => {
-                return Err(ImtError {
-                    kind: ImtErrorKind::MissingTable,
-                    source: ImtErrorSource::NameTable,
-                })
+                Some(FvarTable::try_parse(&bytes[start..end], 0)?)
             },
+            _ => None,
         };
 
-        let hmtx = match hmtx_table_index {
-            Some(table_index) => {
+        let gvar = match (gvar_table_index, glyf.as_ref()) {
+            (Some(table_index), Some(glyf)) if tables.contains(TableSet::GVAR) => {
                 let table_record = &table_directory.table_records[table_index];
                 let start = table_record.offset as usize;
                 let end = start + table_record.length as usize;
@@ -193,22 +568,17 @@ impl Font {
                 if end > bytes.len() {
                     return Err(ImtError {
                         kind: ImtErrorKind::Truncated,
-                        source: ImtErrorSource::HmtxTable,
+                        source: ImtErrorSource::GvarTable,
                     });
                 }
 
-                HmtxTable::try_parse(&bytes[start..end], 0, &maxp, &hhea)?
-            },
-            None => {
-                return Err(ImtError {
-                    kind: ImtErrorKind::MissingTable,
-                    source: ImtErrorSource::HmtxTable,
-                })
+                Some(GvarTable::try_parse(&bytes[start..end], 0, glyf)?)
             },
+            _ => None,
         };
 
-        let loca = match loca_table_index {
-            Some(table_index) => {
+        let avar = match avar_table_index {
+            Some(table_index) if tables.contains(TableSet::AVAR) => {
                 let table_record = &table_directory.table_records[table_index];
                 let start = table_record.offset as usize;
                 let end = start + table_record.length as usize;
@@ -216,22 +586,17 @@ impl Font {
                 if end > bytes.len() {
                     return Err(ImtError {
                         kind: ImtErrorKind::Truncated,
-                        source: ImtErrorSource::LocaTable,
+                        source: ImtErrorSource::AvarTable,
                     });
                 }
 
-                LocaTable::try_parse(&bytes[start..end], 0, &head, &maxp)?
-            },
-            None => {
-                return Err(ImtError {
-                    kind: ImtErrorKind::MissingTable,
-                    source: ImtErrorSource::LocaTable,
-                })
+                Some(AvarTable::try_parse(&bytes[start..end], 0)?)
             },
+            _ => None,
         };
 
-        let glyf = match glyf_table_index {
-            Some(table_index) => {
+        let hvar = match hvar_table_index {
+            Some(table_index) if tables.contains(TableSet::HVAR) => {
                 let table_record = &table_directory.table_records[table_index];
                 let start = table_record.offset as usize;
                 let end = start + table_record.length as usize;
@@ -239,40 +604,115 @@ impl Font {
                 if end > bytes.len() {
                     return Err(ImtError {
                         kind: ImtErrorKind::Truncated,
-                        source: ImtErrorSource::GlyfTable,
+                        source: ImtErrorSource::HvarTable,
                     });
                 }
 
-                GlyfTable::try_parse(&bytes[start..end], 0, &loca)?
+                Some(HvarTable::try_parse(&bytes[start..end], 0)?)
             },
-            None => {
-                return Err(ImtError {
-                    kind: ImtErrorKind::MissingTable,
-                    source: ImtErrorSource::GlyfTable,
+            _ => None,
+        };
+
+        // `OS/2` & `post` only refine what the required tables provide, so when either is
+        // truncated or malformed it is treated as absent instead of rejecting the font.
+        let os2 = match os2_table_index {
+            Some(table_index) if tables.contains(TableSet::OS2) => {
+                let table_record = &table_directory.table_records[table_index];
+                let start = table_record.offset as usize;
+                let end = start + table_record.length as usize;
+
+                bytes
+                    .get(start..end)
+                    .and_then(|bytes| Os2Table::try_parse(bytes, 0).ok())
+            },
+            _ => None,
+        };
+
+        let post = match post_table_index {
+            Some(table_index) if tables.contains(TableSet::POST) => {
+                let table_record = &table_directory.table_records[table_index];
+                let start = table_record.offset as usize;
+                let end = start + table_record.length as usize;
+
+                bytes
+                    .get(start..end)
+                    .and_then(|bytes| PostTable::try_parse(bytes, 0).ok())
+            },
+            _ => None,
+        };
+
+        // Without `MVAR` metrics are those of the default instance, so a malformed one is treated
+        // as absent as well.
+        let mvar = match mvar_table_index {
+            Some(table_index) if tables.contains(TableSet::MVAR) => {
+                let table_record = &table_directory.table_records[table_index];
+                let start = table_record.offset as usize;
+                let end = start + table_record.length as usize;
+
+                bytes
+                    .get(start..end)
+                    .and_then(|bytes| MvarTable::try_parse(bytes, 0).ok())
+            },
+            _ => None,
+        };
+
+        // A malformed `BASE` only loses the baselines it would define.
+        let base = match base_table_index {
+            Some(table_index) if tables.contains(TableSet::BASE) => {
+                let table_record = &table_directory.table_records[table_index];
+                let start = table_record.offset as usize;
+                let end = start + table_record.length as usize;
+
+                bytes
+                    .get(start..end)
+                    .and_then(|bytes| BaseTable::try_parse(bytes, 0).ok())
+            },
+            _ => None,
+        };
+
+        // Text is still laid out without substitutions & positioning, so malformed `GSUB`, `GPOS`
+        // or `GDEF` tables are treated as absent.
+        let gsub = match gsub_table_index {
+            Some(table_index) if tables.contains(TableSet::GSUB) => {
+                let table_record = &table_directory.table_records[table_index];
+                let start = table_record.offset as usize;
+                let end = start + table_record.length as usize;
+
+                bytes.get(start..end).and_then(|bytes| {
+                    LayoutTable::try_parse(bytes, 0, ImtErrorSource::GsubTable).ok()
                 })
             },
+            _ => None,
         };
 
-        let fvar = match fvar_table_index {
-            Some(table_index) => {
+        let gpos = match gpos_table_index {
+            Some(table_index) if tables.contains(TableSet::GPOS) => {
                 let table_record = &table_directory.table_records[table_index];
                 let start = table_record.offset as usize;
                 let end = start + table_record.length as usize;
 
-                if end > bytes.len() {
-                    return Err(ImtError {
-                        kind: ImtErrorKind::Truncated,
-                        source: ImtErrorSource::FvarTable,
-                    });
-                }
+                bytes.get(start..end).and_then(|bytes| {
+                    LayoutTable::try_parse(bytes, 0, ImtErrorSource::GposTable).ok()
+                })
+            },
+            _ => None,
+        };
 
-                Some(FvarTable::try_parse(&bytes[start..end], 0)?)
+        let gdef = match gdef_table_index {
+            Some(table_index) if tables.contains(TableSet::GDEF) => {
+                let table_record = &table_directory.table_records[table_index];
+                let start = table_record.offset as usize;
+                let end = start + table_record.length as usize;
+
+                bytes
+                    .get(start..end)
+                    .and_then(|bytes| GdefTable::try_parse(bytes, 0).ok())
             },
-            None => None,
+            _ => None,
         };
 
-        let gvar = match gvar_table_index {
-            Some(table_index) => {
+        let cvt = match cvt_table_index {
+            Some(table_index) if tables.contains(TableSet::CVT) => {
                 let table_record = &table_directory.table_records[table_index];
                 let start = table_record.offset as usize;
                 let end = start + table_record.length as usize;
@@ -280,17 +720,17 @@ impl Font {
                 if end > bytes.len() {
                     return Err(ImtError {
                         kind: ImtErrorKind::Truncated,
-                        source: ImtErrorSource::GvarTable,
+                        source: ImtErrorSource::CvtTable,
                     });
                 }
 
-                Some(GvarTable::try_parse(&bytes[start..end], 0, &glyf)?)
+                Some(CvtTable::try_parse(&bytes[start..end], 0)?)
             },
-            None => None,
+            _ => None,
         };
 
-        let avar = match avar_table_index {
-            Some(table_index) => {
+        let fpgm = match fpgm_table_index {
+            Some(table_index) if tables.contains(TableSet::FPGM) => {
                 let table_record = &table_directory.table_records[table_index];
                 let start = table_record.offset as usize;
                 let end = start + table_record.length as usize;
@@ -298,17 +738,17 @@ impl Font {
                 if end > bytes.len() {
                     return Err(ImtError {
                         kind: ImtErrorKind::Truncated,
-                        source: ImtErrorSource::AvarTable,
+                        source: ImtErrorSource::FpgmTable,
                     });
                 }
 
-                Some(AvarTable::try_parse(&bytes[start..end], 0)?)
+                Some(FpgmTable::try_parse(&bytes[start..end], 0)?)
             },
-            None => None,
+            _ => None,
         };
 
-        let hvar = match hvar_table_index {
-            Some(table_index) => {
+        let prep = match prep_table_index {
+            Some(table_index) if tables.contains(TableSet::PREP) => {
                 let table_record = &table_directory.table_records[table_index];
                 let start = table_record.offset as usize;
                 let end = start + table_record.length as usize;
@@ -316,34 +756,110 @@ impl Font {
                 if end > bytes.len() {
                     return Err(ImtError {
                         kind: ImtErrorKind::Truncated,
-                        source: ImtErrorSource::HvarTable,
+                        source: ImtErrorSource::PrepTable,
                     });
                 }
 
-                Some(HvarTable::try_parse(&bytes[start..end], 0)?)
+                Some(PrepTable::try_parse(&bytes[start..end], 0)?)
             },
-            None => None,
+            _ => None,
         };
 
-        // TODO: Check if axis count matches between variation tables.
+        if let Some(fvar) = fvar.as_ref() {
+            let axis_count = fvar.axes.len();
+
+            if let Some(gvar) = gvar.as_ref() {
+                if gvar.axis_count != axis_count {
+                    return Err(ImtError {
+                        kind: ImtErrorKind::Malformed,
+                        source: ImtErrorSource::GvarTable,
+                    });
+                }
+            }
+
+            if let Some(avar) = avar.as_ref() {
+                if avar.segment_maps.len() != axis_count
+                    || matches!(
+                        avar.item_variation_store.as_ref(),
+                        Some(store) if store.axis_count != axis_count
+                    )
+                {
+                    return Err(ImtError {
+                        kind: ImtErrorKind::Malformed,
+                        source: ImtErrorSource::AvarTable,
+                    });
+                }
+            }
+
+            if let Some(hvar) = hvar.as_ref() {
+                if hvar.item_variation_store.axis_count != axis_count {
+                    return Err(ImtError {
+                        kind: ImtErrorKind::Malformed,
+                        source: ImtErrorSource::HvarTable,
+                    });
+                }
+            }
+        }
+
+        // Like any other malformed `MVAR`, one that disagrees with `fvar` is treated as absent.
+        let mvar = mvar.filter(|mvar| {
+            match (fvar.as_ref(), mvar.item_variation_store.as_ref()) {
+                (Some(fvar), Some(store)) => store.axis_count == fvar.axes.len(),
+                _ => true,
+            }
+        });
 
         Ok(Self {
+            id: NEXT_FONT_ID.fetch_add(1, Ordering::Relaxed) as u64,
             cmap,
             head,
             hhea,
             hmtx,
             maxp,
             name,
+            loca,
             glyf,
             fvar,
             gvar,
             avar,
             hvar,
+            os2,
+            post,
+            mvar,
+            base,
+            gsub,
+            gpos,
+            gdef,
+            cvt,
+            fpgm,
+            prep,
+            has_dsig,
+            tables,
+            flavor,
+            #[cfg(feature = "std")]
+            varied_outline_cache: None,
         })
     }
 
+    /// An identifier of this font that is unique within the process.
+    ///
+    /// Every parsed font is assigned a new id, including fonts parsed from the same data. Clones
+    /// share the id of the font they were cloned from. This is included in
+    /// `ScaledGlyph::unique_id`, so glyphs of different fonts can be cached alongside each other.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// # Notes
+    /// - Panics if `TableSet::CMAP` was not requested with `from_bytes_selective`, see
+    ///   `try_cmap_table`.
     pub fn cmap_table(&self) -> &CmapTable {
-        &self.cmap
+        required_table(&self.cmap, "cmap")
+    }
+
+    /// The `cmap` table, or an error if it was not requested with `from_bytes_selective`.
+    pub fn try_cmap_table(&self) -> Result<&CmapTable, ImtError> {
+        try_table(&self.cmap, ImtErrorSource::CmapTable)
     }
 
     pub fn head_table(&self) -> &HeadTable {
@@ -354,20 +870,57 @@ impl Font {
         &self.hhea
     }
 
+    /// # Notes
+    /// - Panics if `TableSet::HMTX` was not requested with `from_bytes_selective`, see
+    ///   `try_hmtx_table`.
     pub fn hmtx_table(&self) -> &HmtxTable {
-        &self.hmtx
+        required_table(&self.hmtx, "hmtx")
+    }
+
+    /// The `hmtx` table, or an error if it was not requested with `from_bytes_selective`.
+    pub fn try_hmtx_table(&self) -> Result<&HmtxTable, ImtError> {
+        try_table(&self.hmtx, ImtErrorSource::HmtxTable)
     }
 
     pub fn maxp_table(&self) -> &MaxpTable {
         &self.maxp
     }
 
+    /// # Notes
+    /// - Panics if `TableSet::NAME` was not requested with `from_bytes_selective`, see
+    ///   `try_name_table`.
     pub fn name_table(&self) -> &NameTable {
-        &self.name
+        required_table(&self.name, "name")
+    }
+
+    /// The `name` table, or an error if it was not requested with `from_bytes_selective`.
+    pub fn try_name_table(&self) -> Result<&NameTable, ImtError> {
+        try_table(&self.name, ImtErrorSource::NameTable)
+    }
+
+    /// # Notes
+    /// - Panics if `TableSet::GLYF` was not requested with `from_bytes_selective`, see
+    ///   `try_loca_table`.
+    pub fn loca_table(&self) -> &LocaTable {
+        required_table(&self.loca, "loca")
     }
 
+    /// The `loca` table, or an error if `TableSet::GLYF` was not requested with
+    /// `from_bytes_selective`.
+    pub fn try_loca_table(&self) -> Result<&LocaTable, ImtError> {
+        try_table(&self.loca, ImtErrorSource::LocaTable)
+    }
+
+    /// # Notes
+    /// - Panics if `TableSet::GLYF` was not requested with `from_bytes_selective`, see
+    ///   `try_glyf_table`.
     pub fn glyf_table(&self) -> &GlyfTable {
-        &self.glyf
+        required_table(&self.glyf, "glyf")
+    }
+
+    /// The `glyf` table, or an error if it was not requested with `from_bytes_selective`.
+    pub fn try_glyf_table(&self) -> Result<&GlyfTable, ImtError> {
+        try_table(&self.glyf, ImtErrorSource::GlyfTable)
     }
 
     pub fn fvar_table(&self) -> Option<&FvarTable> {
@@ -385,4 +938,1355 @@ impl Font {
     pub fn hvar_table(&self) -> Option<&HvarTable> {
         self.hvar.as_ref()
     }
+
+    /// `None` if the table is absent, not requested or malformed.
+    pub fn os2_table(&self) -> Option<&Os2Table> {
+        self.os2.as_ref()
+    }
+
+    /// `None` if the table is absent, not requested or malformed.
+    pub fn post_table(&self) -> Option<&PostTable> {
+        self.post.as_ref()
+    }
+
+    /// `None` if the table is absent, not requested or malformed.
+    pub fn mvar_table(&self) -> Option<&MvarTable> {
+        self.mvar.as_ref()
+    }
+
+    /// `None` if the table is absent, not requested or malformed.
+    pub fn base_table(&self) -> Option<&BaseTable> {
+        self.base.as_ref()
+    }
+
+    /// `None` if the table is absent, not requested or malformed.
+    pub fn gsub_table(&self) -> Option<&LayoutTable> {
+        self.gsub.as_ref()
+    }
+
+    /// `None` if the table is absent, not requested or malformed.
+    pub fn gpos_table(&self) -> Option<&LayoutTable> {
+        self.gpos.as_ref()
+    }
+
+    /// `None` if the table is absent, not requested or malformed.
+    pub fn gdef_table(&self) -> Option<&GdefTable> {
+        self.gdef.as_ref()
+    }
+
+    /// The kind of font data this font was parsed from.
+    pub fn flavor(&self) -> FontFlavor {
+        self.flavor
+    }
+
+    /// The tables requested when parsing.
+    pub fn tables(&self) -> TableSet {
+        self.tables
+    }
+
+    /// Only present when `FontOptions::instructions` is enabled or `TableSet::CVT` is requested.
+    pub fn cvt_table(&self) -> Option<&CvtTable> {
+        self.cvt.as_ref()
+    }
+
+    /// Only present when `FontOptions::instructions` is enabled or `TableSet::FPGM` is requested.
+    pub fn fpgm_table(&self) -> Option<&FpgmTable> {
+        self.fpgm.as_ref()
+    }
+
+    /// Only present when `FontOptions::instructions` is enabled or `TableSet::PREP` is requested.
+    pub fn prep_table(&self) -> Option<&PrepTable> {
+        self.prep.as_ref()
+    }
+
+    /// Set the cache used for outlines with `gvar` applied.
+    ///
+    /// # Notes
+    /// - Clones of this `Font` share the same cache.
+    /// - The cache may be set on multiple fonts, outlines are kept apart by `Font::id`.
+    #[cfg(feature = "std")]
+    pub fn set_varied_outline_cache(&mut self, cache: Option<Arc<VariedOutlineCache>>) {
+        self.varied_outline_cache = cache;
+    }
+
+    #[cfg(feature = "std")]
+    pub fn varied_outline_cache(&self) -> Option<&Arc<VariedOutlineCache>> {
+        self.varied_outline_cache.as_ref()
+    }
+
+    /// The amount of glyphs in the font. (`maxp.numGlyphs`)
+    ///
+    /// Valid glyph ids are less than this.
+    pub fn num_glyphs(&self) -> u16 {
+        self.maxp.num_glyphs
+    }
+
+    /// The amount of glyphs that have an outline.
+    ///
+    /// Unlike `num_glyphs` this excludes blank glyphs, e.g. `space`, making it the amount of
+    /// glyphs a tool iterating `GlyfTable::outlines` will visit.
+    ///
+    /// # Notes
+    /// - Composite glyphs are included when their components could be resolved.
+    /// - Returns `ImtErrorKind::MissingTable` if `TableSet::GLYF` was not requested with
+    ///   `from_bytes_selective`.
+    pub fn glyph_count_with_outlines(&self) -> Result<usize, ImtError> {
+        Ok(self.try_glyf_table()?.outlines.len())
+    }
+
+    /// Check if any glyph of the font has an outline.
+    ///
+    /// A font where every offset of `loca` is equal has an empty `glyf` table. This is valid, but
+    /// such a font can only provide metrics, e.g. for layout, as every glyph is blank.
+    ///
+    /// # Notes
+    /// - Returns `false` when `glyf` was not parsed, see `Font::from_bytes_selective`.
+    pub fn has_outlines(&self) -> bool {
+        self.glyf
+            .as_ref()
+            .map(|glyf| !glyf.outlines.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Check if glyphs can be rendered for the default instance without providing coordinates.
+    ///
+    /// The outlines of `glyf` are those of the default instance, `gvar` only holds the deltas
+    /// away from it. Passing `None` as coordinates to `ScaledGlyph::evaluate` is equivalent to
+    /// passing the default of every axis, including the resulting `unique_id`.
+    ///
+    /// # Notes
+    /// - Returns `false` when `glyf` was not parsed, see `Font::from_bytes_selective`.
+    pub fn default_is_renderable(&self) -> bool {
+        self.glyf.is_some()
+    }
+
+    /// The glyph id to draw in place of characters that can't be mapped.
+    ///
+    /// The precedence is as follows:
+    /// 1. The glyph mapped to U+FFFD (`REPLACEMENT CHARACTER`) if the font maps it.
+    /// 2. `.notdef` (glyph id zero) otherwise.
+    /// 3. When the returned glyph has no outline, the caller should draw a box of its own.
+    ///
+    /// # Notes
+    /// - Mapping functions, e.g. `CmapSubtable::map_str`, still map unmapped characters to
+    ///   `.notdef`. Those can be substituted with this glyph where a replacement is preferred.
+    /// - This crate does not synthesize a box for glyphs without an outline.
+    pub fn replacement_glyph(&self) -> u16 {
+        self.unicode_subtable()
+            .and_then(|subtable| subtable.glyph_id_map.get(&0xFFFD).copied())
+            .unwrap_or(0)
+    }
+
+    /// The glyph id mapped to a character.
+    ///
+    /// # Notes
+    /// - The Windows full repertoire (3, 10) subtable of `cmap` is preferred, followed by Windows
+    ///   BMP (3, 1), Unicode (0) and then any other Windows subtable.
+    /// - Returns `None` for characters mapped to `.notdef` as well as unmapped ones.
+    /// - Only format 4 subtables are parsed and others are skipped, so characters beyond the
+    ///   Basic Multilingual Plane are never mapped.
+    pub fn glyph_for_char(&self, c: char) -> Option<u16> {
+        let code = u16::try_from(c as u32).ok()?;
+
+        self.unicode_subtable()?
+            .glyph_id_map
+            .get(&code)
+            .copied()
+            .filter(|glyph_id| *glyph_id != 0)
+    }
+
+    /// The glyph id mapped to each character of `s`, see `Font::glyph_for_char`.
+    pub fn glyph_ids_for_str(&self, s: &str) -> Vec<Option<u16>> {
+        s.chars().map(|c| self.glyph_for_char(c)).collect()
+    }
+
+    /// Check if the font is monospaced.
+    ///
+    /// # Notes
+    /// - `isFixedPitch` of the `post` table and the PANOSE proportion of the `OS/2` table are
+    ///   checked first. Otherwise the font is monospaced if every non-zero advance in `hmtx` is
+    ///   equal.
+    pub fn is_monospaced(&self) -> bool {
+        if self
+            .post
+            .as_ref()
+            .map(|post| post.is_fixed_pitch != 0)
+            .unwrap_or(false)
+            || self
+                .os2
+                .as_ref()
+                .map(|os2| os2.panose_monospaced())
+                .unwrap_or(false)
+        {
+            return true;
+        }
+
+        let hmtx = match self.hmtx.as_ref() {
+            Some(some) => some,
+            None => return false,
+        };
+
+        let mut advances = hmtx
+            .hor_metric
+            .iter()
+            .map(|metric| metric.advance_width)
+            .filter(|advance| *advance != 0);
+
+        match advances.next() {
+            Some(first) => advances.all(|advance| advance == first),
+            None => false,
+        }
+    }
+
+    /// The advance in font units of a cell if the font is monospaced.
+    ///
+    /// # Notes
+    /// - Zero-width glyphs (e.g. combining marks) are ignored. If the font is flagged as
+    ///   monospaced but advances differ the most common advance is returned.
+    pub fn monospace_advance(&self) -> Option<u16> {
+        if !self.is_monospaced() {
+            return None;
+        }
+
+        let mut counts: BTreeMap<u16, usize> = BTreeMap::new();
+
+        for metric in self.hmtx.as_ref()?.hor_metric.iter() {
+            if metric.advance_width != 0 {
+                *counts.entry(metric.advance_width).or_default() += 1;
+            }
+        }
+
+        counts
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(advance, _)| advance)
+    }
+
+    /// The glyph that provides the horizontal metrics of a glyph.
+    ///
+    /// This is `glyph_id` itself unless it is a composite glyph with a component that has the
+    /// `USE_MY_METRICS` flag set, in which case it is that component.
+    pub fn metrics_glyph_id(&self, glyph_id: u16) -> u16 {
+        // Components may be composites themselves, the depth is limited in case of a cycle.
+        const MAX_DEPTH: usize = 8;
+        let mut metrics_glyph_id = glyph_id;
+
+        let glyf = match self.glyf.as_ref() {
+            Some(some) => some,
+            None => return glyph_id,
+        };
+
+        for _ in 0..MAX_DEPTH {
+            match glyf.use_my_metrics.get(&metrics_glyph_id) {
+                Some(component) => metrics_glyph_id = *component,
+                None => break,
+            }
+        }
+
+        metrics_glyph_id
+    }
+
+    /// The advance in pixels of the space character (U+0020) at `size`.
+    ///
+    /// This avoids evaluating a `ScaledGlyph` just to measure whitespace. When the font does not
+    /// map a space a quarter of the em is used instead.
+    ///
+    /// # Notes
+    /// - `coords` should be normalized. Invalid coordinates are ignored.
+    /// - Unlike `scaled_advance` the advance is not rounded.
+    pub fn space_advance(&self, coords: Option<&[f32]>, size: f32) -> f32 {
+        let scaler = (1.0 / self.head.units_per_em as f32) * size;
+
+        let glyph_id = self
+            .unicode_subtable()
+            .and_then(|subtable| subtable.glyph_id_map.get(&0x20).copied());
+
+        let metrics_glyph_id = match glyph_id {
+            Some(glyph_id) => self.metrics_glyph_id(glyph_id),
+            None => return size / 4.0,
+        };
+
+        let mut advance_w = match self
+            .hmtx
+            .as_ref()
+            .and_then(|hmtx| hmtx.advance_width(metrics_glyph_id))
+        {
+            Some(advance_width) => advance_width as f32,
+            None => return size / 4.0,
+        };
+
+        if let Some(coords) = coords {
+            advance_w += advance_width(self, metrics_glyph_id, &coords.to_vec()).unwrap_or(0.0);
+        }
+
+        advance_w * scaler
+    }
+
+    /// The advance in pixels of a glyph at `size`.
+    ///
+    /// This is the `advance_w` that `ScaledGlyph::evaluate` would produce, without cloning or
+    /// rebuilding the outline of the glyph.
+    ///
+    /// # Notes
+    /// - `coords` should be normalized.
+    pub fn scaled_advance(
+        &self,
+        glyph_id: u16,
+        coords: Option<&[f32]>,
+        size: f32,
+    ) -> Result<i16, ScaledGlyphErr> {
+        let coords = coords.map(|coords| coords.to_vec());
+        Ok(float::ceil(self.unrounded_advance(glyph_id, coords.as_ref(), size)?) as i16)
+    }
+
+    /// The advance in pixels of the glyphs of `text` at `size` laid out along a line.
+    ///
+    /// This is intended for measuring text, e.g. when deciding where to break lines. Glyph ids
+    /// are resolved in a single batch and only the advances are computed, no `ScaledGlyph` or
+    /// outline is created.
+    ///
+    /// The returned advance is the sum of `ScaledGlyph::advance_w_f32` of every glyph, i.e. the
+    /// end of the line from `layout::pen_positions` with `fractional` before rounding. With
+    /// `kerning` the `kern` feature of `GPOS` is applied to the advances as well, see
+    /// `util::shaping::pair_adjustments`.
+    ///
+    /// # Notes
+    /// - `coords` should be normalized. Invalid coordinates are ignored and the default instance
+    ///   is used instead.
+    /// - Characters that are not mapped use the advance of `.notdef`.
+    /// - Default ignorable characters have no advance, see `util::unicode::is_default_ignorable`.
+    pub fn string_advance(&self, text: &str, coords: &[f32], size: f32, kerning: bool) -> f32 {
+        let subtable = match self.unicode_subtable() {
+            Some(some) => some,
+            None => return 0.0,
+        };
+
+        let mut glyph_ids = Vec::new();
+        subtable.map_str(text, &mut glyph_ids);
+
+        let glyph_ids: Vec<u16> = text
+            .chars()
+            .zip(glyph_ids)
+            .filter(|(c, _)| !is_default_ignorable(*c))
+            .map(|(_, glyph_id)| glyph_id)
+            .collect();
+
+        let coords = if coords.is_empty() || is_default_normalized(coords) {
+            None
+        } else {
+            Some(coords.to_vec())
+        };
+
+        let mut advance = glyph_ids
+            .iter()
+            .map(|glyph_id| {
+                match self.unrounded_advance(*glyph_id, coords.as_ref(), size) {
+                    Ok(ok) => ok,
+                    Err(_) => self.unrounded_advance(*glyph_id, None, size).unwrap_or(0.0),
+                }
+            })
+            .sum();
+
+        if kerning {
+            let adjustments =
+                match pair_adjustments(self, &glyph_ids, b"kern", coords.as_deref().unwrap_or(&[]))
+                {
+                    Ok(ok) => ok,
+                    Err(_) => pair_adjustments(self, &glyph_ids, b"kern", &[]).unwrap_or_default(),
+                };
+
+            let scaler = (1.0 / self.head.units_per_em as f32) * size;
+
+            advance += adjustments
+                .into_iter()
+                .map(|adjustment| adjustment.x_advance * scaler)
+                .sum::<f32>();
+        }
+
+        advance
+    }
+
+    /// The scripts and language systems that are declared by `GSUB` and `GPOS`.
+    ///
+    /// Scripts declared by either table are merged, sorted by script tag.
+    ///
+    /// # Notes
+    /// - Returns an empty `Vec` when neither table is present.
+    /// - A declared script doesn't imply any features are defined for it.
+    pub fn script_systems(&self) -> Vec<ScriptSystem> {
+        let mut systems: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+
+        for table in [self.gsub.as_ref(), self.gpos.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            for (script, layout_script) in table.scripts.iter() {
+                let languages = systems.entry(*script).or_default();
+
+                for tag in layout_script.lang_sys_tags.iter() {
+                    if !languages.contains(tag) {
+                        languages.push(*tag);
+                    }
+                }
+            }
+        }
+
+        systems
+            .into_iter()
+            .map(|(script, mut languages)| {
+                languages.sort_unstable();
+                ScriptSystem {
+                    script,
+                    languages,
+                }
+            })
+            .collect()
+    }
+
+    /// Map `text` to glyph ids and position them, applying `features`.
+    ///
+    /// The substitution features of `GSUB`, e.g. `Feature::LIGA`, are applied first followed by
+    /// the positioning features of `GPOS`, e.g. `Feature::KERN`, each in the order of
+    /// `features`. The glyphs are returned in logical order, `cluster` of each glyph is the byte
+    /// index within `text` of the first `char` it was produced from.
+    ///
+    /// `x_advance` is the advance from `hmtx` with the `HVAR` delta applied, without any
+    /// rounding.
+    ///
+    /// # Notes
+    /// - `coords` should be normalized. Invalid coordinates are ignored and the default instance
+    ///   is used instead.
+    /// - Characters that are not mapped use `.notdef`.
+    /// - Default ignorable characters, see `util::unicode::is_default_ignorable`, use the glyph of
+    ///   U+0020 SPACE with no advance so they are not rendered. Without a space glyph they keep
+    ///   their own glyph, still with no advance. They are not positioned by `GPOS`.
+    /// - Shaping is scoped to Latin, the lookups of the `latn` script are applied regardless of
+    ///   the script of `text`. Scripts that require contextual substitution, reordering or mark
+    ///   positioning are returned unshaped.
+    pub fn shape(&self, text: &str, coords: &[f32], features: &[Feature]) -> Vec<ShapedGlyph> {
+        let subtable = match self.unicode_subtable() {
+            Some(some) => some,
+            None => return Vec::new(),
+        };
+
+        let mut glyph_ids = Vec::new();
+        subtable.map_str(text, &mut glyph_ids);
+
+        let space_glyph_id = subtable.glyph_id_map.get(&0x20).copied();
+        let mut ignorable_clusters = BTreeSet::new();
+
+        let mut glyphs: Vec<ClusterGlyph> = text
+            .char_indices()
+            .zip(glyph_ids)
+            .map(|((cluster, c), glyph_id)| {
+                if is_default_ignorable(c) {
+                    ignorable_clusters.insert(cluster);
+
+                    ClusterGlyph {
+                        glyph_id: space_glyph_id.unwrap_or(glyph_id),
+                        cluster,
+                    }
+                } else {
+                    ClusterGlyph {
+                        glyph_id,
+                        cluster,
+                    }
+                }
+            })
+            .collect();
+
+        for feature in features.iter() {
+            apply_substitutions(self, &mut glyphs, &feature.0);
+        }
+
+        let coords = if coords.is_empty() || is_default_normalized(coords) {
+            None
+        } else {
+            Some(coords.to_vec())
+        };
+
+        let mut shaped: Vec<ShapedGlyph> = glyphs
+            .iter()
+            .map(|glyph| {
+                let x_advance = if ignorable_clusters.contains(&glyph.cluster) {
+                    0.0
+                } else {
+                    self.font_unit_advance(glyph.glyph_id, coords.as_ref())
+                };
+
+                ShapedGlyph {
+                    glyph_id: glyph.glyph_id,
+                    x_advance,
+                    x_offset: 0.0,
+                    y_offset: 0.0,
+                    cluster: glyph.cluster,
+                }
+            })
+            .collect();
+
+        let positioned: Vec<usize> = (0..glyphs.len())
+            .filter(|i| !ignorable_clusters.contains(&glyphs[*i].cluster))
+            .collect();
+
+        let positioned_ids: Vec<u16> = positioned.iter().map(|i| glyphs[*i].glyph_id).collect();
+
+        for feature in features.iter() {
+            let adjustments = match pair_adjustments(
+                self,
+                &positioned_ids,
+                &feature.0,
+                coords.as_deref().unwrap_or(&[]),
+            ) {
+                Ok(ok) => ok,
+                Err(_) => {
+                    pair_adjustments(self, &positioned_ids, &feature.0, &[]).unwrap_or_default()
+                },
+            };
+
+            for (i, adjustment) in positioned.iter().zip(adjustments) {
+                shaped[*i].x_advance += adjustment.x_advance;
+                shaped[*i].x_offset += adjustment.x_offset;
+                shaped[*i].y_offset += adjustment.y_offset;
+            }
+        }
+
+        shaped
+    }
+
+    /// The advance in font units from `hmtx` with the `HVAR` delta applied.
+    ///
+    /// Glyphs without an advance have none, invalid coordinates are ignored.
+    fn font_unit_advance(&self, glyph_id: u16, coords: Option<&Vec<f32>>) -> f32 {
+        let metrics_glyph_id = self.metrics_glyph_id(glyph_id);
+
+        let advance_w = match self
+            .hmtx
+            .as_ref()
+            .and_then(|hmtx| hmtx.advance_width(metrics_glyph_id))
+        {
+            Some(some) => some as f32,
+            None => return 0.0,
+        };
+
+        let delta = coords
+            .and_then(|coords| advance_width(self, metrics_glyph_id, coords).ok())
+            .unwrap_or(0.0);
+
+        advance_w + delta
+    }
+
+    /// The glyph ids required to render `text`, e.g. for subsetting or to preload glyphs.
+    ///
+    /// Every `char` is mapped to its glyph id, and the components of composite glyphs are
+    /// included along with the composite, as are their components in turn.
+    ///
+    /// # Notes
+    /// - `.notdef` (glyph id zero) is included when any `char` is not mapped.
+    /// - Default ignorable characters are skipped as they are not rendered, see
+    ///   `util::unicode::is_default_ignorable`.
+    /// - Components are only included when `glyf` was parsed.
+    pub fn required_glyphs(&self, text: &str) -> BTreeSet<u16> {
+        let mut glyph_ids = Vec::new();
+
+        match self.unicode_subtable() {
+            Some(subtable) => {
+                subtable.map_str(text, &mut glyph_ids);
+
+                glyph_ids = text
+                    .chars()
+                    .zip(glyph_ids)
+                    .filter(|(c, _)| !is_default_ignorable(*c))
+                    .map(|(_, glyph_id)| glyph_id)
+                    .collect();
+            },
+            None => {
+                if text.chars().any(|c| !is_default_ignorable(c)) {
+                    glyph_ids.push(0);
+                }
+            },
+        }
+
+        let mut required = BTreeSet::new();
+
+        while let Some(glyph_id) = glyph_ids.pop() {
+            // Also guards against composites that reference themselves.
+            if !required.insert(glyph_id) {
+                continue;
+            }
+
+            if let Some(components) = self
+                .glyf
+                .as_ref()
+                .and_then(|glyf| glyf.components.get(&glyph_id))
+            {
+                glyph_ids.extend_from_slice(components);
+            }
+        }
+
+        required
+    }
+
+    /// The Unicode subtable of `cmap`.
+    ///
+    /// Windows full repertoire (3, 10) is preferred, followed by Windows BMP (3, 1), the first
+    /// Unicode platform (0) subtable and then any other Windows subtable, e.g. symbol (3, 0).
+    /// Subtables of unsupported formats are skipped when parsing `cmap`, so a format 12 (3, 10)
+    /// subtable falls back to (3, 1).
+    ///
+    /// Returns `None` when `cmap` was not requested with `from_bytes_selective`.
+    pub(crate) fn unicode_subtable(&self) -> Option<&CmapSubtable> {
+        let records = &self.cmap.as_ref()?.encoding_records;
+
+        [(3, Some(10)), (3, Some(1)), (0, None), (3, None)]
+            .into_iter()
+            .find_map(|(platform_id, encoding_id)| {
+                records.iter().find(|record| {
+                    record.platform_id == platform_id
+                        && encoding_id
+                            .map(|encoding_id| record.encoding_id == encoding_id)
+                            .unwrap_or(true)
+                })
+            })
+            .map(|record| &record.subtable)
+    }
+
+    /// The advance in pixels that `ScaledGlyph::evaluate` would produce prior to rounding.
+    fn unrounded_advance(
+        &self,
+        glyph_id: u16,
+        coords: Option<&Vec<f32>>,
+        size: f32,
+    ) -> Result<f32, ScaledGlyphErr> {
+        if glyph_id >= self.num_glyphs() {
+            return Err(ScaledGlyphErr::Missing);
+        }
+
+        let metrics_glyph_id = self.metrics_glyph_id(glyph_id);
+
+        let mut advance_w = self
+            .try_hmtx_table()
+            .map_err(|_| ScaledGlyphErr::Missing)?
+            .advance_width(metrics_glyph_id)
+            .ok_or(ScaledGlyphErr::Missing)? as f32;
+
+        if let Some(coords) = coords {
+            advance_w += advance_width(self, metrics_glyph_id, coords)
+                .map_err(|_| ScaledGlyphErr::InvalidCoords)?;
+        }
+
+        let scaler = (1.0 / self.head.units_per_em as f32) * size;
+        advance_w *= scaler;
+
+        let outline = match self
+            .try_glyf_table()
+            .map_err(|_| ScaledGlyphErr::Missing)?
+            .outlines
+            .get(&glyph_id)
+        {
+            Some(some) => some,
+            None => return Ok(advance_w),
+        };
+
+        let [x_min, y_min, x_max, y_max] = self
+            .varied_bounds(glyph_id, outline, coords.map(|coords| coords.as_slice()))
+            .map_err(|_| ScaledGlyphErr::InvalidCoords)?;
+
+        advance_w += ((x_max - x_min) - (outline.x_max - outline.x_min)) * scaler;
+
+        let x_max_raw = x_max * scaler;
+        let x_min_raw = x_min * scaler;
+        let (x_min_whole, x_max_whole) = pixel_bounds(x_min_raw, x_max_raw);
+        let (y_min_whole, y_max_whole) = pixel_bounds(y_min * scaler, y_max * scaler);
+
+        if f32_to_dimension(x_max_whole - x_min_whole).is_none()
+            || f32_to_dimension(y_max_whole - y_min_whole).is_none()
+        {
+            return Err(ScaledGlyphErr::Malformed);
+        }
+
+        advance_w -= (x_max_whole - x_min_whole) - (x_max_raw - x_min_raw);
+        Ok(advance_w)
+    }
+
+    /// Prepare a glyph at `size` for rendering.
+    ///
+    /// This is `ScaledGlyph::evaluate` returning the advance, bearings, bounds and outline in a
+    /// single struct for layout. Use `PreparedGlyph::into_scaled` to pass it to a rasterizer.
+    ///
+    /// # Notes
+    /// - `coords` should be normalized.
+    /// - The outline is normalized to the bitmap with `Y` down, see `PreparedGlyph::outline`.
+    pub fn prepare_glyph(
+        &self,
+        glyph_id: u16,
+        coords: Option<&[f32]>,
+        size: f32,
+    ) -> Result<PreparedGlyph, ScaledGlyphErr> {
+        ScaledGlyph::evaluate(self, coords, true, glyph_id, size).map(PreparedGlyph::from)
+    }
+
+    /// The left and right sidebearings of a glyph in font units.
+    ///
+    /// The left sidebearing is from `hmtx` and the right is the remainder of the advance after
+    /// the left sidebearing and the width of the outline. With `coords` the advance is varied by
+    /// `HVAR` and the outline by `gvar`, both sidebearings account for the varied bounds.
+    ///
+    /// # Notes
+    /// - `coords` should be normalized.
+    /// - Returns `None` if the glyph does not have an outline, or if `coords` are invalid.
+    pub fn glyph_sidebearings(&self, glyph_id: u16, coords: Option<&[f32]>) -> Option<(i16, i16)> {
+        let outline = self.glyf.as_ref()?.outlines.get(&glyph_id)?;
+        let hmtx = self.hmtx.as_ref()?;
+        let metrics_glyph_id = self.metrics_glyph_id(glyph_id);
+        let mut advance_w = hmtx.advance_width(metrics_glyph_id)? as f32;
+        let mut lsb = hmtx.lsb(glyph_id)? as f32;
+
+        let [x_min, _, x_max, _] = self.varied_bounds(glyph_id, outline, coords).ok()?;
+
+        if let Some(coords) = coords {
+            advance_w += advance_width(self, metrics_glyph_id, &coords.to_vec()).ok()?;
+            lsb += x_min - outline.x_min;
+        }
+
+        let rsb = advance_w - lsb - (x_max - x_min);
+        Some((float::round(lsb) as i16, float::round(rsb) as i16))
+    }
+
+    /// The outline of a glyph in font units with `gvar` applied for `coords`.
+    ///
+    /// Unlike the outline of a `ScaledGlyph` this is not scaled to a pixel size, which makes it
+    /// suitable for vector export, triangulation and boolean operations at full precision.
+    ///
+    /// # Notes
+    /// - `coords` should be normalized. Without `coords` the outline from `glyf` is returned.
+    /// - Returns `None` if the glyph does not have an outline, or if `coords` are invalid.
+    /// - The `VariedOutlineCache` of the font is used when set.
+    pub fn varied_outline(&self, glyph_id: u16, coords: Option<&[f32]>) -> Option<Outline> {
+        let coords = coords.map(|coords| coords.to_vec());
+        self.try_varied_outline(glyph_id, coords.as_ref())
+            .ok()
+            .flatten()
+    }
+
+    pub(crate) fn try_varied_outline(
+        &self,
+        glyph_id: u16,
+        coords: Option<&Vec<f32>>,
+    ) -> Result<Option<Outline>, ScaledGlyphErr> {
+        let glyf = self.try_glyf_table().map_err(|_| ScaledGlyphErr::Missing)?;
+
+        let mut outline = match glyf.outlines.get(&glyph_id) {
+            Some(some) => Outline::clone(some),
+            None => return Ok(None),
+        };
+
+        let coords = match coords {
+            Some(coords) if !is_default_normalized(coords) => coords,
+            _ => return Ok(Some(outline)),
+        };
+
+        #[cfg(feature = "std")]
+        let cache = self.varied_outline_cache();
+
+        #[cfg(feature = "std")]
+        if let Some(varied) = cache.and_then(|cache| cache.get(self.id, glyph_id, coords)) {
+            return Ok(Some(varied));
+        }
+
+        if let Some(composite) = glyf.composites.get(&glyph_id) {
+            outline = self.varied_composite(glyph_id, composite, coords)?;
+        } else {
+            match outline_apply_gvar(self, glyph_id, &mut outline, coords) {
+                Err(ImtUtilError::InvalidCoords) => return Err(ScaledGlyphErr::InvalidCoords),
+                Err(ImtUtilError::MalformedOutline) => return Err(ScaledGlyphErr::Malformed),
+                _ => (),
+            }
+        }
+
+        #[cfg(feature = "std")]
+        if let Some(cache) = cache {
+            cache.insert(self.id, glyph_id, coords, outline.clone());
+        }
+
+        Ok(Some(outline))
+    }
+
+    /// Compose the outline of a composite glyph from its varied components.
+    ///
+    /// The `gvar` deltas of the composite move the offsets of its components.
+    fn varied_composite(
+        &self,
+        glyph_id: u16,
+        composite: &[Component],
+        coords: &Vec<f32>,
+    ) -> Result<Outline, ScaledGlyphErr> {
+        let deltas = match gvar_component_deltas(self, glyph_id, composite.len(), coords) {
+            Ok(ok) => ok,
+            Err(ImtUtilError::InvalidCoords) => return Err(ScaledGlyphErr::InvalidCoords),
+            Err(_) => vec![[0.0; 2]; composite.len()],
+        };
+
+        let mut outline = Outline {
+            x_min: 0.0,
+            y_min: 0.0,
+            x_max: 0.0,
+            y_max: 0.0,
+            points: Vec::new(),
+            contours: Vec::new(),
+            geometry: Vec::new(),
+        };
+
+        // Only composites that were resolved have an outline, so components do not cycle.
+        for (component, delta) in composite.iter().zip(deltas) {
+            let component_outline =
+                match self.try_varied_outline(component.glyph_id, Some(coords))? {
+                    Some(some) => some,
+                    None => continue,
+                };
+
+            outline
+                .append_component(component, &component_outline, delta)
+                .map_err(|_| ScaledGlyphErr::Malformed)?;
+        }
+
+        outline.rebuild().map_err(|_| ScaledGlyphErr::Malformed)?;
+        Ok(outline)
+    }
+
+    /// The bounds of an outline with `gvar` applied, as `[x_min, y_min, x_max, y_max]`.
+    ///
+    /// The outline is not rebuilt. Only invalid coordinates result in an error, otherwise when
+    /// the deltas can not be computed the bounds of the default outline are used.
+    fn varied_bounds(
+        &self,
+        glyph_id: u16,
+        outline: &Outline,
+        coords: Option<&[f32]>,
+    ) -> Result<[f32; 4], ImtUtilError> {
+        let bounds = [outline.x_min, outline.y_min, outline.x_max, outline.y_max];
+
+        let coords = match coords {
+            Some(some) => some,
+            None => return Ok(bounds),
+        };
+
+        let glyf = self
+            .try_glyf_table()
+            .map_err(|_| ImtUtilError::MissingTable)?;
+
+        if glyf.composites.contains_key(&glyph_id) {
+            return match self.try_varied_outline(glyph_id, Some(&coords.to_vec())) {
+                Ok(Some(varied)) => Ok([varied.x_min, varied.y_min, varied.x_max, varied.y_max]),
+                Err(ScaledGlyphErr::InvalidCoords) => Err(ImtUtilError::InvalidCoords),
+                _ => Ok(bounds),
+            };
+        }
+
+        let point_deltas = match gvar_point_deltas(self, glyph_id, outline, coords) {
+            Ok(ok) => ok,
+            Err(ImtUtilError::InvalidCoords) => return Err(ImtUtilError::InvalidCoords),
+            Err(_) => return Ok(bounds),
+        };
+
+        let (mut x_min, mut x_max) = (f32::INFINITY, f32::NEG_INFINITY);
+        let (mut y_min, mut y_max) = (f32::INFINITY, f32::NEG_INFINITY);
+
+        for i in outline.contours.iter().cloned().flatten() {
+            let x = outline.points[i].x + point_deltas[i][0];
+            let y = outline.points[i].y + point_deltas[i][1];
+            x_min = x_min.min(x);
+            x_max = x_max.max(x);
+            y_min = y_min.min(y);
+            y_max = y_max.max(y);
+        }
+
+        Ok([x_min, y_min, x_max, y_max])
+    }
+
+    /// The recommended distance between baselines in pixels at `size`.
+    ///
+    /// This is the sum of the ascender, descender and line gap. The typographic metrics of the
+    /// `OS/2` table are preferred with `hhea` used when they are not available.
+    ///
+    /// # Notes
+    /// - `coords` should be normalized. `MVAR` deltas are applied when `coords` is not empty.
+    /// - Invalid coordinates are ignored and the default instance is used instead.
+    pub fn line_height(&self, coords: &[f32], size: f32) -> f32 {
+        let (ascender, descender, line_gap) = match self.os2.as_ref() {
+            Some(os2) => {
+                (
+                    os2.s_typo_ascender,
+                    os2.s_typo_descender,
+                    os2.s_typo_line_gap,
+                )
+            },
+            None => (self.hhea.ascender, self.hhea.descender, self.hhea.line_gap),
+        };
+
+        let mut line_height = ascender as f32 - descender as f32 + line_gap as f32;
+
+        if !coords.is_empty() {
+            let delta = |value_tag| metric_delta(self, value_tag, coords).unwrap_or(0.0);
+            line_height += delta(mvar_table::value_tag::HASC) - delta(mvar_table::value_tag::HDSC)
+                + delta(mvar_table::value_tag::HLGP);
+        }
+
+        (line_height / self.head.units_per_em as f32) * size
+    }
+
+    /// The position in font units of a baseline for a script along the horizontal axis.
+    ///
+    /// `tag` is a baseline tag such as `base_table::baseline_tag::IDEO` and `script` is an
+    /// OpenType script tag. If the script is not listed the `DFLT` script is used instead.
+    ///
+    /// # Notes
+    /// - Returns `None` if the font does not have a `BASE` table or the baseline is not defined.
+    pub fn baseline_offset(&self, tag: u32, script: u32) -> Option<i16> {
+        let axis = self.base.as_ref()?.horizontal.as_ref()?;
+        let index = axis
+            .baseline_tags
+            .iter()
+            .position(|baseline| *baseline == tag)?;
+
+        axis.scripts
+            .get(&script)
+            .or_else(|| axis.scripts.get(&crate::parse::tag(b"DFLT")))?
+            .base_coords
+            .get(index)
+            .copied()
+    }
+
+    /// Iterate the glyphs that have an outline in order of glyph id.
+    ///
+    /// # Notes
+    /// - Glyphs without contours (e.g. space) are skipped.
+    /// - Nothing is iterated when `glyf` was not requested with `from_bytes_selective`.
+    pub fn glyphs_with_outlines(&self) -> impl Iterator<Item = (u16, &Outline)> {
+        self.glyf
+            .iter()
+            .flat_map(|glyf| glyf.outlines.iter())
+            .map(|(glyph_id, outline)| (*glyph_id, outline.as_ref()))
+    }
+
+    /// Check if the glyph has any `gvar` variation data.
+    ///
+    /// # Notes
+    /// - The advance of a glyph may still vary through `HVAR` when this returns `false`.
+    pub fn glyph_varies(&self, glyph_id: u16) -> bool {
+        let gvar = match self.gvar.as_ref() {
+            Some(some) => some,
+            None => return false,
+        };
+
+        let mut pending = vec![glyph_id];
+        let mut visited = BTreeSet::new();
+
+        while let Some(glyph_id) = pending.pop() {
+            // Also guards against composites that reference themselves.
+            if !visited.insert(glyph_id) {
+                continue;
+            }
+
+            if gvar.glyph_variations.contains_key(&glyph_id) {
+                return true;
+            }
+
+            // A composite varies when any of its components do.
+            if let Some(components) = self
+                .glyf
+                .as_ref()
+                .and_then(|glyf| glyf.components.get(&glyph_id))
+            {
+                pending.extend(components.iter().copied());
+            }
+        }
+
+        false
+    }
+
+    /// Check if two sets of normalized coordinates produce identical geometry for a glyph.
+    ///
+    /// # Notes
+    /// - Only the `gvar` tuples are compared, so advances varied by `HVAR` may still differ.
+    /// - Glyphs without variation data are always equivalent, composites are equivalent when
+    ///   they and all of their components are.
+    /// - Returns `false` if either has a length other than the axis count of `gvar`.
+    pub fn coords_equivalent(&self, glyph_id: u16, a: &[f32], b: &[f32]) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+
+        let gvar = match self.gvar.as_ref() {
+            Some(some) => some,
+            None => return true,
+        };
+
+        if a.len() != gvar.axis_count {
+            return false;
+        }
+
+        let mut pending = vec![glyph_id];
+        let mut visited = BTreeSet::new();
+
+        while let Some(glyph_id) = pending.pop() {
+            if !visited.insert(glyph_id) {
+                continue;
+            }
+
+            if let Some(glyph_variation) = gvar.glyph_variations.get(&glyph_id) {
+                if !glyph_variation
+                    .tuples
+                    .iter()
+                    .all(|tuple| tuple_scaler(tuple, a) == tuple_scaler(tuple, b))
+                {
+                    return false;
+                }
+            }
+
+            // A composite is only equivalent when all of its components are.
+            if let Some(components) = self
+                .glyf
+                .as_ref()
+                .and_then(|glyf| glyf.components.get(&glyph_id))
+            {
+                pending.extend(components.iter().copied());
+            }
+        }
+
+        true
+    }
+
+    /// The variation axes of the font, e.g. to present as sliders.
+    ///
+    /// Hidden axes are only included when `include_hidden` is `true`, in which case they are
+    /// marked by `VariationAxis::hidden`.
+    ///
+    /// # Notes
+    /// - Returns an empty `Vec` if the font does not have a `fvar` table.
+    pub fn variation_axes(&self, include_hidden: bool) -> Vec<VariationAxis> {
+        let fvar = match self.fvar.as_ref() {
+            Some(some) => some,
+            None => return Vec::new(),
+        };
+
+        fvar.axes
+            .iter()
+            .enumerate()
+            .filter(|(_, axis)| include_hidden || !axis.hidden_axis())
+            .map(|(index, axis)| {
+                VariationAxis {
+                    index,
+                    tag: axis.axis_tag.to_be_bytes(),
+                    name: self
+                        .name
+                        .as_ref()
+                        .and_then(|name| name.name(axis.axis_name_id))
+                        .map(|name| name.to_string()),
+                    min_value: axis.min_value,
+                    default_value: axis.default_value,
+                    max_value: axis.max_value,
+                    hidden: axis.hidden_axis(),
+                }
+            })
+            .collect()
+    }
+
+    /// Build coordinates for every axis, starting from their defaults and applying `overrides`.
+    ///
+    /// The returned coordinates are in user space and not normalized.
+    ///
+    /// # Notes
+    /// - Returns `ImtUtilError::MissingTable` if the font does not have a `fvar` table.
+    /// - Returns `ImtUtilError::UnknownAxis` if a tag does not correspond to an axis.
+    pub fn coords_from<'a>(
+        &self,
+        overrides: impl IntoIterator<Item = (&'a [u8; 4], f32)>,
+    ) -> Result<Vec<f32>, ImtUtilError> {
+        overrides
+            .into_iter()
+            .fold(
+                CoordBuilder::new(self).on_missing(MissingAxisPolicy::Error),
+                |builder, (tag, value)| builder.set(tag, value),
+            )
+            .build()
+    }
+
+    /// Retrieve the coordinates of a named instance by its subfamily name, e.g. `"Bold Italic"`.
+    ///
+    /// Names are compared case-insensitively. When `normalized` is `true` the coordinates are
+    /// normalized, otherwise they are in user space.
+    ///
+    /// # Notes
+    /// - Returns `None` if no instance has a matching name.
+    pub fn coords_for_instance_name(&self, name: &str, normalized: bool) -> Option<Vec<f32>> {
+        let name = name.to_lowercase();
+
+        let instance = self.fvar.as_ref()?.instances.iter().find(|instance| {
+            self.name
+                .as_ref()
+                .and_then(|name_table| name_table.name(instance.sub_family_name_id))
+                .map(|instance_name| instance_name.to_lowercase() == name)
+                .unwrap_or(false)
+        })?;
+
+        let mut coords = instance.coordinates.clone();
+
+        if normalized {
+            normalize_axis_coords(self, &mut coords).ok()?;
+        }
+
+        Some(coords)
+    }
+
+    /// Find the named instance that matches the provided normalized coordinates.
+    ///
+    /// Each instance's coordinates are normalized and compared axis by axis. An instance matches
+    /// when every axis is within `tolerance`. If multiple instances match the closest is returned.
+    pub fn matching_instance(&self, normalized_coords: &[f32], tolerance: f32) -> Option<usize> {
+        let fvar = self.fvar.as_ref()?;
+        let mut best: Option<(usize, f32)> = None;
+
+        for (i, instance) in fvar.instances.iter().enumerate() {
+            let mut coords = instance.coordinates.clone();
+
+            if normalize_axis_coords(self, &mut coords).is_err()
+                || coords.len() != normalized_coords.len()
+            {
+                continue;
+            }
+
+            let distance = coords
+                .iter()
+                .zip(normalized_coords.iter())
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f32::max);
+
+            if distance <= tolerance && best.map(|(_, d)| distance < d).unwrap_or(true) {
+                best = Some((i, distance));
+            }
+        }
+
+        best.map(|(i, _)| i)
+    }
+
+    /// The PostScript name of the font. (Name ID 6)
+    ///
+    /// This is the name that should be used when embedding the font within a PDF.
+    pub fn postscript_name(&self) -> Option<&str> {
+        self.name.as_ref()?.name(6)
+    }
+
+    /// The unique font identifier of the font. (Name ID 3)
+    pub fn unique_font_id(&self) -> Option<&str> {
+        self.name.as_ref()?.name(3)
+    }
+
+    /// A stable seed suitable for deriving the six letter tag of an embedded subset.
+    ///
+    /// # Notes
+    /// - This is `checksum_adjustment` of the `head` table, so it only changes when the font data
+    ///   changes.
+    /// - Subsets are unsigned, see `has_dsig`.
+    pub fn subset_tag_seed(&self) -> u32 {
+        self.head.checksum_adjustment
+    }
+
+    /// Estimate the amount of memory in bytes used by the parsed font.
+    ///
+    /// This includes the heap allocations of the larger tables: outlines, character maps,
+    /// glyph variations, metrics and names. Allocator overhead and the node overhead of maps are
+    /// not accounted for, so the actual usage is somewhat higher. The outline cache is excluded.
+    pub fn memory_estimate(&self) -> usize {
+        use core::mem::size_of;
+
+        #[allow(clippy::ptr_arg)]
+        fn vec_bytes<T>(vec: &Vec<T>) -> usize {
+            vec.capacity() * size_of::<T>()
+        }
+
+        fn map_bytes<K, V>(map: &BTreeMap<K, V>) -> usize {
+            map.len() * (size_of::<K>() + size_of::<V>())
+        }
+
+        let mut bytes = size_of::<Self>();
+
+        if let Some(glyf) = self.glyf.as_ref() {
+            bytes += map_bytes(&glyf.outlines)
+                + map_bytes(&glyf.use_my_metrics)
+                + map_bytes(&glyf.components)
+                + map_bytes(&glyf.composites);
+
+            for composite in glyf.composites.values() {
+                bytes += vec_bytes(composite);
+            }
+
+            let mut counted = BTreeSet::new();
+
+            // Shared outlines are only allocated once.
+            for outline in glyf.outlines.values() {
+                if !counted.insert(Arc::as_ptr(outline)) {
+                    continue;
+                }
+
+                bytes += size_of::<Outline>()
+                    + vec_bytes(&outline.points)
+                    + vec_bytes(&outline.contours)
+                    + vec_bytes(&outline.geometry);
+            }
+        }
+
+        if let Some(cmap) = self.cmap.as_ref() {
+            bytes += vec_bytes(&cmap.encoding_records);
+
+            for encoding_record in cmap.encoding_records.iter() {
+                bytes += map_bytes(&encoding_record.subtable.glyph_id_map);
+            }
+        }
+
+        if let Some(gvar) = self.gvar.as_ref() {
+            bytes += vec_bytes(&gvar.shared_tuples) + map_bytes(&gvar.glyph_variations);
+
+            for variation in gvar.glyph_variations.values() {
+                bytes += vec_bytes(&variation.tuples);
+
+                for tuple in variation.tuples.iter() {
+                    bytes += vec_bytes(&tuple.peak)
+                        + vec_bytes(&tuple.points)
+                        + vec_bytes(&tuple.deltas);
+
+                    if let Some(interm) = tuple.interm.as_ref() {
+                        bytes += vec_bytes(&interm.start) + vec_bytes(&interm.end);
+                    }
+                }
+            }
+        }
+
+        if let Some(hvar) = self.hvar.as_ref() {
+            let store = &hvar.item_variation_store;
+            bytes += vec_bytes(&store.regions) + vec_bytes(&store.item_data);
+
+            for region in store.regions.iter() {
+                bytes += vec_bytes(&region.axes);
+            }
+
+            for item_data in store.item_data.iter() {
+                bytes += vec_bytes(&item_data.region_indexes) + vec_bytes(&item_data.delta_sets);
+
+                for delta_set in item_data.delta_sets.iter() {
+                    bytes += vec_bytes(&delta_set.data);
+                }
+            }
+
+            for map in [&hvar.advance_map, &hvar.lsb_map, &hvar.rsb_map]
+                .into_iter()
+                .flatten()
+            {
+                bytes += vec_bytes(&map.map_data);
+            }
+        }
+
+        if let Some(hmtx) = self.hmtx.as_ref() {
+            bytes += vec_bytes(&hmtx.hor_metric) + vec_bytes(&hmtx.left_side_bearings);
+        }
+
+        if let Some(loca) = self.loca.as_ref() {
+            bytes += vec_bytes(&loca.offsets);
+        }
+
+        if let Some(name) = self.name.as_ref() {
+            bytes += vec_bytes(&name.name_records) + vec_bytes(&name.lang_tag_records);
+
+            for name_record in name.name_records.iter() {
+                bytes += name_record.name.capacity();
+            }
+
+            for lang_tag_record in name.lang_tag_records.iter() {
+                bytes += lang_tag_record.0.capacity();
+            }
+        }
+
+        if let Some(cvt) = self.cvt.as_ref() {
+            bytes += vec_bytes(&cvt.values);
+        }
+
+        for instructions in [
+            self.fpgm.as_ref().map(|fpgm| &fpgm.instructions),
+            self.prep.as_ref().map(|prep| &prep.instructions),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            bytes += instructions.capacity();
+        }
+
+        bytes
+    }
+
+    /// The smallest size in pixels per em the font is recommended to be rendered at.
+    ///
+    /// Below this size the designer considers the font unreadable, so UIs may warn or switch to
+    /// another font.
+    pub fn lowest_rec_ppem(&self) -> u16 {
+        self.head.lowest_rec_ppem
+    }
+
+    /// Whether the font is bold according to `head.mac_style`.
+    ///
+    /// # Notes
+    /// - For variable fonts this only describes the default instance.
+    pub fn is_bold(&self) -> bool {
+        self.head.mac_style & 0x0001 != 0
+    }
+
+    /// Whether the font is italic according to `head.mac_style`.
+    ///
+    /// # Notes
+    /// - For variable fonts this only describes the default instance.
+    pub fn is_italic(&self) -> bool {
+        self.head.mac_style & 0x0002 != 0
+    }
+
+    /// A key to sort the styles of a family by, from thin to black with italics following their
+    /// upright counterpart.
+    ///
+    /// Returns `(weight, italic)` from `OS/2.usWeightClass` and `OS/2.fsSelection`. When the font
+    /// does not have an `OS/2` table `head.mac_style` is used instead, which results in a weight
+    /// of `700` for bold and `400` otherwise.
+    ///
+    /// # Notes
+    /// - For variable fonts this only describes the default instance.
+    pub fn style_sort_key(&self) -> (u16, bool) {
+        match self.os2.as_ref() {
+            Some(os2) => (os2.us_weight_class, os2.is_italic()),
+            None => (if self.is_bold() { 700 } else { 400 }, self.is_italic()),
+        }
+    }
+
+    /// Whether the font contains a digital signature. (`DSIG` table)
+    ///
+    /// # Notes
+    /// - Any modification of the font data, such as subsetting, invalidates the signature. A
+    ///   subset must omit the `DSIG` table as some consumers reject fonts with an invalid one.
+    pub fn has_dsig(&self) -> bool {
+        self.has_dsig
+    }
+}
+
+fn try_table<T>(table: &Option<T>, source: ImtErrorSource) -> Result<&T, ImtError> {
+    table.as_ref().ok_or(ImtError {
+        kind: ImtErrorKind::MissingTable,
+        source,
+    })
+}
+
+fn required_table<'a, T>(table: &'a Option<T>, tag: &str) -> &'a T {
+    match table.as_ref() {
+        Some(some) => some,
+        None => {
+            panic!(
+                "The `{}` table was not requested with `Font::from_bytes_selective`.",
+                tag
+            )
+        },
+    }
 }
@@ -0,0 +1,30 @@
+use alloc::vec::Vec;
+
+use crate::error::*;
+
+/// Corresponds to the `fpgm` table.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/fpgm>
+///
+/// # Notes
+/// - Only parsed when `FontOptions::instructions` is enabled.
+/// - Instructions are not interpreted.
+#[derive(Debug, Clone)]
+pub struct FpgmTable {
+    /// Instructions executed once when the font is first used.
+    pub instructions: Vec<u8>,
+}
+
+impl FpgmTable {
+    pub fn try_parse(bytes: &[u8], table_offset: usize) -> Result<Self, ImtError> {
+        if table_offset > bytes.len() {
+            return Err(ImtError {
+                kind: ImtErrorKind::Truncated,
+                source: ImtErrorSource::FpgmTable,
+            });
+        }
+
+        Ok(Self {
+            instructions: bytes[table_offset..].to_vec(),
+        })
+    }
+}
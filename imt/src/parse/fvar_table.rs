@@ -1,3 +1,6 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
 use crate::error::*;
 use crate::parse::{read_fixed, read_u16, read_u32};
 
@@ -98,8 +101,71 @@ impl FvarTable {
             instances,
         })
     }
+
+    /// Sample the design space as a grid of coordinates.
+    ///
+    /// Each axis is sampled `steps_per_axis` times evenly from its minimum to its maximum value
+    /// and the cartesian product of the samples is returned. With a single step the default value
+    /// is used. Coordinates are in user space and not normalized.
+    ///
+    /// Hidden axes are kept at their default value unless `include_hidden` is `true`.
+    ///
+    /// # Notes
+    /// - The amount of coordinates is capped at `SAMPLE_GRID_LIMIT`. Axes are varied in order and
+    ///   once varying another axis would exceed the cap, it and all following axes are kept at
+    ///   their default value.
+    pub fn sample_grid(&self, steps_per_axis: usize, include_hidden: bool) -> Vec<Vec<f32>> {
+        let steps = steps_per_axis.max(1);
+        let mut varied_axes = Vec::new();
+        let mut count: usize = 1;
+
+        for (i, axis) in self.axes.iter().enumerate() {
+            if axis.hidden_axis() && !include_hidden {
+                continue;
+            }
+
+            match count.checked_mul(steps) {
+                Some(next) if next <= SAMPLE_GRID_LIMIT => {
+                    count = next;
+                    varied_axes.push(i);
+                },
+                _ => break,
+            }
+        }
+
+        let mut grid = vec![self
+            .axes
+            .iter()
+            .map(|axis| axis.default_value)
+            .collect::<Vec<f32>>()];
+
+        if steps == 1 {
+            return grid;
+        }
+
+        for i in varied_axes {
+            let axis = &self.axes[i];
+            let mut next = Vec::with_capacity(grid.len() * steps);
+
+            for coords in grid.iter() {
+                for step in 0..steps {
+                    let mut coords = coords.clone();
+                    coords[i] = axis.min_value
+                        + ((axis.max_value - axis.min_value) * (step as f32 / (steps - 1) as f32));
+                    next.push(coords);
+                }
+            }
+
+            grid = next;
+        }
+
+        grid
+    }
 }
 
+/// The maximum amount of coordinates returned by `FvarTable::sample_grid`.
+pub const SAMPLE_GRID_LIMIT: usize = 1024;
+
 #[derive(Debug, Clone)]
 pub struct VariationAxisRecord {
     pub axis_tag: u32,
@@ -122,6 +188,7 @@ impl VariationAxisRecord {
         }
     }
 
+    /// Whether the axis should not be exposed to users, e.g. as a slider.
     pub fn hidden_axis(&self) -> bool {
         self.flags & 0x0001 == 0x0001
     }
@@ -0,0 +1,91 @@
+use crate::error::*;
+use crate::parse::{read_u16, read_u32, ClassDef, ItemVariationStore};
+
+const TRUNCATED: ImtError = ImtError {
+    kind: ImtErrorKind::Truncated,
+    source: ImtErrorSource::GdefTable,
+};
+
+/// Corresponds to the `GDEF` table.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/gdef>
+///
+/// # Notes
+/// - The attachment point, ligature caret & mark glyph set lists are not parsed.
+#[derive(Debug, Clone)]
+pub struct GdefTable {
+    pub major_version: u16,
+    pub minor_version: u16,
+    /// Classes are 1 for base glyphs, 2 for ligatures, 3 for marks & 4 for components.
+    pub glyph_class_def: Option<ClassDef>,
+    pub mark_attach_class_def: Option<ClassDef>,
+    /// Deltas of the variation index device tables of `GPOS`.
+    pub item_variation_store: Option<ItemVariationStore>,
+}
+
+impl GdefTable {
+    pub fn try_parse(bytes: &[u8], table_offset: usize) -> Result<Self, ImtError> {
+        if table_offset + 12 > bytes.len() {
+            return Err(TRUNCATED);
+        }
+
+        let major_version = read_u16(bytes, table_offset);
+        let minor_version = read_u16(bytes, table_offset + 2);
+
+        if major_version != 1 || !matches!(minor_version, 0 | 2 | 3) {
+            return Err(ImtError {
+                kind: ImtErrorKind::UnexpectedVersion,
+                source: ImtErrorSource::GdefTable,
+            });
+        }
+
+        let class_def = |offset: usize| -> Result<Option<ClassDef>, ImtError> {
+            match read_u16(bytes, offset) {
+                0 => Ok(None),
+                class_def_offset => {
+                    Ok(Some(ClassDef::try_parse(
+                        bytes,
+                        table_offset + class_def_offset as usize,
+                        ImtErrorSource::GdefTable,
+                    )?))
+                },
+            }
+        };
+
+        let glyph_class_def = class_def(table_offset + 4)?;
+        let mark_attach_class_def = class_def(table_offset + 10)?;
+
+        let item_variation_store = if minor_version >= 3 {
+            if table_offset + 18 > bytes.len() {
+                return Err(TRUNCATED);
+            }
+
+            match read_u32(bytes, table_offset + 14) {
+                0 => None,
+                offset => {
+                    Some(ItemVariationStore::try_parse(
+                        bytes,
+                        table_offset + offset as usize,
+                    )?)
+                },
+            }
+        } else {
+            None
+        };
+
+        Ok(Self {
+            major_version,
+            minor_version,
+            glyph_class_def,
+            mark_attach_class_def,
+            item_variation_store,
+        })
+    }
+
+    /// The class of a glyph, zero if it isn't classified.
+    pub fn glyph_class(&self, glyph_id: u16) -> u16 {
+        self.glyph_class_def
+            .as_ref()
+            .map(|class_def| class_def.class(glyph_id))
+            .unwrap_or(0)
+    }
+}
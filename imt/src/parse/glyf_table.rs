@@ -1,8 +1,11 @@
-use std::collections::BTreeMap;
-use std::ops::Range;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Range;
 
 use crate::error::*;
-use crate::parse::{read_i16, read_u16, LocaTable};
+use crate::parse::{read_f2dot14, read_i16, read_u16, LocaTable};
+use crate::util::float;
 
 const MALFORMED: ImtError = ImtError {
     kind: ImtErrorKind::Malformed,
@@ -14,9 +17,50 @@ const TRUNCATED: ImtError = ImtError {
     source: ImtErrorSource::GlyfTable,
 };
 
+/// The maximum nesting of composite glyphs, deeper composites are not resolved.
+pub(crate) const MAX_COMPONENT_DEPTH: usize = 16;
+
 #[derive(Debug, Clone)]
 pub struct GlyfTable {
-    pub outlines: BTreeMap<u16, Outline>,
+    /// Glyphs whose `loca` ranges are equal share a single outline, see `shared_outline_count`.
+    pub outlines: BTreeMap<u16, Arc<Outline>>,
+    /// Composite glyphs that use the metrics of one of their components. (`USE_MY_METRICS`)
+    ///
+    /// Keyed by the glyph id of the composite with the value being the glyph id of the component.
+    pub use_my_metrics: BTreeMap<u16, u16>,
+    /// Glyph ids of the components of composite glyphs in the order they appear.
+    ///
+    /// Keyed by the glyph id of the composite.
+    pub components: BTreeMap<u16, Vec<u16>>,
+    /// The components of composite glyphs along with their transform and placement.
+    ///
+    /// Keyed by the glyph id of the composite. The outline of a composite is included within
+    /// `outlines` when all of its components could be resolved.
+    pub composites: BTreeMap<u16, Vec<Component>>,
+}
+
+/// A component of a composite glyph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Component {
+    /// The glyph id of the glyph that is placed.
+    pub glyph_id: u16,
+    pub transform: ComponentTransform,
+    pub placement: ComponentPlacement,
+}
+
+/// How a component is positioned within its composite glyph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComponentPlacement {
+    /// The component is moved by an offset after being transformed. (`ARGS_ARE_XY_VALUES`)
+    Offset { x: f32, y: f32 },
+    /// The component is moved so that its point aligns with a point of the composite.
+    ///
+    /// The point of the composite is indexed within the points of the components placed before
+    /// this one.
+    Anchor {
+        composite_point: u16,
+        component_point: u16,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -68,18 +112,107 @@ impl OutlineGeometry {
                 p3,
             } => {
                 OutlinePoint {
-                    x: ((1.0 - t).powi(2) * p1.x)
+                    x: (float::powi(1.0 - t, 2) * p1.x)
                         + (2.0 * (1.0 - t) * t * p2.x)
-                        + (t.powi(2) * p3.x),
-                    y: ((1.0 - t).powi(2) * p1.y)
+                        + (float::powi(t, 2) * p3.x),
+                    y: (float::powi(1.0 - t, 2) * p1.y)
                         + (2.0 * (1.0 - t) * t * p2.y)
-                        + (t.powi(2) * p3.y),
+                        + (float::powi(t, 2) * p3.y),
                 }
             },
         }
     }
 }
 
+/// An event of a path, as consumed by vector graphics libraries.
+///
+/// See `Outline::path_events`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathEvent {
+    /// Begin a new contour.
+    MoveTo {
+        to: OutlinePoint,
+    },
+    LineTo {
+        to: OutlinePoint,
+    },
+    QuadTo {
+        ctrl: OutlinePoint,
+        to: OutlinePoint,
+    },
+    /// Close the current contour.
+    Close,
+}
+
+/// Iterator over the `PathEvent`s of an `Outline`.
+#[derive(Debug, Clone)]
+pub struct PathEvents<'a> {
+    geometry: core::slice::Iter<'a, OutlineGeometry>,
+    pending: Option<&'a OutlineGeometry>,
+    start: Option<OutlinePoint>,
+    current: Option<OutlinePoint>,
+}
+
+impl<'a> Iterator for PathEvents<'a> {
+    type Item = PathEvent;
+
+    fn next(&mut self) -> Option<PathEvent> {
+        let geometry = match self.pending.take().or_else(|| self.geometry.next()) {
+            Some(some) => some,
+            None => return self.start.take().map(|_| PathEvent::Close),
+        };
+
+        let (p1, event, end) = match geometry {
+            OutlineGeometry::Segment {
+                p1,
+                p2,
+            } => {
+                (
+                    p1,
+                    PathEvent::LineTo {
+                        to: p2.clone(),
+                    },
+                    p2,
+                )
+            },
+            OutlineGeometry::QuadraticCurve {
+                p1,
+                p2,
+                p3,
+            } => {
+                (
+                    p1,
+                    PathEvent::QuadTo {
+                        ctrl: p2.clone(),
+                        to: p3.clone(),
+                    },
+                    p3,
+                )
+            },
+        };
+
+        if self.start.is_some() && self.current.as_ref() != Some(p1) {
+            // Not continuous with the previous geometry, so it belongs to the next contour.
+            self.pending = Some(geometry);
+            self.start = None;
+            return Some(PathEvent::Close);
+        }
+
+        if self.start.is_none() {
+            self.pending = Some(geometry);
+            self.start = Some(p1.clone());
+            self.current = Some(p1.clone());
+
+            return Some(PathEvent::MoveTo {
+                to: p1.clone(),
+            });
+        }
+
+        self.current = Some(end.clone());
+        Some(event)
+    }
+}
+
 /// A struct referencing the raw point parsed from font data.
 #[derive(Debug, Clone, PartialEq)]
 pub struct OutlineRawPoint {
@@ -190,6 +323,248 @@ impl Outline {
         self.geometry = geometry;
         Ok(())
     }
+
+    /// Append the points of `outline` placed as `component` of this composite outline.
+    ///
+    /// `delta` is added to the offset of the component, e.g. from `gvar`. The outline is not
+    /// rebuilt.
+    pub(crate) fn append_component(
+        &mut self,
+        component: &Component,
+        outline: &Outline,
+        delta: [f32; 2],
+    ) -> Result<(), ImtError> {
+        let [xscale, scale01, scale10, yscale] = component.transform.as_matrix();
+
+        let transform = |point: &OutlineRawPoint| -> [f32; 2] {
+            [
+                (xscale * point.x) + (scale10 * point.y),
+                (scale01 * point.x) + (yscale * point.y),
+            ]
+        };
+
+        let [dx, dy] = match component.placement {
+            ComponentPlacement::Offset {
+                x,
+                y,
+            } => [x + delta[0], y + delta[1]],
+            ComponentPlacement::Anchor {
+                composite_point,
+                component_point,
+            } => {
+                let anchor = self.points.get(composite_point as usize).ok_or(MALFORMED)?;
+                let [x, y] = transform(
+                    outline
+                        .points
+                        .get(component_point as usize)
+                        .ok_or(MALFORMED)?,
+                );
+                [anchor.x - x, anchor.y - y]
+            },
+        };
+
+        // Point numbers are 16-bit, which also bounds the size of deeply nested composites.
+        if self.points.len() + outline.points.len() > u16::MAX as usize {
+            return Err(MALFORMED);
+        }
+
+        let point_offset = self.points.len();
+        let contour_offset = self.contours.len() as u16;
+
+        for point in outline.points.iter() {
+            let [x, y] = transform(point);
+
+            self.points.push(OutlineRawPoint {
+                c: point.c + contour_offset,
+                x: x + dx,
+                y: y + dy,
+                control: point.control,
+            });
+        }
+
+        for range in outline.contours.iter() {
+            self.contours
+                .push((range.start + point_offset)..(range.end + point_offset));
+        }
+
+        Ok(())
+    }
+
+    /// Iterate the geometry of the outline as `PathEvent`s.
+    ///
+    /// This allows outlines to be passed to tessellators, rasterizers and writers that consume
+    /// path events, e.g. lyon, tiny-skia or an SVG path. Each contour begins with a `MoveTo` and
+    /// ends with a `Close`.
+    ///
+    /// # Notes
+    /// - Contours are stored one after another in `geometry`, so a contour ends where the next
+    ///   segment or curve does not continue from the previous one.
+    /// - Point values are the same as those of `geometry`.
+    pub fn path_events(&self) -> PathEvents<'_> {
+        PathEvents {
+            geometry: self.geometry.iter(),
+            pending: None,
+            start: None,
+            current: None,
+        }
+    }
+
+    /// The bounding box of each contour as `(x_min, y_min, x_max, y_max)`.
+    ///
+    /// Boxes are in the same order as `contours`. This allows hit-testing parts of a glyph,
+    /// e.g. the dot of an `i`.
+    ///
+    /// # Notes
+    /// - Like the bounds of the outline, these are computed from the points of the contour
+    ///   including control points, so a box may extend beyond the curves it contains.
+    pub fn contour_bounds(&self) -> Vec<(f32, f32, f32, f32)> {
+        self.contours
+            .iter()
+            .map(|range| {
+                self.points[range.clone()].iter().fold(
+                    (
+                        f32::INFINITY,
+                        f32::INFINITY,
+                        f32::NEG_INFINITY,
+                        f32::NEG_INFINITY,
+                    ),
+                    |(x_min, y_min, x_max, y_max), point| {
+                        (
+                            x_min.min(point.x),
+                            y_min.min(point.y),
+                            x_max.max(point.x),
+                            y_max.max(point.y),
+                        )
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// The area enclosed by the outline in font units.
+    ///
+    /// The area of each contour is signed by its direction, positive when counter-clockwise
+    /// with Y up. Holes wind opposite to the contour around them, so they subtract from it.
+    ///
+    /// # Notes
+    /// - TrueType specifies clockwise outer contours, but many fonts use the opposite direction.
+    ///   Use the magnitude when the direction does not matter.
+    /// - Curves are measured exactly, not approximated.
+    /// - The loops of a contour that crosses itself may cancel each other out.
+    pub fn signed_area(&self) -> f32 {
+        self.geometry.iter().map(geometry_signed_area).sum()
+    }
+
+    /// The area enclosed by each contour in font units, summed regardless of their direction.
+    ///
+    /// Unlike `signed_area` disjoint contours wound in opposite directions don't cancel each
+    /// other out, e.g. those of mirrored composite components. Holes add to the area as well,
+    /// so this is only suitable to check if an outline encloses any area at all.
+    ///
+    /// # Notes
+    /// - Contours are split the same way as `path_events`.
+    pub fn enclosed_area(&self) -> f32 {
+        let mut total = 0.0;
+        let mut contour = 0.0_f32;
+        let mut previous_end: Option<&OutlinePoint> = None;
+
+        for geometry in self.geometry.iter() {
+            let (start, end) = match geometry {
+                OutlineGeometry::Segment {
+                    p1,
+                    p2,
+                } => (p1, p2),
+                OutlineGeometry::QuadraticCurve {
+                    p1,
+                    p3,
+                    ..
+                } => (p1, p3),
+            };
+
+            if previous_end
+                .map(|previous| previous != start)
+                .unwrap_or(false)
+            {
+                total += contour.abs();
+                contour = 0.0;
+            }
+
+            contour += geometry_signed_area(geometry);
+            previous_end = Some(end);
+        }
+
+        total + contour.abs()
+    }
+
+    /// Check if any contour of the outline crosses itself.
+    ///
+    /// Where a contour crosses itself the winding of the enclosed areas depends on the direction
+    /// of each loop, so a malformed contour may leave unexpected holes with the nonzero rule.
+    /// This helps explain such artifacts, consumers may then choose to use the even-odd rule.
+    ///
+    /// # Notes
+    /// - This is intended as a debugging aid. Every pair of segments is compared, so it is
+    ///   `O(n²)` in the amount of segments. Curves are approximated by line segments.
+    /// - Well-formed glyphs may cross themselves too, e.g. the loop of an ampersand.
+    /// - Overlapping contours are not reported, these are common in variable fonts and are filled
+    ///   correctly with the nonzero rule.
+    /// - Segments that only touch, e.g. at a shared point, are not considered to cross.
+    pub fn is_self_intersecting(&self) -> bool {
+        const CURVE_SUBDIVISIONS: usize = 8;
+
+        let mut contours: Vec<Vec<[OutlinePoint; 2]>> = Vec::new();
+
+        for geometry in self.geometry.iter() {
+            let steps = if geometry.is_curve() {
+                CURVE_SUBDIVISIONS
+            } else {
+                1
+            };
+
+            let mut p1 = geometry.evaluate(0.0);
+
+            // Contours are stored one after another, so a gap marks the start of the next.
+            let continues = contours
+                .last()
+                .and_then(|contour| contour.last())
+                .map(|[_, end]| *end == p1)
+                .unwrap_or(false);
+
+            if !continues {
+                contours.push(Vec::new());
+            }
+
+            let contour = contours.last_mut().unwrap();
+
+            for i in 1..=steps {
+                let p2 = geometry.evaluate(i as f32 / steps as f32);
+                contour.push([p1.clone(), p2.clone()]);
+                p1 = p2;
+            }
+        }
+
+        contours.iter().any(|segments| {
+            segments
+                .iter()
+                .enumerate()
+                .any(|(i, a)| segments[(i + 1)..].iter().any(|b| segments_cross(a, b)))
+        })
+    }
+}
+
+/// Check if two line segments cross at a single point that is not an end point of either.
+fn segments_cross(a: &[OutlinePoint; 2], b: &[OutlinePoint; 2]) -> bool {
+    let orientation = |p: &OutlinePoint, q: &OutlinePoint, r: &OutlinePoint| {
+        ((q.x - p.x) * (r.y - p.y)) - ((q.y - p.y) * (r.x - p.x))
+    };
+
+    let d1 = orientation(&a[0], &a[1], &b[0]);
+    let d2 = orientation(&a[0], &a[1], &b[1]);
+    let d3 = orientation(&b[0], &b[1], &a[0]);
+    let d4 = orientation(&b[0], &b[1], &a[1]);
+
+    ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
 }
 
 #[derive(Clone, Copy)]
@@ -221,8 +596,151 @@ impl SimpleFlags {
     }
 }
 
-impl std::fmt::Debug for SimpleFlags {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// The transform of a component of a composite glyph.
+///
+/// Values are decoded from `F2DOT14`. Use `as_matrix` to apply the transform uniformly
+/// regardless of how it was encoded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComponentTransform {
+    /// The component is not scaled.
+    None,
+    /// The component is scaled uniformly. (`WE_HAVE_A_SCALE`)
+    Scale(f32),
+    /// The component is scaled separately on each axis. (`WE_HAVE_AN_X_AND_Y_SCALE`)
+    XYScale { x: f32, y: f32 },
+    /// A 2x2 matrix as `[xscale, scale01, scale10, yscale]`. (`WE_HAVE_A_TWO_BY_TWO`)
+    TwoByTwo([f32; 4]),
+}
+
+impl ComponentTransform {
+    /// Decode the transform following the arguments of a component with the provided `flags`.
+    pub fn try_parse(bytes: &[u8], offset: usize, flags: u16) -> Result<Self, ImtError> {
+        let flags = CompositeFlags(flags);
+
+        let transform = if flags.we_have_a_scale() {
+            Self::Scale(0.0)
+        } else if flags.we_have_an_x_and_y_scale() {
+            Self::XYScale {
+                x: 0.0,
+                y: 0.0,
+            }
+        } else if flags.we_have_a_two_by_two() {
+            Self::TwoByTwo([0.0; 4])
+        } else {
+            Self::None
+        };
+
+        if offset + transform.byte_len() > bytes.len() {
+            return Err(TRUNCATED);
+        }
+
+        Ok(match transform {
+            Self::None => Self::None,
+            Self::Scale(_) => Self::Scale(read_f2dot14(bytes, offset)),
+            Self::XYScale {
+                ..
+            } => {
+                Self::XYScale {
+                    x: read_f2dot14(bytes, offset),
+                    y: read_f2dot14(bytes, offset + 2),
+                }
+            },
+            Self::TwoByTwo(_) => {
+                Self::TwoByTwo([
+                    read_f2dot14(bytes, offset),
+                    read_f2dot14(bytes, offset + 2),
+                    read_f2dot14(bytes, offset + 4),
+                    read_f2dot14(bytes, offset + 6),
+                ])
+            },
+        })
+    }
+
+    /// The transform as `[xscale, scale01, scale10, yscale]`.
+    ///
+    /// A point is transformed with `x' = (xscale * x) + (scale10 * y)` and
+    /// `y' = (scale01 * x) + (yscale * y)`.
+    pub fn as_matrix(&self) -> [f32; 4] {
+        match *self {
+            Self::None => [1.0, 0.0, 0.0, 1.0],
+            Self::Scale(scale) => [scale, 0.0, 0.0, scale],
+            Self::XYScale {
+                x,
+                y,
+            } => [x, 0.0, 0.0, y],
+            Self::TwoByTwo(matrix) => matrix,
+        }
+    }
+
+    /// The amount of bytes the transform occupies within a component.
+    pub fn byte_len(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Scale(_) => 2,
+            Self::XYScale {
+                ..
+            } => 4,
+            Self::TwoByTwo(_) => 8,
+        }
+    }
+}
+
+/// The signed area between the origin and a segment or curve, see `Outline::signed_area`.
+fn geometry_signed_area(geometry: &OutlineGeometry) -> f32 {
+    let cross = |a: &OutlinePoint, b: &OutlinePoint| (a.x * b.y) - (b.x * a.y);
+
+    match geometry {
+        OutlineGeometry::Segment {
+            p1,
+            p2,
+        } => cross(p1, p2) / 2.0,
+        OutlineGeometry::QuadraticCurve {
+            p1,
+            p2,
+            p3,
+        } => {
+            // The chord plus two thirds of the triangle formed with the control.
+            let triangle = cross(p1, p2) + cross(p2, p3) + cross(p3, p1);
+            (cross(p1, p3) / 2.0) + (triangle / 3.0)
+        },
+    }
+}
+
+#[derive(Clone, Copy)]
+struct CompositeFlags(u16);
+
+impl CompositeFlags {
+    fn arg_1_and_2_are_words(&self) -> bool {
+        self.0 & 0x0001 != 0
+    }
+
+    fn args_are_xy_values(&self) -> bool {
+        self.0 & 0x0002 != 0
+    }
+
+    fn we_have_a_scale(&self) -> bool {
+        self.0 & 0x0008 != 0
+    }
+
+    fn more_components(&self) -> bool {
+        self.0 & 0x0020 != 0
+    }
+
+    fn we_have_an_x_and_y_scale(&self) -> bool {
+        self.0 & 0x0040 != 0
+    }
+
+    fn we_have_a_two_by_two(&self) -> bool {
+        self.0 & 0x0080 != 0
+    }
+
+    fn use_my_metrics(&self) -> bool {
+        self.0 & 0x0200 != 0
+    }
+}
+
+impl core::fmt::Debug for SimpleFlags {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("SimpleFlags")
             .field("ON_CURVE_POINT", &self.on_curve_point())
             .field("X_SHORT_VECTOR", &self.x_short_vector())
@@ -247,6 +765,13 @@ impl GlyfTable {
         loca_table: &LocaTable,
     ) -> Result<Self, ImtError> {
         let mut outlines = BTreeMap::new();
+        let mut use_my_metrics = BTreeMap::new();
+        let mut components = BTreeMap::new();
+        let mut composites = BTreeMap::new();
+        // Composites that share the glyph data of another, their outline is resolved once.
+        let mut shared_composites = Vec::new();
+        // The first glyph id parsed for each range of `loca`, for glyphs that share their data.
+        let mut parsed_at = BTreeMap::new();
 
         if loca_table.offsets.len() < 2 {
             return Err(MALFORMED);
@@ -258,6 +783,31 @@ impl GlyfTable {
                 continue;
             }
 
+            let range = (loca_table.offsets[i], loca_table.offsets[i + 1]);
+
+            if let Some(first) = parsed_at.get(&range).copied() {
+                if let Some(outline) = outlines.get(&first).cloned() {
+                    outlines.insert(i as u16, outline);
+                }
+
+                if let Some(glyph_index) = use_my_metrics.get(&first).copied() {
+                    use_my_metrics.insert(i as u16, glyph_index);
+                }
+
+                if let Some(glyph_components) = components.get(&first).cloned() {
+                    components.insert(i as u16, glyph_components);
+                }
+
+                if let Some(composite) = composites.get(&first).cloned() {
+                    composites.insert(i as u16, composite);
+                    shared_composites.push((i as u16, first));
+                }
+
+                continue;
+            }
+
+            parsed_at.insert(range, i as u16);
+
             let glyph_offset = table_offset + loca_table.offsets[i] as usize;
 
             if glyph_offset + 10 > bytes.len() {
@@ -428,16 +978,214 @@ impl GlyfTable {
                 };
 
                 outline.rebuild()?;
-                outlines.insert(i as u16, outline);
+                outlines.insert(i as u16, Arc::new(outline));
             } else if number_of_contours < 0 {
-                // TODO: Composite
+                let mut component_offset = glyph_offset + 10;
+                let mut glyph_components = Vec::new();
+                let mut composite = Vec::new();
+
+                loop {
+                    if component_offset + 4 > bytes.len() {
+                        return Err(TRUNCATED);
+                    }
+
+                    let flags = CompositeFlags(read_u16(bytes, component_offset));
+                    let glyph_index = read_u16(bytes, component_offset + 2);
+
+                    if flags.use_my_metrics() {
+                        use_my_metrics.insert(i as u16, glyph_index);
+                    }
+
+                    glyph_components.push(glyph_index);
+                    component_offset += 4;
+
+                    let (arg1, arg2) = if flags.arg_1_and_2_are_words() {
+                        if component_offset + 4 > bytes.len() {
+                            return Err(TRUNCATED);
+                        }
+
+                        let args = (
+                            read_u16(bytes, component_offset),
+                            read_u16(bytes, component_offset + 2),
+                        );
+
+                        component_offset += 4;
+                        args
+                    } else {
+                        if component_offset + 2 > bytes.len() {
+                            return Err(TRUNCATED);
+                        }
+
+                        // Offsets are sign extended, point numbers are not.
+                        let args = if flags.args_are_xy_values() {
+                            (
+                                bytes[component_offset] as i8 as u16,
+                                bytes[component_offset + 1] as i8 as u16,
+                            )
+                        } else {
+                            (
+                                bytes[component_offset] as u16,
+                                bytes[component_offset + 1] as u16,
+                            )
+                        };
+
+                        component_offset += 2;
+                        args
+                    };
+
+                    let placement = if flags.args_are_xy_values() {
+                        ComponentPlacement::Offset {
+                            x: arg1 as i16 as f32,
+                            y: arg2 as i16 as f32,
+                        }
+                    } else {
+                        ComponentPlacement::Anchor {
+                            composite_point: arg1,
+                            component_point: arg2,
+                        }
+                    };
+
+                    let transform =
+                        ComponentTransform::try_parse(bytes, component_offset, flags.0)?;
+                    component_offset += transform.byte_len();
+
+                    composite.push(Component {
+                        glyph_id: glyph_index,
+                        transform,
+                        placement,
+                    });
+
+                    if !flags.more_components() {
+                        break;
+                    }
+                }
+
+                components.insert(i as u16, glyph_components);
+                composites.insert(i as u16, composite);
             } else {
                 // Empty
             }
         }
 
+        let mut resolved = BTreeMap::new();
+
+        for glyph_id in composites.keys() {
+            resolve_composite(*glyph_id, &composites, &mut outlines, &mut resolved, 0);
+        }
+
+        for (glyph_id, first) in shared_composites {
+            if let Some(outline) = outlines.get(&first).cloned() {
+                outlines.insert(glyph_id, outline);
+            }
+        }
+
         Ok(Self {
             outlines,
+            use_my_metrics,
+            components,
+            composites,
         })
     }
+
+    /// The amount of points of a glyph that `gvar` deltas apply to, excluding phantom points.
+    ///
+    /// For composite glyphs this is the amount of components, as their deltas move the offsets
+    /// of the components rather than the points of the outline.
+    pub fn point_count(&self, glyph_id: u16) -> usize {
+        match self.composites.get(&glyph_id) {
+            Some(composite) => composite.len(),
+            None => {
+                self.outlines
+                    .get(&glyph_id)
+                    .map(|outline| outline.points.len())
+                    .unwrap_or(0)
+            },
+        }
+    }
+
+    /// The amount of glyphs whose outline is shared with a glyph of a lower id.
+    ///
+    /// Fonts may point multiple glyph ids at the same glyph data with equal `loca` offsets. Glyphs
+    /// whose start & end offsets are both equal are only parsed once, so this is the amount of
+    /// outlines that were not duplicated.
+    pub fn shared_outline_count(&self) -> usize {
+        let unique: BTreeSet<*const Outline> = self.outlines.values().map(Arc::as_ptr).collect();
+        self.outlines.len() - unique.len()
+    }
+}
+
+/// Resolve the outline of a composite glyph, inserting it into `outlines` when it has points.
+///
+/// Returns `false` if a component can't be resolved, e.g. if components reference each other
+/// or are nested deeper than `MAX_COMPONENT_DEPTH`. `resolved` holds the result of every
+/// composite visited, so each is only resolved once.
+fn resolve_composite(
+    glyph_id: u16,
+    composites: &BTreeMap<u16, Vec<Component>>,
+    outlines: &mut BTreeMap<u16, Arc<Outline>>,
+    resolved: &mut BTreeMap<u16, bool>,
+    depth: usize,
+) -> bool {
+    if let Some(result) = resolved.get(&glyph_id) {
+        return *result;
+    }
+
+    let composite = match composites.get(&glyph_id) {
+        Some(some) => some,
+        // Simple glyphs and glyphs without an outline
+        None => return true,
+    };
+
+    if depth >= MAX_COMPONENT_DEPTH {
+        return false;
+    }
+
+    // A component that references this composite will see it as unresolvable.
+    resolved.insert(glyph_id, false);
+
+    let mut outline = Outline {
+        x_min: 0.0,
+        y_min: 0.0,
+        x_max: 0.0,
+        y_max: 0.0,
+        points: Vec::new(),
+        contours: Vec::new(),
+        geometry: Vec::new(),
+    };
+
+    for component in composite.iter() {
+        if !resolve_composite(
+            component.glyph_id,
+            composites,
+            outlines,
+            resolved,
+            depth + 1,
+        ) {
+            return false;
+        }
+
+        // Components without an outline, e.g. a space, only contribute their metrics.
+        let component_outline = match outlines.get(&component.glyph_id) {
+            Some(some) => some.clone(),
+            None => continue,
+        };
+
+        if outline
+            .append_component(component, &component_outline, [0.0; 2])
+            .is_err()
+        {
+            return false;
+        }
+    }
+
+    if !outline.points.is_empty() {
+        if outline.rebuild().is_err() {
+            return false;
+        }
+
+        outlines.insert(glyph_id, Arc::new(outline));
+    }
+
+    resolved.insert(glyph_id, true);
+    true
 }
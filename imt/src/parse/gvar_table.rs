@@ -1,4 +1,5 @@
-use std::collections::BTreeMap;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
 
 use crate::error::*;
 use crate::parse::{read_f2dot14, read_u16, read_u32, GlyfTable};
@@ -10,6 +11,11 @@ pub struct GvarTable {
     pub major_version: u16,
     pub minor_version: u16,
     pub axis_count: usize,
+    /// Peak tuples referenced by the tuple variations of multiple glyphs.
+    ///
+    /// Tuples are stored one after another with `axis_count` normalized coordinates each. These
+    /// are already resolved into `TupleVariation::peak`, see `shared_tuple` to access one.
+    pub shared_tuples: Vec<f32>,
     pub glyph_variations: BTreeMap<u16, GlyphVariation>,
 }
 
@@ -69,12 +75,15 @@ impl GvarTable {
 
         let axis_count = read_u16(bytes, table_offset + 4) as usize;
         let share_tuple_count = read_u16(bytes, table_offset + 6) as usize;
-        let shared_tuples_offset = read_u32(bytes, table_offset + 8) as usize + table_offset;
+        let shared_tuples_offset = (read_u32(bytes, table_offset + 8) as usize)
+            .checked_add(table_offset)
+            .ok_or(MALFORMED)?;
         let glyph_count = read_u16(bytes, table_offset + 12) as usize;
         let flags = read_u16(bytes, table_offset + 14);
-        let glyph_variation_data_array_offset =
-            read_u32(bytes, table_offset + 16) as usize + table_offset;
-        let mut glyph_variation_data_offsets = Vec::with_capacity(glyph_count);
+        let glyph_variation_data_array_offset = (read_u32(bytes, table_offset + 16) as usize)
+            .checked_add(table_offset)
+            .ok_or(MALFORMED)?;
+        let mut glyph_variation_data_offsets = Vec::with_capacity(glyph_count + 1);
 
         if flags & 1 == 1 {
             if table_offset + 20 + ((glyph_count + 1) * 4) > bytes.len() {
@@ -84,8 +93,11 @@ impl GvarTable {
             for i in 0..=glyph_count {
                 let glyph_variation_data_offset =
                     read_u32(bytes, table_offset + 20 + (i * 4)) as usize;
-                glyph_variation_data_offsets
-                    .push(glyph_variation_data_array_offset + glyph_variation_data_offset);
+                glyph_variation_data_offsets.push(
+                    glyph_variation_data_array_offset
+                        .checked_add(glyph_variation_data_offset)
+                        .ok_or(MALFORMED)?,
+                );
             }
         } else {
             if table_offset + 20 + ((glyph_count + 1) * 2) > bytes.len() {
@@ -95,16 +107,25 @@ impl GvarTable {
             for i in 0..=glyph_count {
                 let glyph_variation_data_offset =
                     read_u16(bytes, table_offset + 20 + (i * 2)) as usize * 2;
-                glyph_variation_data_offsets
-                    .push(glyph_variation_data_array_offset + glyph_variation_data_offset);
+                glyph_variation_data_offsets.push(
+                    glyph_variation_data_array_offset
+                        .checked_add(glyph_variation_data_offset)
+                        .ok_or(MALFORMED)?,
+                );
             }
         }
 
-        if shared_tuples_offset + (share_tuple_count * 2 * axis_count) > bytes.len() {
+        let shared_tuples_end = share_tuple_count
+            .checked_mul(axis_count)
+            .and_then(|count| count.checked_mul(2))
+            .and_then(|size| size.checked_add(shared_tuples_offset))
+            .ok_or(MALFORMED)?;
+
+        if shared_tuples_end > bytes.len() {
             return Err(TRUNCATED);
         }
 
-        let mut shared_tuples: Vec<f32> = Vec::with_capacity(share_tuple_count);
+        let mut shared_tuples: Vec<f32> = Vec::with_capacity(share_tuple_count * axis_count);
 
         for i in 0..(share_tuple_count * axis_count) {
             shared_tuples.push(read_f2dot14(bytes, shared_tuples_offset + (i * 2)));
@@ -113,10 +134,11 @@ impl GvarTable {
         let mut glyph_variations = BTreeMap::new();
 
         for i in 0..glyph_count {
-            let outline = match glyf_table.outlines.get(&(i as u16)) {
-                Some(outline) => outline,
-                None => continue,
-            };
+            let point_count = glyf_table.point_count(i as u16);
+
+            if point_count == 0 {
+                continue;
+            }
 
             // set & check glyph variation data
 
@@ -175,6 +197,12 @@ impl GvarTable {
 
                 let variation_data_size =
                     read_u16(glyph_variation_data, tuple_variation_header_offset) as usize;
+                let variation_data_end = serialized_offset + variation_data_size;
+
+                if variation_data_end > serialized_data.len() {
+                    return Err(TRUNCATED);
+                }
+
                 let tuple_index = read_u16(glyph_variation_data, tuple_variation_header_offset + 2);
                 tuple_variation_header_offset += 4;
                 let has_embedded_peak_tuple = tuple_index & 0x8000 != 0;
@@ -246,13 +274,8 @@ impl GvarTable {
                 let mut point_numbers = Vec::new();
 
                 let delta_offset = if has_private_point_numbers {
-                    if serialized_offset >= serialized_data.len() {
-                        return Err(TRUNCATED);
-                    }
-
                     parse_packed_points(
-                        &serialized_data
-                            [serialized_offset..(serialized_offset + variation_data_size)],
+                        &serialized_data[serialized_offset..variation_data_end],
                         &mut point_numbers,
                     )?
                 } else {
@@ -260,27 +283,26 @@ impl GvarTable {
                     0
                 };
 
-                if point_numbers.last().copied().unwrap_or(0) as usize > outline.points.len() + 4 {
+                if point_numbers.last().copied().unwrap_or(0) as usize > point_count + 4 {
                     return Err(MALFORMED);
                 }
 
-                if serialized_offset + delta_offset >= serialized_data.len() {
+                if serialized_offset + delta_offset > variation_data_end {
                     return Err(MALFORMED);
                 }
 
                 let delta_count = if point_numbers.is_empty() {
-                    outline.points.len() + 4
+                    point_count + 4
                 } else {
                     point_numbers.len()
                 };
 
                 let deltas = parse_packed_deltas(
-                    &serialized_data[(serialized_offset + delta_offset)
-                        ..(serialized_offset + variation_data_size)],
+                    &serialized_data[(serialized_offset + delta_offset)..variation_data_end],
                     delta_count,
                 )?;
 
-                serialized_offset += variation_data_size;
+                serialized_offset = variation_data_end;
 
                 // Sanity Checks
 
@@ -314,7 +336,7 @@ impl GvarTable {
                 let mut last_point_op = None;
 
                 for point in point_numbers.iter() {
-                    if *point as usize >= outline.points.len() + 4 {
+                    if *point as usize >= point_count + 4 {
                         return Err(MALFORMED);
                     }
 
@@ -347,9 +369,28 @@ impl GvarTable {
             major_version,
             minor_version,
             axis_count,
+            shared_tuples,
             glyph_variations,
         })
     }
+
+    /// The amount of tuples in `shared_tuples`.
+    pub fn shared_tuple_count(&self) -> usize {
+        match self.axis_count {
+            0 => 0,
+            axis_count => self.shared_tuples.len() / axis_count,
+        }
+    }
+
+    /// The normalized coordinates of the shared tuple at `index`.
+    pub fn shared_tuple(&self, index: usize) -> Option<&[f32]> {
+        if index >= self.shared_tuple_count() {
+            return None;
+        }
+
+        let start = index * self.axis_count;
+        Some(&self.shared_tuples[start..(start + self.axis_count)])
+    }
 }
 
 fn parse_packed_deltas(bytes: &[u8], count: usize) -> Result<Vec<[i16; 2]>, ImtError> {
@@ -422,6 +463,7 @@ fn parse_packed_points(bytes: &[u8], points: &mut Vec<u16>) -> Result<usize, Imt
             return Err(TRUNCATED);
         }
 
+        offset += 2;
         u16::from_be_bytes([bytes[0] & 0x7F, bytes[1]]) as usize
     } else {
         offset += 1;
@@ -459,7 +501,9 @@ fn parse_packed_points(bytes: &[u8], points: &mut Vec<u16>) -> Result<usize, Imt
                 return Err(TRUNCATED);
             }
 
-            last_point += read_u16(bytes, offset);
+            last_point = read_u16(bytes, offset)
+                .checked_add(last_point)
+                .ok_or(MALFORMED)?;
             points.push(last_point);
             offset += 2;
             remaining -= 1;
@@ -468,7 +512,9 @@ fn parse_packed_points(bytes: &[u8], points: &mut Vec<u16>) -> Result<usize, Imt
                 return Err(TRUNCATED);
             }
 
-            last_point += bytes[offset] as u16;
+            last_point = (bytes[offset] as u16)
+                .checked_add(last_point)
+                .ok_or(MALFORMED)?;
             points.push(last_point);
             offset += 1;
             remaining -= 1;
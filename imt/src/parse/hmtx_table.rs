@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use crate::error::*;
 use crate::parse::{read_i16, read_u16, HheaTable, MaxpTable};
 
@@ -16,7 +18,10 @@ impl HmtxTable {
         maxp_table: &MaxpTable,
         hhea_table: &HheaTable,
     ) -> Result<Self, ImtError> {
-        if maxp_table.num_glyphs < hhea_table.number_of_h_metrics {
+        // At least one metric is required as it also applies to the glyphs beyond the last.
+        if hhea_table.number_of_h_metrics == 0
+            || maxp_table.num_glyphs < hhea_table.number_of_h_metrics
+        {
             return Err(ImtError {
                 kind: ImtErrorKind::Malformed,
                 source: ImtErrorSource::HmtxTable,
@@ -57,6 +62,28 @@ impl HmtxTable {
             left_side_bearings,
         })
     }
+
+    /// The advance width of a glyph in font units.
+    ///
+    /// Glyphs beyond the last `HorMetric` share its advance width.
+    pub fn advance_width(&self, glyph_id: u16) -> Option<u16> {
+        self.hor_metric
+            .get(glyph_id as usize)
+            .or(self.hor_metric.last())
+            .map(|hor_metric| hor_metric.advance_width)
+    }
+
+    /// The left side bearing of a glyph in font units.
+    pub fn lsb(&self, glyph_id: u16) -> Option<i16> {
+        match self.hor_metric.get(glyph_id as usize) {
+            Some(hor_metric) => Some(hor_metric.lsb),
+            None => {
+                self.left_side_bearings
+                    .get(glyph_id as usize - self.hor_metric.len())
+                    .copied()
+            },
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
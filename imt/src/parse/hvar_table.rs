@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use crate::error::*;
 use crate::parse::{read_f2dot14, read_i16, read_i32, read_i8, read_u16, read_u32};
 
@@ -364,14 +366,14 @@ impl DeltaSetIndexMap {
                     return Err(TRUNCATED);
                 }
 
-                (read_u16(bytes, map_offset + 2) as usize, 4)
+                (read_u16(bytes, map_offset + 2) as usize, map_offset + 4)
             },
             1 => {
                 if map_offset + 6 > bytes.len() {
                     return Err(TRUNCATED);
                 }
 
-                (read_u32(bytes, map_offset + 2) as usize, 6)
+                (read_u32(bytes, map_offset + 2) as usize, map_offset + 6)
             },
             _ => return Err(MALFORMED),
         };
@@ -0,0 +1,1106 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::error::*;
+use crate::parse::{read_u16, read_u32};
+
+/// Lookup flag that skips base glyphs, see `Lookup::ignores`.
+pub const IGNORE_BASE_GLYPHS: u16 = 0x0002;
+/// Lookup flag that skips ligature glyphs, see `Lookup::ignores`.
+pub const IGNORE_LIGATURES: u16 = 0x0004;
+/// Lookup flag that skips mark glyphs, see `Lookup::ignores`.
+pub const IGNORE_MARKS: u16 = 0x0008;
+
+/// The common header of the `GSUB` & `GPOS` tables.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/chapter2>
+///
+/// # Notes
+/// - Only single & ligature substitution subtables of `GSUB` and pair adjustment subtables of
+///   `GPOS` are parsed, see `LookupSubtable`.
+#[derive(Debug, Clone)]
+pub struct LayoutTable {
+    pub major_version: u16,
+    pub minor_version: u16,
+    /// Keyed by script tag.
+    pub scripts: BTreeMap<u32, LayoutScript>,
+    /// Features in the order they appear in the font.
+    pub features: Vec<LayoutFeature>,
+    /// Lookups in the order they appear in the font.
+    pub lookups: Vec<Lookup>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LayoutScript {
+    /// The script has a default language system.
+    pub has_default_lang_sys: bool,
+    /// Language system tags in the order they appear in the font.
+    pub lang_sys_tags: Vec<u32>,
+    /// Indexes into `LayoutTable::features` of the default language system.
+    pub default_feature_indexes: Vec<u16>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LayoutFeature {
+    pub feature_tag: u32,
+    /// Indexes into `LayoutTable::lookups`.
+    pub lookup_indexes: Vec<u16>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Lookup {
+    /// The type of the subtables, extension lookups are resolved to the type they contain.
+    pub lookup_type: u16,
+    pub lookup_flag: u16,
+    pub subtables: Vec<LookupSubtable>,
+}
+
+#[derive(Debug, Clone)]
+pub enum LookupSubtable {
+    /// `GSUB` lookup type 1.
+    SingleSubst(SingleSubst),
+    /// `GSUB` lookup type 4.
+    LigatureSubst(LigatureSubst),
+    /// `GPOS` lookup type 2.
+    PairPos(PairPos),
+    /// A subtable of a lookup type or format that is not parsed.
+    Unsupported,
+}
+
+/// Maps glyph ids to their coverage index.
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    /// Sorted by `start_glyph`.
+    pub ranges: Vec<CoverageRange>,
+}
+
+/// A range of glyphs with consecutive coverage indexes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoverageRange {
+    pub start_glyph: u16,
+    pub end_glyph: u16,
+    /// The coverage index of `start_glyph`.
+    pub start_index: usize,
+}
+
+/// Maps glyph ids to a class. Glyphs that are not present are of class zero.
+#[derive(Debug, Clone, Default)]
+pub struct ClassDef {
+    /// Sorted by `start_glyph`, ranges of class zero are omitted.
+    pub ranges: Vec<ClassRange>,
+}
+
+/// A range of glyphs of the same class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassRange {
+    pub start_glyph: u16,
+    pub end_glyph: u16,
+    pub class: u16,
+}
+
+/// Adjustment of the position of a glyph, in font units.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PosValue {
+    pub x_placement: i16,
+    pub y_placement: i16,
+    pub x_advance: i16,
+    pub y_advance: i16,
+    /// Outer & inner index into the `ItemVariationStore` of `GDEF` of each field.
+    ///
+    /// Device tables with hinting deltas are not parsed.
+    pub x_placement_variation: Option<[usize; 2]>,
+    pub y_placement_variation: Option<[usize; 2]>,
+    pub x_advance_variation: Option<[usize; 2]>,
+    pub y_advance_variation: Option<[usize; 2]>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SingleSubst {
+    /// Format 1, the substitute is the glyph id plus `delta` modulo 65536.
+    Delta { coverage: Coverage, delta: u16 },
+    /// Format 2, substitutes indexed by coverage index.
+    Glyphs {
+        coverage: Coverage,
+        substitutes: Vec<u16>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct LigatureSubst {
+    pub coverage: Coverage,
+    /// Indexed by coverage index of the first glyph, in order of preference.
+    pub ligature_sets: Vec<Vec<Ligature>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Ligature {
+    pub ligature_glyph: u16,
+    /// The glyphs following the first glyph that form the ligature.
+    pub components: Vec<u16>,
+}
+
+#[derive(Debug, Clone)]
+pub enum PairPos {
+    /// Format 1, pairs of individual glyphs.
+    Glyphs {
+        coverage: Coverage,
+        /// Indexed by coverage index of the first glyph, sorted by second glyph.
+        pair_sets: Vec<Vec<PairValue>>,
+        /// The second glyph has a value, so it isn't the first of the following pair.
+        has_second_value: bool,
+    },
+    /// Format 2, pairs of glyph classes.
+    Classes {
+        coverage: Coverage,
+        class_def_1: ClassDef,
+        class_def_2: ClassDef,
+        class_2_count: usize,
+        /// Indexed by `(class_1 * class_2_count) + class_2`.
+        values: Vec<[PosValue; 2]>,
+        /// The second glyph has a value, so it isn't the first of the following pair.
+        has_second_value: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct PairValue {
+    pub second_glyph: u16,
+    pub values: [PosValue; 2],
+}
+
+impl Coverage {
+    pub fn try_parse(
+        bytes: &[u8],
+        coverage_offset: usize,
+        source: ImtErrorSource,
+    ) -> Result<Self, ImtError> {
+        let truncated = ImtError {
+            kind: ImtErrorKind::Truncated,
+            source,
+        };
+
+        if coverage_offset + 4 > bytes.len() {
+            return Err(truncated);
+        }
+
+        let format = read_u16(bytes, coverage_offset);
+        let count = read_u16(bytes, coverage_offset + 2) as usize;
+        let mut ranges: Vec<CoverageRange> = Vec::new();
+
+        match format {
+            1 => {
+                if coverage_offset + 4 + (count * 2) > bytes.len() {
+                    return Err(truncated);
+                }
+
+                for i in 0..count {
+                    let glyph = read_u16(bytes, coverage_offset + 4 + (i * 2));
+
+                    // Consecutive glyphs are merged into a single range.
+                    match ranges.last_mut() {
+                        Some(range) if range.end_glyph.checked_add(1) == Some(glyph) => {
+                            range.end_glyph = glyph;
+                        },
+                        _ => {
+                            ranges.push(CoverageRange {
+                                start_glyph: glyph,
+                                end_glyph: glyph,
+                                start_index: i,
+                            });
+                        },
+                    }
+                }
+            },
+            2 => {
+                if coverage_offset + 4 + (count * 6) > bytes.len() {
+                    return Err(truncated);
+                }
+
+                for i in 0..count {
+                    let record_offset = coverage_offset + 4 + (i * 6);
+                    let start_glyph = read_u16(bytes, record_offset);
+                    let end_glyph = read_u16(bytes, record_offset + 2);
+
+                    if start_glyph <= end_glyph {
+                        ranges.push(CoverageRange {
+                            start_glyph,
+                            end_glyph,
+                            start_index: read_u16(bytes, record_offset + 4) as usize,
+                        });
+                    }
+                }
+            },
+            _ => {
+                return Err(ImtError {
+                    kind: ImtErrorKind::FormatNotSupported,
+                    source,
+                });
+            },
+        }
+
+        // Sorted by the spec, but a binary search relies on it.
+        ranges.sort_by_key(|range| range.start_glyph);
+
+        Ok(Self {
+            ranges,
+        })
+    }
+
+    /// The coverage index of a glyph, `None` if it isn't covered.
+    pub fn index(&self, glyph_id: u16) -> Option<usize> {
+        let i = self
+            .ranges
+            .partition_point(|range| range.start_glyph <= glyph_id)
+            .checked_sub(1)?;
+        let range = &self.ranges[i];
+
+        if glyph_id > range.end_glyph {
+            return None;
+        }
+
+        Some(range.start_index + (glyph_id - range.start_glyph) as usize)
+    }
+}
+
+impl ClassDef {
+    pub fn try_parse(
+        bytes: &[u8],
+        class_def_offset: usize,
+        source: ImtErrorSource,
+    ) -> Result<Self, ImtError> {
+        let truncated = ImtError {
+            kind: ImtErrorKind::Truncated,
+            source,
+        };
+
+        if class_def_offset + 4 > bytes.len() {
+            return Err(truncated);
+        }
+
+        let format = read_u16(bytes, class_def_offset);
+        let mut ranges: Vec<ClassRange> = Vec::new();
+
+        match format {
+            1 => {
+                if class_def_offset + 6 > bytes.len() {
+                    return Err(truncated);
+                }
+
+                let start_glyph = read_u16(bytes, class_def_offset + 2);
+                let glyph_count = read_u16(bytes, class_def_offset + 4) as usize;
+
+                if class_def_offset + 6 + (glyph_count * 2) > bytes.len() {
+                    return Err(truncated);
+                }
+
+                for i in 0..glyph_count {
+                    let class = read_u16(bytes, class_def_offset + 6 + (i * 2));
+
+                    let glyph = match start_glyph.checked_add(i as u16) {
+                        Some(glyph) if class != 0 => glyph,
+                        _ => continue,
+                    };
+
+                    // Consecutive glyphs of the same class are merged into a single range.
+                    match ranges.last_mut() {
+                        Some(range)
+                            if range.class == class
+                                && range.end_glyph.checked_add(1) == Some(glyph) =>
+                        {
+                            range.end_glyph = glyph;
+                        },
+                        _ => {
+                            ranges.push(ClassRange {
+                                start_glyph: glyph,
+                                end_glyph: glyph,
+                                class,
+                            });
+                        },
+                    }
+                }
+            },
+            2 => {
+                let range_count = read_u16(bytes, class_def_offset + 2) as usize;
+
+                if class_def_offset + 4 + (range_count * 6) > bytes.len() {
+                    return Err(truncated);
+                }
+
+                for i in 0..range_count {
+                    let record_offset = class_def_offset + 4 + (i * 6);
+                    let start_glyph = read_u16(bytes, record_offset);
+                    let end_glyph = read_u16(bytes, record_offset + 2);
+                    let class = read_u16(bytes, record_offset + 4);
+
+                    if class != 0 && start_glyph <= end_glyph {
+                        ranges.push(ClassRange {
+                            start_glyph,
+                            end_glyph,
+                            class,
+                        });
+                    }
+                }
+            },
+            _ => {
+                return Err(ImtError {
+                    kind: ImtErrorKind::FormatNotSupported,
+                    source,
+                });
+            },
+        }
+
+        // Sorted by the spec, but a binary search relies on it.
+        ranges.sort_by_key(|range| range.start_glyph);
+
+        Ok(Self {
+            ranges,
+        })
+    }
+
+    /// The class of a glyph.
+    pub fn class(&self, glyph_id: u16) -> u16 {
+        match self
+            .ranges
+            .partition_point(|range| range.start_glyph <= glyph_id)
+            .checked_sub(1)
+        {
+            Some(i) if glyph_id <= self.ranges[i].end_glyph => self.ranges[i].class,
+            _ => 0,
+        }
+    }
+}
+
+impl Lookup {
+    /// Check if the lookup skips a glyph of the `GDEF` glyph class, i.e. the lookup is applied
+    /// as if the glyph was not present.
+    ///
+    /// # Notes
+    /// - Mark attachment types & mark filtering sets are not supported, marks are only skipped
+    ///   with `IGNORE_MARKS`.
+    pub fn ignores(&self, glyph_class: u16) -> bool {
+        match glyph_class {
+            1 => self.lookup_flag & IGNORE_BASE_GLYPHS != 0,
+            2 => self.lookup_flag & IGNORE_LIGATURES != 0,
+            3 => self.lookup_flag & IGNORE_MARKS != 0,
+            _ => false,
+        }
+    }
+}
+
+impl SingleSubst {
+    fn try_parse(
+        bytes: &[u8],
+        subtable_offset: usize,
+        source: ImtErrorSource,
+    ) -> Result<Option<Self>, ImtError> {
+        let truncated = ImtError {
+            kind: ImtErrorKind::Truncated,
+            source,
+        };
+
+        if subtable_offset + 6 > bytes.len() {
+            return Err(truncated);
+        }
+
+        let format = read_u16(bytes, subtable_offset);
+        let coverage = Coverage::try_parse(
+            bytes,
+            subtable_offset + read_u16(bytes, subtable_offset + 2) as usize,
+            source,
+        )?;
+
+        match format {
+            1 => {
+                Ok(Some(Self::Delta {
+                    coverage,
+                    delta: read_u16(bytes, subtable_offset + 4),
+                }))
+            },
+            2 => {
+                let glyph_count = read_u16(bytes, subtable_offset + 4) as usize;
+
+                if subtable_offset + 6 + (glyph_count * 2) > bytes.len() {
+                    return Err(truncated);
+                }
+
+                Ok(Some(Self::Glyphs {
+                    coverage,
+                    substitutes: (0..glyph_count)
+                        .map(|i| read_u16(bytes, subtable_offset + 6 + (i * 2)))
+                        .collect(),
+                }))
+            },
+            _ => Ok(None),
+        }
+    }
+
+    /// The substitute of a glyph, `None` if it isn't substituted.
+    pub fn substitute(&self, glyph_id: u16) -> Option<u16> {
+        match self {
+            Self::Delta {
+                coverage,
+                delta,
+            } => {
+                coverage.index(glyph_id)?;
+                Some(glyph_id.wrapping_add(*delta))
+            },
+            Self::Glyphs {
+                coverage,
+                substitutes,
+            } => substitutes.get(coverage.index(glyph_id)?).copied(),
+        }
+    }
+}
+
+impl LigatureSubst {
+    fn try_parse(
+        bytes: &[u8],
+        subtable_offset: usize,
+        source: ImtErrorSource,
+    ) -> Result<Option<Self>, ImtError> {
+        let truncated = ImtError {
+            kind: ImtErrorKind::Truncated,
+            source,
+        };
+
+        if subtable_offset + 6 > bytes.len() {
+            return Err(truncated);
+        }
+
+        if read_u16(bytes, subtable_offset) != 1 {
+            return Ok(None);
+        }
+
+        let coverage = Coverage::try_parse(
+            bytes,
+            subtable_offset + read_u16(bytes, subtable_offset + 2) as usize,
+            source,
+        )?;
+
+        let ligature_set_count = read_u16(bytes, subtable_offset + 4) as usize;
+
+        if subtable_offset + 6 + (ligature_set_count * 2) > bytes.len() {
+            return Err(truncated);
+        }
+
+        let mut ligature_sets = Vec::with_capacity(ligature_set_count);
+
+        for i in 0..ligature_set_count {
+            let ligature_set_offset =
+                subtable_offset + read_u16(bytes, subtable_offset + 6 + (i * 2)) as usize;
+
+            if ligature_set_offset + 2 > bytes.len() {
+                return Err(truncated);
+            }
+
+            let ligature_count = read_u16(bytes, ligature_set_offset) as usize;
+
+            if ligature_set_offset + 2 + (ligature_count * 2) > bytes.len() {
+                return Err(truncated);
+            }
+
+            let mut ligature_set = Vec::with_capacity(ligature_count);
+
+            for j in 0..ligature_count {
+                let ligature_offset = ligature_set_offset
+                    + read_u16(bytes, ligature_set_offset + 2 + (j * 2)) as usize;
+
+                if ligature_offset + 4 > bytes.len() {
+                    return Err(truncated);
+                }
+
+                let ligature_glyph = read_u16(bytes, ligature_offset);
+                let component_count = read_u16(bytes, ligature_offset + 2) as usize;
+
+                if component_count == 0 {
+                    return Err(ImtError {
+                        kind: ImtErrorKind::Malformed,
+                        source,
+                    });
+                }
+
+                if ligature_offset + 4 + ((component_count - 1) * 2) > bytes.len() {
+                    return Err(truncated);
+                }
+
+                ligature_set.push(Ligature {
+                    ligature_glyph,
+                    components: (0..(component_count - 1))
+                        .map(|k| read_u16(bytes, ligature_offset + 4 + (k * 2)))
+                        .collect(),
+                });
+            }
+
+            ligature_sets.push(ligature_set);
+        }
+
+        Ok(Some(Self {
+            coverage,
+            ligature_sets,
+        }))
+    }
+
+    /// The ligatures that start with a glyph in order of preference.
+    pub fn ligatures(&self, first_glyph: u16) -> &[Ligature] {
+        self.coverage
+            .index(first_glyph)
+            .and_then(|i| self.ligature_sets.get(i))
+            .map(|ligature_set| ligature_set.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+impl PairPos {
+    /// The values of the first & second glyph of a pair, `None` if the pair isn't covered.
+    pub fn values(&self, first_glyph: u16, second_glyph: u16) -> Option<[PosValue; 2]> {
+        match self {
+            Self::Glyphs {
+                coverage,
+                pair_sets,
+                ..
+            } => {
+                let pair_set = pair_sets.get(coverage.index(first_glyph)?)?;
+
+                pair_set
+                    .binary_search_by_key(&second_glyph, |pair| pair.second_glyph)
+                    .ok()
+                    .map(|i| pair_set[i].values)
+            },
+            Self::Classes {
+                coverage,
+                class_def_1,
+                class_def_2,
+                class_2_count,
+                values,
+                ..
+            } => {
+                coverage.index(first_glyph)?;
+                let class_1 = class_def_1.class(first_glyph) as usize;
+                let class_2 = class_def_2.class(second_glyph) as usize;
+
+                if class_2 >= *class_2_count {
+                    return None;
+                }
+
+                values.get((class_1 * class_2_count) + class_2).copied()
+            },
+        }
+    }
+
+    /// Check if the second glyph has a value, if so it isn't the first of the following pair.
+    pub fn has_second_value(&self) -> bool {
+        match self {
+            Self::Glyphs {
+                has_second_value, ..
+            }
+            | Self::Classes {
+                has_second_value, ..
+            } => *has_second_value,
+        }
+    }
+
+    fn try_parse(
+        bytes: &[u8],
+        subtable_offset: usize,
+        source: ImtErrorSource,
+    ) -> Result<Option<Self>, ImtError> {
+        let truncated = ImtError {
+            kind: ImtErrorKind::Truncated,
+            source,
+        };
+
+        if subtable_offset + 10 > bytes.len() {
+            return Err(truncated);
+        }
+
+        let format = read_u16(bytes, subtable_offset);
+        let coverage = Coverage::try_parse(
+            bytes,
+            subtable_offset + read_u16(bytes, subtable_offset + 2) as usize,
+            source,
+        )?;
+
+        let value_format_1 = read_u16(bytes, subtable_offset + 4);
+        let value_format_2 = read_u16(bytes, subtable_offset + 6);
+        let value_size_1 = value_format_1.count_ones() as usize * 2;
+        let value_size_2 = value_format_2.count_ones() as usize * 2;
+        let has_second_value = value_format_2 != 0;
+
+        match format {
+            1 => {
+                let pair_set_count = read_u16(bytes, subtable_offset + 8) as usize;
+
+                if subtable_offset + 10 + (pair_set_count * 2) > bytes.len() {
+                    return Err(truncated);
+                }
+
+                let mut pair_sets = Vec::with_capacity(pair_set_count);
+
+                for i in 0..pair_set_count {
+                    let pair_set_offset =
+                        subtable_offset + read_u16(bytes, subtable_offset + 10 + (i * 2)) as usize;
+
+                    if pair_set_offset + 2 > bytes.len() {
+                        return Err(truncated);
+                    }
+
+                    let pair_count = read_u16(bytes, pair_set_offset) as usize;
+                    let record_size = 2 + value_size_1 + value_size_2;
+
+                    if pair_set_offset + 2 + (pair_count * record_size) > bytes.len() {
+                        return Err(truncated);
+                    }
+
+                    let mut pair_set = Vec::with_capacity(pair_count);
+
+                    for j in 0..pair_count {
+                        let record_offset = pair_set_offset + 2 + (j * record_size);
+
+                        pair_set.push(PairValue {
+                            second_glyph: read_u16(bytes, record_offset),
+                            values: [
+                                PosValue::try_parse(
+                                    bytes,
+                                    record_offset + 2,
+                                    value_format_1,
+                                    subtable_offset,
+                                    source,
+                                )?,
+                                PosValue::try_parse(
+                                    bytes,
+                                    record_offset + 2 + value_size_1,
+                                    value_format_2,
+                                    subtable_offset,
+                                    source,
+                                )?,
+                            ],
+                        });
+                    }
+
+                    // Sorted by the spec, but a binary search relies on it.
+                    pair_set.sort_by_key(|pair| pair.second_glyph);
+                    pair_sets.push(pair_set);
+                }
+
+                Ok(Some(Self::Glyphs {
+                    coverage,
+                    pair_sets,
+                    has_second_value,
+                }))
+            },
+            2 => {
+                if subtable_offset + 16 > bytes.len() {
+                    return Err(truncated);
+                }
+
+                let class_def_1 = ClassDef::try_parse(
+                    bytes,
+                    subtable_offset + read_u16(bytes, subtable_offset + 8) as usize,
+                    source,
+                )?;
+
+                let class_def_2 = ClassDef::try_parse(
+                    bytes,
+                    subtable_offset + read_u16(bytes, subtable_offset + 10) as usize,
+                    source,
+                )?;
+
+                let class_1_count = read_u16(bytes, subtable_offset + 12) as usize;
+                let class_2_count = read_u16(bytes, subtable_offset + 14) as usize;
+                let record_size = value_size_1 + value_size_2;
+
+                // Without values the subtable doesn't adjust anything.
+                if record_size == 0 {
+                    return Ok(None);
+                }
+
+                let record_count = class_1_count * class_2_count;
+
+                if record_count > (bytes.len() - (subtable_offset + 16)) / record_size {
+                    return Err(truncated);
+                }
+
+                let mut values = Vec::with_capacity(record_count);
+
+                for i in 0..record_count {
+                    let record_offset = subtable_offset + 16 + (i * record_size);
+
+                    values.push([
+                        PosValue::try_parse(
+                            bytes,
+                            record_offset,
+                            value_format_1,
+                            subtable_offset,
+                            source,
+                        )?,
+                        PosValue::try_parse(
+                            bytes,
+                            record_offset + value_size_1,
+                            value_format_2,
+                            subtable_offset,
+                            source,
+                        )?,
+                    ]);
+                }
+
+                Ok(Some(Self::Classes {
+                    coverage,
+                    class_def_1,
+                    class_def_2,
+                    class_2_count,
+                    values,
+                    has_second_value,
+                }))
+            },
+            _ => Ok(None),
+        }
+    }
+}
+
+impl PosValue {
+    /// Parse a value record of `value_format`, offsets of device tables are relative to
+    /// `subtable_offset`.
+    fn try_parse(
+        bytes: &[u8],
+        record_offset: usize,
+        value_format: u16,
+        subtable_offset: usize,
+        source: ImtErrorSource,
+    ) -> Result<Self, ImtError> {
+        let mut value = Self::default();
+        let mut field_offset = record_offset;
+
+        let mut next_field = |bit: u16| -> Option<u16> {
+            if value_format & bit == 0 {
+                return None;
+            }
+
+            let field = read_u16(bytes, field_offset);
+            field_offset += 2;
+            Some(field)
+        };
+
+        value.x_placement = next_field(0x0001).unwrap_or(0) as i16;
+        value.y_placement = next_field(0x0002).unwrap_or(0) as i16;
+        value.x_advance = next_field(0x0004).unwrap_or(0) as i16;
+        value.y_advance = next_field(0x0008).unwrap_or(0) as i16;
+
+        let device_offsets = [
+            next_field(0x0010),
+            next_field(0x0020),
+            next_field(0x0040),
+            next_field(0x0080),
+        ];
+
+        let mut variations = [None; 4];
+
+        for (variation, device_offset) in variations.iter_mut().zip(device_offsets) {
+            let device_offset = match device_offset {
+                Some(0) | None => continue,
+                Some(offset) => subtable_offset + offset as usize,
+            };
+
+            if device_offset + 6 > bytes.len() {
+                return Err(ImtError {
+                    kind: ImtErrorKind::Truncated,
+                    source,
+                });
+            }
+
+            // VariationIndex tables have a delta format of 0x8000.
+            if read_u16(bytes, device_offset + 4) == 0x8000 {
+                *variation = Some([
+                    read_u16(bytes, device_offset) as usize,
+                    read_u16(bytes, device_offset + 2) as usize,
+                ]);
+            }
+        }
+
+        [
+            value.x_placement_variation,
+            value.y_placement_variation,
+            value.x_advance_variation,
+            value.y_advance_variation,
+        ] = variations;
+
+        Ok(value)
+    }
+}
+
+impl LayoutTable {
+    /// Parse either a `GSUB` or `GPOS` table, `source` is used for errors and to determine which
+    /// lookup types are parsed.
+    pub fn try_parse(
+        bytes: &[u8],
+        table_offset: usize,
+        source: ImtErrorSource,
+    ) -> Result<Self, ImtError> {
+        let truncated = ImtError {
+            kind: ImtErrorKind::Truncated,
+            source,
+        };
+
+        if table_offset + 10 > bytes.len() {
+            return Err(truncated);
+        }
+
+        let major_version = read_u16(bytes, table_offset);
+        let minor_version = read_u16(bytes, table_offset + 2);
+
+        if major_version != 1 || minor_version > 1 {
+            return Err(ImtError {
+                kind: ImtErrorKind::UnexpectedVersion,
+                source,
+            });
+        }
+
+        let mut scripts = BTreeMap::new();
+        let script_list_offset = read_u16(bytes, table_offset + 4) as usize;
+
+        if script_list_offset != 0 {
+            let script_list_offset = table_offset + script_list_offset;
+
+            if script_list_offset + 2 > bytes.len() {
+                return Err(truncated);
+            }
+
+            let script_count = read_u16(bytes, script_list_offset) as usize;
+
+            if script_list_offset + 2 + (script_count * 6) > bytes.len() {
+                return Err(truncated);
+            }
+
+            for i in 0..script_count {
+                let record_offset = script_list_offset + 2 + (i * 6);
+                let script_tag = read_u32(bytes, record_offset);
+                let script_offset =
+                    script_list_offset + read_u16(bytes, record_offset + 4) as usize;
+
+                if script_offset + 4 > bytes.len() {
+                    return Err(truncated);
+                }
+
+                let default_lang_sys_offset = read_u16(bytes, script_offset) as usize;
+                let lang_sys_count = read_u16(bytes, script_offset + 2) as usize;
+
+                if script_offset + 4 + (lang_sys_count * 6) > bytes.len() {
+                    return Err(truncated);
+                }
+
+                let lang_sys_tags = (0..lang_sys_count)
+                    .map(|j| read_u32(bytes, script_offset + 4 + (j * 6)))
+                    .collect();
+
+                let default_feature_indexes = match default_lang_sys_offset {
+                    0 => Vec::new(),
+                    offset => {
+                        let lang_sys_offset = script_offset + offset;
+
+                        if lang_sys_offset + 6 > bytes.len() {
+                            return Err(truncated);
+                        }
+
+                        let feature_index_count = read_u16(bytes, lang_sys_offset + 4) as usize;
+
+                        if lang_sys_offset + 6 + (feature_index_count * 2) > bytes.len() {
+                            return Err(truncated);
+                        }
+
+                        (0..feature_index_count)
+                            .map(|j| read_u16(bytes, lang_sys_offset + 6 + (j * 2)))
+                            .collect()
+                    },
+                };
+
+                scripts.insert(
+                    script_tag,
+                    LayoutScript {
+                        has_default_lang_sys: default_lang_sys_offset != 0,
+                        lang_sys_tags,
+                        default_feature_indexes,
+                    },
+                );
+            }
+        }
+
+        let mut features = Vec::new();
+        let feature_list_offset = read_u16(bytes, table_offset + 6) as usize;
+
+        if feature_list_offset != 0 {
+            let feature_list_offset = table_offset + feature_list_offset;
+
+            if feature_list_offset + 2 > bytes.len() {
+                return Err(truncated);
+            }
+
+            let feature_count = read_u16(bytes, feature_list_offset) as usize;
+
+            if feature_list_offset + 2 + (feature_count * 6) > bytes.len() {
+                return Err(truncated);
+            }
+
+            for i in 0..feature_count {
+                let record_offset = feature_list_offset + 2 + (i * 6);
+                let feature_tag = read_u32(bytes, record_offset);
+                let feature_offset =
+                    feature_list_offset + read_u16(bytes, record_offset + 4) as usize;
+
+                if feature_offset + 4 > bytes.len() {
+                    return Err(truncated);
+                }
+
+                let lookup_index_count = read_u16(bytes, feature_offset + 2) as usize;
+
+                if feature_offset + 4 + (lookup_index_count * 2) > bytes.len() {
+                    return Err(truncated);
+                }
+
+                features.push(LayoutFeature {
+                    feature_tag,
+                    lookup_indexes: (0..lookup_index_count)
+                        .map(|j| read_u16(bytes, feature_offset + 4 + (j * 2)))
+                        .collect(),
+                });
+            }
+        }
+
+        let mut lookups = Vec::new();
+        let lookup_list_offset = read_u16(bytes, table_offset + 8) as usize;
+
+        if lookup_list_offset != 0 {
+            let lookup_list_offset = table_offset + lookup_list_offset;
+
+            if lookup_list_offset + 2 > bytes.len() {
+                return Err(truncated);
+            }
+
+            let lookup_count = read_u16(bytes, lookup_list_offset) as usize;
+
+            if lookup_list_offset + 2 + (lookup_count * 2) > bytes.len() {
+                return Err(truncated);
+            }
+
+            for i in 0..lookup_count {
+                let lookup_offset =
+                    lookup_list_offset + read_u16(bytes, lookup_list_offset + 2 + (i * 2)) as usize;
+
+                lookups.push(Self::parse_lookup(bytes, lookup_offset, source)?);
+            }
+        }
+
+        Ok(Self {
+            major_version,
+            minor_version,
+            scripts,
+            features,
+            lookups,
+        })
+    }
+
+    /// The indexes of the lookups of a feature sorted in the order they're applied.
+    ///
+    /// The features of the default language system of `script` are used, falling back to the
+    /// `DFLT` script. If neither script is present every feature with the tag is used.
+    pub fn feature_lookups(&self, script: u32, feature_tag: u32) -> Vec<u16> {
+        let feature_indexes: Vec<usize> = match self
+            .scripts
+            .get(&script)
+            .or_else(|| self.scripts.get(&u32::from_be_bytes(*b"DFLT")))
+        {
+            Some(layout_script) => {
+                layout_script
+                    .default_feature_indexes
+                    .iter()
+                    .map(|i| *i as usize)
+                    .collect()
+            },
+            None => (0..self.features.len()).collect(),
+        };
+
+        let mut lookup_indexes: Vec<u16> = feature_indexes
+            .into_iter()
+            .filter_map(|i| self.features.get(i))
+            .filter(|feature| feature.feature_tag == feature_tag)
+            .flat_map(|feature| feature.lookup_indexes.iter().copied())
+            .filter(|i| (*i as usize) < self.lookups.len())
+            .collect();
+
+        lookup_indexes.sort_unstable();
+        lookup_indexes.dedup();
+        lookup_indexes
+    }
+
+    fn parse_lookup(
+        bytes: &[u8],
+        lookup_offset: usize,
+        source: ImtErrorSource,
+    ) -> Result<Lookup, ImtError> {
+        let truncated = ImtError {
+            kind: ImtErrorKind::Truncated,
+            source,
+        };
+
+        if lookup_offset + 6 > bytes.len() {
+            return Err(truncated);
+        }
+
+        let mut lookup_type = read_u16(bytes, lookup_offset);
+        let lookup_flag = read_u16(bytes, lookup_offset + 2);
+        let subtable_count = read_u16(bytes, lookup_offset + 4) as usize;
+
+        if lookup_offset + 6 + (subtable_count * 2) > bytes.len() {
+            return Err(truncated);
+        }
+
+        let is_extension = match source {
+            ImtErrorSource::GposTable => lookup_type == 9,
+            _ => lookup_type == 7,
+        };
+
+        let mut subtables = Vec::with_capacity(subtable_count);
+
+        for i in 0..subtable_count {
+            let mut subtable_offset =
+                lookup_offset + read_u16(bytes, lookup_offset + 6 + (i * 2)) as usize;
+
+            if is_extension {
+                if subtable_offset + 8 > bytes.len() {
+                    return Err(truncated);
+                }
+
+                if read_u16(bytes, subtable_offset) != 1 {
+                    return Err(ImtError {
+                        kind: ImtErrorKind::FormatNotSupported,
+                        source,
+                    });
+                }
+
+                // Every subtable of an extension lookup has the same type.
+                lookup_type = read_u16(bytes, subtable_offset + 2);
+                subtable_offset += read_u32(bytes, subtable_offset + 4) as usize;
+            }
+
+            let subtable = match (source, lookup_type) {
+                (ImtErrorSource::GsubTable, 1) => {
+                    SingleSubst::try_parse(bytes, subtable_offset, source)?
+                        .map(LookupSubtable::SingleSubst)
+                },
+                (ImtErrorSource::GsubTable, 4) => {
+                    LigatureSubst::try_parse(bytes, subtable_offset, source)?
+                        .map(LookupSubtable::LigatureSubst)
+                },
+                (ImtErrorSource::GposTable, 2) => {
+                    PairPos::try_parse(bytes, subtable_offset, source)?.map(LookupSubtable::PairPos)
+                },
+                _ => None,
+            };
+
+            subtables.push(subtable.unwrap_or(LookupSubtable::Unsupported));
+        }
+
+        Ok(Lookup {
+            lookup_type,
+            lookup_flag,
+            subtables,
+        })
+    }
+}
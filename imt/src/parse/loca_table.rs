@@ -1,8 +1,17 @@
+use alloc::vec::Vec;
+
 use crate::error::*;
 use crate::parse::{read_u16, read_u32, HeadTable, MaxpTable};
 
+/// Corresponds to the `loca` table.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/loca>
 #[derive(Debug, Clone)]
 pub struct LocaTable {
+    /// Offsets of each glyph into the `glyf` table, followed by the end offset of the last glyph.
+    ///
+    /// # Notes
+    /// - Offsets of the short format are already doubled.
+    /// - A glyph without an outline has an offset equal to the one that follows.
     pub offsets: Vec<u32>,
 }
 
@@ -1,39 +1,68 @@
 //! This `mod` contains the raw parsed data of a font file.
 
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use crate::error::*;
 
 pub mod avar_table;
+pub mod base_table;
 pub mod cmap_table;
+pub mod cvt_table;
 pub mod font;
+pub mod fpgm_table;
 pub mod fvar_table;
+pub mod gdef_table;
 pub mod glyf_table;
 pub mod gvar_table;
 pub mod head_table;
 pub mod hhea_table;
 pub mod hmtx_table;
 pub mod hvar_table;
+pub mod layout_table;
 pub mod loca_table;
 pub mod maxp_table;
+pub mod mvar_table;
 pub mod name_table;
+pub mod os2_table;
+pub mod post_table;
+pub mod prep_table;
 pub mod table_directory;
 pub mod ttc_header;
 
 pub use avar_table::{AvarTable, AxisValueMap, SegmentMap};
+pub use base_table::{BaseAxis, BaseScript, BaseTable, MinMax};
 pub use cmap_table::{CmapSubtable, CmapTable, EncodingRecord};
-pub use font::Font;
+pub use cvt_table::CvtTable;
+pub use font::{
+    Feature, Font, FontFlavor, FontOptions, ScriptSystem, ShapedGlyph, TableSet, VariationAxis,
+};
+pub use fpgm_table::FpgmTable;
 pub use fvar_table::{FvarTable, InstanceRecord, VariationAxisRecord};
-pub use glyf_table::{GlyfTable, Outline, OutlineGeometry, OutlinePoint};
+pub use gdef_table::GdefTable;
+pub use glyf_table::{
+    Component, ComponentPlacement, ComponentTransform, GlyfTable, Outline, OutlineGeometry,
+    OutlinePoint, PathEvent, PathEvents,
+};
 pub use gvar_table::{GlyphVariation, GvarTable, IntermediateTuples, TupleVariation};
 pub use head_table::HeadTable;
 pub use hhea_table::HheaTable;
 pub use hmtx_table::HmtxTable;
 pub use hvar_table::{
-    DeltaData, DeltaSet, HvarTable, ItemVariationData, ItemVariationStore, RegionAxisCoordinates,
-    VariationRegion,
+    DeltaData, DeltaSet, DeltaSetIndexMap, HvarTable, ItemVariationData, ItemVariationStore,
+    RegionAxisCoordinates, VariationRegion,
+};
+pub use layout_table::{
+    ClassDef, Coverage, LayoutFeature, LayoutScript, LayoutTable, Ligature, LigatureSubst, Lookup,
+    LookupSubtable, PairPos, PairValue, PosValue, SingleSubst,
 };
 pub use loca_table::LocaTable;
 pub use maxp_table::MaxpTable;
+pub use mvar_table::{MvarTable, ValueRecord};
 pub use name_table::{LangTagRecord, NameRecord, NameTable};
+pub use os2_table::Os2Table;
+pub use post_table::PostTable;
+pub use prep_table::PrepTable;
 pub use table_directory::{TableDirectory, TableRecord};
 pub use ttc_header::TTCHeader;
 
@@ -110,7 +139,7 @@ fn read_utf16be(
     })
 }
 
-const fn tag(bytes: &[u8; 4]) -> u32 {
+pub(crate) const fn tag(bytes: &[u8; 4]) -> u32 {
     u32::from_be_bytes(*bytes)
 }
 
@@ -128,4 +157,20 @@ pub mod table_tag {
     pub const GVAR: u32 = tag(b"gvar");
     pub const AVAR: u32 = tag(b"avar");
     pub const HVAR: u32 = tag(b"HVAR");
+    pub const OS2: u32 = tag(b"OS/2");
+    pub const POST: u32 = tag(b"post");
+    pub const MVAR: u32 = tag(b"MVAR");
+    pub const BASE: u32 = tag(b"BASE");
+    pub const DSIG: u32 = tag(b"DSIG");
+    pub const CVT: u32 = tag(b"cvt ");
+    pub const FPGM: u32 = tag(b"fpgm");
+    pub const PREP: u32 = tag(b"prep");
+    pub const GSUB: u32 = tag(b"GSUB");
+    pub const GPOS: u32 = tag(b"GPOS");
+    pub const GDEF: u32 = tag(b"GDEF");
+}
+
+#[cfg(test)]
+pub(crate) fn test() -> Font {
+    Font::from_bytes(include_bytes!("../RobotoFlex.ttf")).unwrap()
 }
@@ -0,0 +1,135 @@
+use alloc::vec::Vec;
+
+use crate::error::*;
+use crate::parse::{read_u16, read_u32, ItemVariationStore};
+
+const TRUNCATED: ImtError = ImtError {
+    kind: ImtErrorKind::Truncated,
+    source: ImtErrorSource::MvarTable,
+};
+
+const MALFORMED: ImtError = ImtError {
+    kind: ImtErrorKind::Malformed,
+    source: ImtErrorSource::MvarTable,
+};
+
+/// Corresponds to the `MVAR` table.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/mvar>
+#[derive(Debug, Clone)]
+pub struct MvarTable {
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub value_records: Vec<ValueRecord>,
+    /// `None` if the table does not have any value records.
+    pub item_variation_store: Option<ItemVariationStore>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ValueRecord {
+    pub value_tag: u32,
+    pub outer_index: usize,
+    pub inner_index: usize,
+}
+
+impl MvarTable {
+    pub fn try_parse(bytes: &[u8], table_offset: usize) -> Result<Self, ImtError> {
+        // Read Header
+
+        if table_offset + 12 > bytes.len() {
+            return Err(TRUNCATED);
+        }
+
+        let major_version = read_u16(bytes, table_offset);
+        let minor_version = read_u16(bytes, table_offset + 2);
+
+        if major_version != 1 || minor_version != 0 {
+            return Err(ImtError {
+                kind: ImtErrorKind::UnexpectedVersion,
+                source: ImtErrorSource::MvarTable,
+            });
+        }
+
+        let value_record_size = read_u16(bytes, table_offset + 6) as usize;
+        let value_record_count = read_u16(bytes, table_offset + 8) as usize;
+
+        let var_store_offset = match read_u16(bytes, table_offset + 10) {
+            0 => None,
+            offset => Some(offset as usize + table_offset),
+        };
+
+        // Read ValueRecord's
+
+        if value_record_count > 0 && value_record_size < 8 {
+            return Err(MALFORMED);
+        }
+
+        if table_offset + 12 + (value_record_count * value_record_size) > bytes.len() {
+            return Err(TRUNCATED);
+        }
+
+        let mut value_records = Vec::with_capacity(value_record_count);
+
+        for i in 0..value_record_count {
+            let record_offset = table_offset + 12 + (i * value_record_size);
+
+            value_records.push(ValueRecord {
+                value_tag: read_u32(bytes, record_offset),
+                outer_index: read_u16(bytes, record_offset + 4) as usize,
+                inner_index: read_u16(bytes, record_offset + 6) as usize,
+            });
+        }
+
+        let item_variation_store = match var_store_offset {
+            Some(offset) => Some(ItemVariationStore::try_parse(bytes, offset)?),
+            None => {
+                if !value_records.is_empty() {
+                    return Err(MALFORMED);
+                }
+
+                None
+            },
+        };
+
+        Ok(Self {
+            major_version,
+            minor_version,
+            value_records,
+            item_variation_store,
+        })
+    }
+
+    /// Find the value record for the provided tag.
+    pub fn value_record(&self, value_tag: u32) -> Option<&ValueRecord> {
+        self.value_records
+            .iter()
+            .find(|record| record.value_tag == value_tag)
+    }
+}
+
+/// Value tags used by `MVAR` value records.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/mvar#value-tags>
+pub mod value_tag {
+    use crate::parse::tag;
+    /// `OS/2.sTypoAscender`
+    pub const HASC: u32 = tag(b"hasc");
+    /// `OS/2.sTypoDescender`
+    pub const HDSC: u32 = tag(b"hdsc");
+    /// `OS/2.sTypoLineGap`
+    pub const HLGP: u32 = tag(b"hlgp");
+    /// `OS/2.usWinAscent`
+    pub const HCLA: u32 = tag(b"hcla");
+    /// `OS/2.usWinDescent`
+    pub const HCLD: u32 = tag(b"hcld");
+    /// `OS/2.sxHeight`
+    pub const XHGT: u32 = tag(b"xhgt");
+    /// `OS/2.sCapHeight`
+    pub const CPHT: u32 = tag(b"cpht");
+    /// `post.underlinePosition`
+    pub const UNDO: u32 = tag(b"undo");
+    /// `post.underlineThickness`
+    pub const UNDS: u32 = tag(b"unds");
+    /// `OS/2.yStrikeoutPosition`
+    pub const STRO: u32 = tag(b"stro");
+    /// `OS/2.yStrikeoutSize`
+    pub const STRS: u32 = tag(b"strs");
+}
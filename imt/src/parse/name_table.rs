@@ -1,3 +1,6 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use crate::error::*;
 use crate::parse::{read_u16, read_utf16be};
 
@@ -88,6 +91,55 @@ impl NameTable {
             lang_tag_records,
         })
     }
+
+    /// Find the string for the provided `name_id`.
+    ///
+    /// # Notes
+    /// - Windows English (US) records are preferred, followed by any Windows record, then the
+    ///   first record with a matching `name_id`.
+    pub fn name(&self, name_id: u16) -> Option<&str> {
+        let mut fallback: Option<&NameRecord> = None;
+
+        for record in self.name_records.iter() {
+            if record.name_id != name_id {
+                continue;
+            }
+
+            if record.platform_id == 3 {
+                if record.language_id == 0x0409 {
+                    return Some(&record.name);
+                }
+
+                if fallback
+                    .map(|record| record.platform_id != 3)
+                    .unwrap_or(true)
+                {
+                    fallback = Some(record);
+                }
+            } else if fallback.is_none() {
+                fallback = Some(record);
+            }
+        }
+
+        fallback.map(|record| record.name.as_str())
+    }
+
+    /// The name a family is grouped by, e.g. in a font picker.
+    ///
+    /// This is the Typographic Family Name (name ID 16), falling back to the Font Family Name
+    /// (name ID 1) when absent. Families with more than the four styles of regular, italic, bold
+    /// and bold italic only group correctly with the former.
+    pub fn typographic_family(&self) -> Option<&str> {
+        self.name(16).or_else(|| self.name(1))
+    }
+
+    /// The name of the style within the family of `typographic_family`, e.g. `Condensed Bold`.
+    ///
+    /// This is the Typographic Subfamily Name (name ID 17), falling back to the Font Subfamily
+    /// Name (name ID 2) when absent.
+    pub fn typographic_subfamily(&self) -> Option<&str> {
+        self.name(17).or_else(|| self.name(2))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,182 @@
+use crate::error::*;
+use crate::parse::{read_i16, read_u16, read_u32};
+
+/// Corresponds to the `OS/2` table.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/os2>
+///
+/// # Notes
+/// - Fields introduced after version 0 are `None` when the table version does not contain them.
+#[derive(Debug, Clone)]
+pub struct Os2Table {
+    pub version: u16,
+    pub x_avg_char_width: i16,
+    pub us_weight_class: u16,
+    pub us_width_class: u16,
+    pub fs_type: u16,
+    pub y_subscript_x_size: i16,
+    pub y_subscript_y_size: i16,
+    pub y_subscript_x_offset: i16,
+    pub y_subscript_y_offset: i16,
+    pub y_superscript_x_size: i16,
+    pub y_superscript_y_size: i16,
+    pub y_superscript_x_offset: i16,
+    pub y_superscript_y_offset: i16,
+    pub y_strikeout_size: i16,
+    pub y_strikeout_position: i16,
+    pub s_family_class: i16,
+    pub panose: [u8; 10],
+    pub ul_unicode_range: [u32; 4],
+    pub ach_vend_id: u32,
+    pub fs_selection: u16,
+    pub us_first_char_index: u16,
+    pub us_last_char_index: u16,
+    pub s_typo_ascender: i16,
+    pub s_typo_descender: i16,
+    pub s_typo_line_gap: i16,
+    pub us_win_ascent: u16,
+    pub us_win_descent: u16,
+    /// Version 1 and later
+    pub ul_code_page_range: Option<[u32; 2]>,
+    /// Version 2 and later
+    pub sx_height: Option<i16>,
+    /// Version 2 and later
+    pub s_cap_height: Option<i16>,
+    /// Version 2 and later
+    pub us_default_char: Option<u16>,
+    /// Version 2 and later
+    pub us_break_char: Option<u16>,
+    /// Version 2 and later
+    pub us_max_context: Option<u16>,
+    /// Version 5 and later
+    pub us_lower_optical_point_size: Option<u16>,
+    /// Version 5 and later
+    pub us_upper_optical_point_size: Option<u16>,
+}
+
+impl Os2Table {
+    pub fn try_parse(bytes: &[u8], table_offset: usize) -> Result<Self, ImtError> {
+        if table_offset + 2 > bytes.len() {
+            return Err(ImtError {
+                kind: ImtErrorKind::Truncated,
+                source: ImtErrorSource::Os2Table,
+            });
+        }
+
+        let version = read_u16(bytes, table_offset);
+
+        let length = match version {
+            0 => 78,
+            1 => 86,
+            2..=4 => 96,
+            5 => 100,
+            _ => {
+                return Err(ImtError {
+                    kind: ImtErrorKind::UnexpectedVersion,
+                    source: ImtErrorSource::Os2Table,
+                })
+            },
+        };
+
+        if table_offset + length > bytes.len() {
+            return Err(ImtError {
+                kind: ImtErrorKind::Truncated,
+                source: ImtErrorSource::Os2Table,
+            });
+        }
+
+        let mut panose = [0; 10];
+        panose.copy_from_slice(&bytes[(table_offset + 32)..(table_offset + 42)]);
+
+        let ul_code_page_range = if version >= 1 {
+            Some([
+                read_u32(bytes, table_offset + 78),
+                read_u32(bytes, table_offset + 82),
+            ])
+        } else {
+            None
+        };
+
+        let (sx_height, s_cap_height, us_default_char, us_break_char, us_max_context) =
+            if version >= 2 {
+                (
+                    Some(read_i16(bytes, table_offset + 86)),
+                    Some(read_i16(bytes, table_offset + 88)),
+                    Some(read_u16(bytes, table_offset + 90)),
+                    Some(read_u16(bytes, table_offset + 92)),
+                    Some(read_u16(bytes, table_offset + 94)),
+                )
+            } else {
+                (None, None, None, None, None)
+            };
+
+        let (us_lower_optical_point_size, us_upper_optical_point_size) = if version >= 5 {
+            (
+                Some(read_u16(bytes, table_offset + 96)),
+                Some(read_u16(bytes, table_offset + 98)),
+            )
+        } else {
+            (None, None)
+        };
+
+        Ok(Self {
+            version,
+            x_avg_char_width: read_i16(bytes, table_offset + 2),
+            us_weight_class: read_u16(bytes, table_offset + 4),
+            us_width_class: read_u16(bytes, table_offset + 6),
+            fs_type: read_u16(bytes, table_offset + 8),
+            y_subscript_x_size: read_i16(bytes, table_offset + 10),
+            y_subscript_y_size: read_i16(bytes, table_offset + 12),
+            y_subscript_x_offset: read_i16(bytes, table_offset + 14),
+            y_subscript_y_offset: read_i16(bytes, table_offset + 16),
+            y_superscript_x_size: read_i16(bytes, table_offset + 18),
+            y_superscript_y_size: read_i16(bytes, table_offset + 20),
+            y_superscript_x_offset: read_i16(bytes, table_offset + 22),
+            y_superscript_y_offset: read_i16(bytes, table_offset + 24),
+            y_strikeout_size: read_i16(bytes, table_offset + 26),
+            y_strikeout_position: read_i16(bytes, table_offset + 28),
+            s_family_class: read_i16(bytes, table_offset + 30),
+            panose,
+            ul_unicode_range: [
+                read_u32(bytes, table_offset + 42),
+                read_u32(bytes, table_offset + 46),
+                read_u32(bytes, table_offset + 50),
+                read_u32(bytes, table_offset + 54),
+            ],
+            ach_vend_id: read_u32(bytes, table_offset + 58),
+            fs_selection: read_u16(bytes, table_offset + 62),
+            us_first_char_index: read_u16(bytes, table_offset + 64),
+            us_last_char_index: read_u16(bytes, table_offset + 66),
+            s_typo_ascender: read_i16(bytes, table_offset + 68),
+            s_typo_descender: read_i16(bytes, table_offset + 70),
+            s_typo_line_gap: read_i16(bytes, table_offset + 72),
+            us_win_ascent: read_u16(bytes, table_offset + 74),
+            us_win_descent: read_u16(bytes, table_offset + 76),
+            ul_code_page_range,
+            sx_height,
+            s_cap_height,
+            us_default_char,
+            us_break_char,
+            us_max_context,
+            us_lower_optical_point_size,
+            us_upper_optical_point_size,
+        })
+    }
+
+    pub fn is_italic(&self) -> bool {
+        self.fs_selection & 0x0001 == 0x0001
+    }
+
+    pub fn is_bold(&self) -> bool {
+        self.fs_selection & 0x0020 == 0x0020
+    }
+
+    /// If set the typographic metrics should be used for line spacing instead of `hhea`.
+    pub fn use_typo_metrics(&self) -> bool {
+        self.fs_selection & 0x0080 == 0x0080
+    }
+
+    /// Whether PANOSE classifies the font as a monospaced latin text font.
+    pub fn panose_monospaced(&self) -> bool {
+        self.panose[0] == 2 && self.panose[3] == 9
+    }
+}
@@ -0,0 +1,55 @@
+use crate::error::*;
+use crate::parse::{read_fixed, read_i16, read_u32};
+
+/// Corresponds to the `post` table.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/post>
+///
+/// # Notes
+/// - Only the header is parsed, glyph names are not.
+#[derive(Debug, Clone)]
+pub struct PostTable {
+    pub version: u32,
+    pub italic_angle: f32,
+    pub underline_position: i16,
+    pub underline_thickness: i16,
+    pub is_fixed_pitch: u32,
+    pub min_mem_type_42: u32,
+    pub max_mem_type_42: u32,
+    pub min_mem_type_1: u32,
+    pub max_mem_type_1: u32,
+}
+
+impl PostTable {
+    pub fn try_parse(bytes: &[u8], table_offset: usize) -> Result<Self, ImtError> {
+        if table_offset + 32 > bytes.len() {
+            return Err(ImtError {
+                kind: ImtErrorKind::Truncated,
+                source: ImtErrorSource::PostTable,
+            });
+        }
+
+        let version = read_u32(bytes, table_offset);
+
+        match version {
+            0x00010000 | 0x00020000 | 0x00025000 | 0x00030000 => (),
+            _ => {
+                return Err(ImtError {
+                    kind: ImtErrorKind::UnexpectedVersion,
+                    source: ImtErrorSource::PostTable,
+                })
+            },
+        }
+
+        Ok(Self {
+            version,
+            italic_angle: read_fixed(bytes, table_offset + 4),
+            underline_position: read_i16(bytes, table_offset + 8),
+            underline_thickness: read_i16(bytes, table_offset + 10),
+            is_fixed_pitch: read_u32(bytes, table_offset + 12),
+            min_mem_type_42: read_u32(bytes, table_offset + 16),
+            max_mem_type_42: read_u32(bytes, table_offset + 20),
+            min_mem_type_1: read_u32(bytes, table_offset + 24),
+            max_mem_type_1: read_u32(bytes, table_offset + 28),
+        })
+    }
+}
@@ -0,0 +1,30 @@
+use alloc::vec::Vec;
+
+use crate::error::*;
+
+/// Corresponds to the `prep` table.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/prep>
+///
+/// # Notes
+/// - Only parsed when `FontOptions::instructions` is enabled.
+/// - Instructions are not interpreted.
+#[derive(Debug, Clone)]
+pub struct PrepTable {
+    /// Instructions executed whenever the point size or transformation changes.
+    pub instructions: Vec<u8>,
+}
+
+impl PrepTable {
+    pub fn try_parse(bytes: &[u8], table_offset: usize) -> Result<Self, ImtError> {
+        if table_offset > bytes.len() {
+            return Err(ImtError {
+                kind: ImtErrorKind::Truncated,
+                source: ImtErrorSource::PrepTable,
+            });
+        }
+
+        Ok(Self {
+            instructions: bytes[table_offset..].to_vec(),
+        })
+    }
+}
@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use crate::error::*;
 use crate::parse::tag;
 
@@ -28,7 +30,8 @@ impl TableDirectory {
             });
         }
 
-        if sfnt_version != 65536 {
+        // Apple uses `true` for TrueType outlines as well.
+        if sfnt_version != 65536 && sfnt_version != tag(b"true") {
             return Err(ImtError {
                 kind: ImtErrorKind::InvalidSfntVersion,
                 source: ImtErrorSource::TableDirectory,
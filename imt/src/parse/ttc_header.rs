@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use crate::error::*;
 use crate::parse::tag;
 
@@ -40,8 +42,6 @@ impl TTCHeader {
         let num_fonts = u32::from_be_bytes(bytes[8..12].try_into().unwrap());
         let table_directory_offsets_end = 12 + (num_fonts as usize * 4);
 
-        dbg!(ttc_tag, major_version, minor_version, num_fonts);
-
         if bytes.len() < table_directory_offsets_end {
             return Err(ImtError {
                 kind: ImtErrorKind::Truncated,
@@ -0,0 +1,149 @@
+use alloc::vec::Vec;
+#[cfg(feature = "gpu")]
+use std::sync::Arc;
+
+#[cfg(feature = "gpu")]
+use vulkano::device::Queue;
+
+use crate::raster::cpu::CpuRasterizer;
+#[cfg(feature = "gpu")]
+use crate::raster::gpu::compute::GpuRasteredGlyph;
+#[cfg(feature = "gpu")]
+use crate::raster::gpu::GpuRasterizer;
+use crate::raster::ScaledGlyph;
+
+/// A rasterizer that uses either the GPU or the CPU.
+///
+/// This allows applications to be written independently of the backend and to fall back to
+/// the CPU on machines without a compute queue.
+#[allow(clippy::large_enum_variant)]
+pub enum Rasterizer {
+    #[cfg(feature = "gpu")]
+    Gpu(GpuRasterizer),
+    Cpu(CpuRasterizer),
+}
+
+impl Rasterizer {
+    /// Use the GPU when a compute queue is provided, otherwise the CPU.
+    ///
+    /// Only available with the `gpu` feature, see `new_cpu` otherwise.
+    #[cfg(feature = "gpu")]
+    pub fn new_auto(queue: Option<Arc<Queue>>) -> Self {
+        match queue {
+            Some(queue) => Self::Gpu(GpuRasterizer::new(queue)),
+            None => Self::new_cpu(),
+        }
+    }
+
+    /// Use the CPU, this is available regardless of features.
+    pub fn new_cpu() -> Self {
+        Self::Cpu(CpuRasterizer::new())
+    }
+
+    pub fn is_gpu(&self) -> bool {
+        !matches!(self, Self::Cpu(_))
+    }
+
+    pub fn process(&self, glyphs: &[ScaledGlyph]) -> Vec<RasteredGlyph> {
+        match self {
+            #[cfg(feature = "gpu")]
+            Self::Gpu(gpu) => {
+                gpu.process(glyphs)
+                    .into_iter()
+                    .map(RasteredGlyph::Gpu)
+                    .collect()
+            },
+            Self::Cpu(cpu) => {
+                glyphs
+                    .iter()
+                    .map(|glyph| {
+                        RasteredGlyph::Cpu(CpuRasteredGlyph {
+                            width: glyph.width,
+                            height: glyph.height,
+                            bearing_x: glyph.bearing_x,
+                            bearing_y: glyph.bearing_y,
+                            advance_w: glyph.advance_w,
+                            coverage: cpu.render(glyph),
+                            unique_id: glyph.unique_id,
+                        })
+                    })
+                    .collect()
+            },
+        }
+    }
+}
+
+/// A glyph rasterized on the CPU by `Rasterizer`.
+#[derive(Debug, Clone)]
+pub struct CpuRasteredGlyph {
+    pub width: u32,
+    pub height: u32,
+    pub bearing_x: i16,
+    pub bearing_y: i16,
+    pub advance_w: i16,
+    /// Single channel coverage of `width * height` bytes, see `CpuRasterizer::render`.
+    pub coverage: Vec<u8>,
+    pub unique_id: u64,
+}
+
+/// A glyph rasterized by `Rasterizer`.
+///
+/// # Notes
+/// - The bitmap differs between backends. The GPU produces subpixel coverage in an image, while
+///   the CPU produces single channel coverage in memory.
+#[derive(Debug, Clone)]
+pub enum RasteredGlyph {
+    #[cfg(feature = "gpu")]
+    Gpu(GpuRasteredGlyph),
+    Cpu(CpuRasteredGlyph),
+}
+
+impl RasteredGlyph {
+    pub fn width(&self) -> u32 {
+        match self {
+            #[cfg(feature = "gpu")]
+            Self::Gpu(glyph) => glyph.width,
+            Self::Cpu(glyph) => glyph.width,
+        }
+    }
+
+    pub fn height(&self) -> u32 {
+        match self {
+            #[cfg(feature = "gpu")]
+            Self::Gpu(glyph) => glyph.height,
+            Self::Cpu(glyph) => glyph.height,
+        }
+    }
+
+    pub fn bearing_x(&self) -> i16 {
+        match self {
+            #[cfg(feature = "gpu")]
+            Self::Gpu(glyph) => glyph.bearing_x,
+            Self::Cpu(glyph) => glyph.bearing_x,
+        }
+    }
+
+    pub fn bearing_y(&self) -> i16 {
+        match self {
+            #[cfg(feature = "gpu")]
+            Self::Gpu(glyph) => glyph.bearing_y,
+            Self::Cpu(glyph) => glyph.bearing_y,
+        }
+    }
+
+    pub fn advance_w(&self) -> i16 {
+        match self {
+            #[cfg(feature = "gpu")]
+            Self::Gpu(glyph) => glyph.advance_w,
+            Self::Cpu(glyph) => glyph.advance_w,
+        }
+    }
+
+    pub fn unique_id(&self) -> u64 {
+        match self {
+            #[cfg(feature = "gpu")]
+            Self::Gpu(glyph) => glyph.unique_id,
+            Self::Cpu(glyph) => glyph.unique_id,
+        }
+    }
+}
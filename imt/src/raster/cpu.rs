@@ -0,0 +1,566 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::layout::pen_positions;
+use crate::parse::Font;
+use crate::raster::{ScaledGlyph, ScaledGlyphErr, DEFAULT_CURVE_TOLERANCE};
+use crate::util::float;
+use crate::util::unicode::is_default_ignorable;
+
+/// Amount of samples taken vertically within each row of pixels.
+const SUB_SCANLINES: usize = 16;
+/// Curve tolerance used by `CpuRasterizer::render_supersampled`.
+const REFERENCE_CURVE_TOLERANCE: f32 = 0.001;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuRasterErr {
+    /// The glyph does not fit within the target buffer at the requested location.
+    OutOfBounds,
+}
+
+impl fmt::Display for CpuRasterErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::OutOfBounds => "glyph does not fit within the buffer",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CpuRasterErr {}
+
+/// Rasterizes glyphs on the CPU into single channel coverage.
+///
+/// Coverage is computed with the nonzero winding rule. Horizontal coverage is exact, while
+/// vertical coverage is sampled `SUB_SCANLINES` times per row.
+#[derive(Debug, Clone, Default)]
+pub struct CpuRasterizer {}
+
+impl CpuRasterizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render a glyph into a new buffer of `width * height` bytes.
+    pub fn render(&self, glyph: &ScaledGlyph) -> Vec<u8> {
+        let mut buf = vec![0; glyph.width as usize * glyph.height as usize];
+        self.render_into(glyph, &mut buf, glyph.width as usize, 0, 0)
+            .unwrap();
+        buf
+    }
+
+    /// Render a glyph into a 1-bit bitmap, e.g. for monochrome displays.
+    ///
+    /// Pixels with at least half coverage are set. See `MonoBitmap` for the layout of the bits.
+    pub fn render_mono(&self, glyph: &ScaledGlyph) -> MonoBitmap {
+        let width = glyph.width as usize;
+        let stride = width.div_ceil(8);
+        let coverage = self.render(glyph);
+        let mut data = vec![0; stride * glyph.height as usize];
+
+        if width != 0 {
+            for (row, values) in coverage.chunks_exact(width).enumerate() {
+                for (x, value) in values.iter().enumerate() {
+                    if *value >= 128 {
+                        data[(row * stride) + (x / 8)] |= 0x80 >> (x % 8);
+                    }
+                }
+            }
+        }
+
+        MonoBitmap {
+            width: glyph.width,
+            height: glyph.height,
+            stride,
+            data,
+        }
+    }
+
+    /// Render a glyph by sampling a grid of `samples` by `samples` points within each pixel.
+    ///
+    /// Coverage is the fraction of points inside of the outline, i.e. a box filter. This is
+    /// slow, but simple enough to serve as a reference that faster paths can be compared against
+    /// within a tolerance, e.g. to produce golden images.
+    ///
+    /// # Notes
+    /// - A `samples` of zero is treated as one.
+    /// - Curves are flattened much finer than with `render`.
+    pub fn render_supersampled(&self, glyph: &ScaledGlyph, samples: u32) -> Vec<u8> {
+        let width = glyph.width as usize;
+        let height = glyph.height as usize;
+        let samples = samples.max(1) as usize;
+        let mut counts = vec![0_usize; width * height];
+
+        let edges = pixel_edges(glyph, REFERENCE_CURVE_TOLERANCE);
+
+        let mut crossings: Vec<(f32, i32)> = Vec::new();
+
+        for sample_row in 0..(height * samples) {
+            let sample_y = (sample_row as f32 + 0.5) / samples as f32;
+            crossings.clear();
+
+            for [x1, y1, x2, y2] in edges.iter().copied() {
+                if sample_y < y1.min(y2) || sample_y >= y1.max(y2) {
+                    continue;
+                }
+
+                let cross_x = x1 + ((sample_y - y1) * (x2 - x1) / (y2 - y1));
+                crossings.push((cross_x, if y2 > y1 { 1 } else { -1 }));
+            }
+
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let row_start = (sample_row / samples) * width;
+            let mut winding = 0;
+            let mut next = 0;
+
+            for sample_column in 0..(width * samples) {
+                let sample_x = (sample_column as f32 + 0.5) / samples as f32;
+
+                while next < crossings.len() && crossings[next].0 <= sample_x {
+                    winding += crossings[next].1;
+                    next += 1;
+                }
+
+                if winding != 0 {
+                    counts[row_start + (sample_column / samples)] += 1;
+                }
+            }
+        }
+
+        counts
+            .into_iter()
+            .map(|count| float::round((count * 255) as f32 / (samples * samples) as f32) as u8)
+            .collect()
+    }
+
+    /// Render a glyph into a caller owned buffer of single channel coverage.
+    ///
+    /// `stride` is the amount of bytes per row of `buf` and `x` & `y` is the location in pixels
+    /// of the top left of the glyph within `buf`.
+    ///
+    /// # Notes
+    /// - Coverage is combined with existing values by taking the maximum, so glyphs rendered into
+    ///   the same canvas do not erase each other.
+    /// - Glyphs without an outline are not rendered.
+    /// - Curves are flattened within `DEFAULT_CURVE_TOLERANCE` pixels, the same as the default
+    ///   of the GPU rasterizer.
+    pub fn render_into(
+        &self,
+        glyph: &ScaledGlyph,
+        buf: &mut [u8],
+        stride: usize,
+        x: usize,
+        y: usize,
+    ) -> Result<(), CpuRasterErr> {
+        if glyph.outline.is_none() {
+            return Ok(());
+        }
+
+        let width = glyph.width as usize;
+        let height = glyph.height as usize;
+
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let row_end = x.checked_add(width).ok_or(CpuRasterErr::OutOfBounds)?;
+
+        let buf_end = y
+            .checked_add(height - 1)
+            .and_then(|last_row| last_row.checked_mul(stride))
+            .and_then(|offset| offset.checked_add(row_end))
+            .ok_or(CpuRasterErr::OutOfBounds)?;
+
+        if row_end > stride || buf_end > buf.len() {
+            return Err(CpuRasterErr::OutOfBounds);
+        }
+
+        let edges = pixel_edges(glyph, DEFAULT_CURVE_TOLERANCE);
+        let mut crossings: Vec<(f32, i32)> = Vec::new();
+        let mut coverage = vec![0.0_f32; width];
+
+        for row in 0..height {
+            coverage.iter_mut().for_each(|value| *value = 0.0);
+
+            for sub in 0..SUB_SCANLINES {
+                let sample_y = row as f32 + ((sub as f32 + 0.5) / SUB_SCANLINES as f32);
+                crossings.clear();
+
+                for [x1, y1, x2, y2] in edges.iter().copied() {
+                    if sample_y < y1.min(y2) || sample_y >= y1.max(y2) {
+                        continue;
+                    }
+
+                    let cross_x = x1 + ((sample_y - y1) * (x2 - x1) / (y2 - y1));
+                    crossings.push((cross_x, if y2 > y1 { 1 } else { -1 }));
+                }
+
+                crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let mut winding = 0;
+
+                for i in 0..crossings.len() {
+                    winding += crossings[i].1;
+
+                    if winding != 0 && i + 1 < crossings.len() {
+                        accumulate_span(&mut coverage, crossings[i].0, crossings[i + 1].0);
+                    }
+                }
+            }
+
+            let row_start = ((y + row) * stride) + x;
+
+            for (dst, value) in buf[row_start..(row_start + width)]
+                .iter_mut()
+                .zip(coverage.iter())
+            {
+                let value = float::round((value / SUB_SCANLINES as f32) * 255.0).min(255.0) as u8;
+                *dst = (*dst).max(value);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Edges of the glyph's flattened outline in pixels, without horizontal edges as they never
+/// cross a scanline.
+fn pixel_edges(glyph: &ScaledGlyph, curve_tolerance: f32) -> Vec<[f32; 4]> {
+    let width = glyph.width as f32;
+    let height = glyph.height as f32;
+
+    glyph
+        .segment_data(curve_tolerance)
+        .into_iter()
+        .map(|[x1, y1, x2, y2]| [x1 * width, y1 * height, x2 * width, y2 * height])
+        .filter(|[_, y1, _, y2]| y1 != y2)
+        .collect()
+}
+
+/// A 1-bit bitmap produced by `CpuRasterizer::render_mono`.
+///
+/// Each row is `stride` bytes with eight pixels per byte. The leftmost pixel of a byte is its
+/// most significant bit, unused bits at the end of a row are zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonoBitmap {
+    pub width: u32,
+    pub height: u32,
+    /// Amount of bytes per row.
+    pub stride: usize,
+    pub data: Vec<u8>,
+}
+
+impl MonoBitmap {
+    /// Check if the pixel at `x` & `y` is set. Pixels outside of the bitmap are not set.
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+
+        self.data[(y as usize * self.stride) + (x as usize / 8)] & (0x80 >> (x % 8)) != 0
+    }
+}
+
+/// A line of text rendered into a single image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedString {
+    pub width: u32,
+    pub height: u32,
+    /// Row of the baseline from the top of the image.
+    pub baseline: u32,
+    /// Row-major RGBA pixels with premultiplied alpha.
+    pub pixels: Vec<[u8; 4]>,
+}
+
+/// A shadow drawn beneath the text by `render_string_shadowed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShadowStyle {
+    /// Horizontal offset of the shadow in pixels, positive is to the right.
+    pub dx: i32,
+    /// Vertical offset of the shadow in pixels, positive is downwards.
+    pub dy: i32,
+    /// Radius of the box blur applied to the shadow in pixels. Zero results in a hard shadow.
+    pub blur: u32,
+    /// Color of the shadow, not premultiplied.
+    pub color: [u8; 4],
+}
+
+/// Color of glyphs when no colors are provided to `render_string_colored`.
+const DEFAULT_GLYPH_COLOR: [u8; 4] = [0, 0, 0, 255];
+
+/// Render a line of text into a single image with a color per glyph.
+///
+/// Each character is mapped to a glyph and composited over the previous ones with the color at
+/// the same index of `colors`. When `colors` is shorter than `text` the last color is repeated,
+/// if it is empty opaque black is used. The image is just large enough to contain the ink and
+/// the advance of the line.
+///
+/// # Notes
+/// - `coords` should be normalized.
+/// - Characters are mapped with the same `cmap` subtable as `Font::glyph_for_char`, unmapped
+///   ones use the missing glyph.
+/// - Glyphs are positioned with their unrounded advances, see `layout::pen_positions`.
+/// - Default ignorable characters are not rendered and have no advance, see
+///   `util::unicode::is_default_ignorable`. They still have an index in `colors`.
+pub fn render_string_colored(
+    rasterizer: &CpuRasterizer,
+    font: &Font,
+    coords: Option<&[f32]>,
+    size: f32,
+    text: &str,
+    colors: &[[u8; 4]],
+) -> Result<RenderedString, ScaledGlyphErr> {
+    render_string(rasterizer, font, coords, size, text, colors, None)
+}
+
+/// Render a line of text into a single image with a color per glyph and a shadow beneath it.
+///
+/// This is the same as `render_string_colored`, except that the coverage of the text is offset,
+/// blurred and composited beneath it with the color of `shadow`. The image is enlarged to
+/// contain the shadow, so `baseline` may differ from the image without a shadow.
+///
+/// # Notes
+/// - The blur is a single pass of a box blur, larger radii will look boxy.
+pub fn render_string_shadowed(
+    rasterizer: &CpuRasterizer,
+    font: &Font,
+    coords: Option<&[f32]>,
+    size: f32,
+    text: &str,
+    colors: &[[u8; 4]],
+    shadow: &ShadowStyle,
+) -> Result<RenderedString, ScaledGlyphErr> {
+    render_string(rasterizer, font, coords, size, text, colors, Some(shadow))
+}
+
+fn render_string(
+    rasterizer: &CpuRasterizer,
+    font: &Font,
+    coords: Option<&[f32]>,
+    size: f32,
+    text: &str,
+    colors: &[[u8; 4]],
+    shadow: Option<&ShadowStyle>,
+) -> Result<RenderedString, ScaledGlyphErr> {
+    let subtable = font.unicode_subtable().ok_or(ScaledGlyphErr::Missing)?;
+
+    let mut glyph_ids = Vec::new();
+    subtable.map_str(text, &mut glyph_ids);
+
+    // The index of the `char` each glyph is produced from, as `colors` is indexed by `char`.
+    let mut char_indexes = Vec::with_capacity(glyph_ids.len());
+    let mut glyphs = Vec::with_capacity(glyph_ids.len());
+
+    for (i, (c, glyph_id)) in text.chars().zip(glyph_ids).enumerate() {
+        if is_default_ignorable(c) {
+            continue;
+        }
+
+        char_indexes.push(i);
+        glyphs.push(ScaledGlyph::evaluate(font, coords, true, glyph_id, size)?);
+    }
+
+    let positions = pen_positions(&glyphs, true);
+    let mut x_min = 0;
+    let mut x_max = positions[glyphs.len()];
+    let mut ascent = 0;
+    let mut descent = 0;
+
+    for (glyph, pen_x) in glyphs.iter().zip(positions.iter()) {
+        if glyph.outline.is_none() {
+            continue;
+        }
+
+        let left = pen_x + glyph.bearing_x as i32;
+        x_min = x_min.min(left);
+        x_max = x_max.max(left + glyph.width as i32);
+        ascent = ascent.max(glyph.bearing_y as i32 + glyph.height as i32);
+        descent = descent.max(-(glyph.bearing_y as i32));
+    }
+
+    let width = (x_max - x_min).max(0) as usize;
+    let height = (ascent + descent) as usize;
+    let mut pixels = vec![[0.0_f32; 4]; width * height];
+
+    for (i, (glyph, pen_x)) in glyphs.iter().zip(positions.iter()).enumerate() {
+        if glyph.outline.is_none() {
+            continue;
+        }
+
+        let color = colors
+            .get(char_indexes[i])
+            .or_else(|| colors.last())
+            .unwrap_or(&DEFAULT_GLYPH_COLOR);
+
+        let alpha = color[3] as f32 / 255.0;
+        let coverage = rasterizer.render(glyph);
+        let left = (pen_x + glyph.bearing_x as i32 - x_min) as usize;
+        let top = (ascent - (glyph.bearing_y as i32 + glyph.height as i32)) as usize;
+
+        for (row, coverage_row) in coverage.chunks_exact(glyph.width as usize).enumerate() {
+            let row_start = ((top + row) * width) + left;
+
+            for (dst, value) in pixels[row_start..(row_start + glyph.width as usize)]
+                .iter_mut()
+                .zip(coverage_row.iter())
+            {
+                let src_alpha = alpha * (*value as f32 / 255.0);
+
+                for (channel, dst) in dst.iter_mut().enumerate() {
+                    let src = match channel {
+                        3 => src_alpha,
+                        _ => (color[channel] as f32 / 255.0) * src_alpha,
+                    };
+
+                    *dst = src + (*dst * (1.0 - src_alpha));
+                }
+            }
+        }
+    }
+
+    let (width, height, baseline, pixels) = match shadow {
+        Some(shadow) => composite_shadow(shadow, width, height, ascent as usize, pixels),
+        None => (width, height, ascent as usize, pixels),
+    };
+
+    Ok(RenderedString {
+        width: width as u32,
+        height: height as u32,
+        baseline: baseline as u32,
+        pixels: pixels
+            .into_iter()
+            .map(|pixel| pixel.map(|value| float::round(value * 255.0).min(255.0) as u8))
+            .collect(),
+    })
+}
+
+/// Composite `shadow` beneath the premultiplied `pixels` of the text, enlarging the image to
+/// contain it. Returns the new width, height, baseline & pixels.
+fn composite_shadow(
+    shadow: &ShadowStyle,
+    width: usize,
+    height: usize,
+    baseline: usize,
+    pixels: Vec<[f32; 4]>,
+) -> (usize, usize, usize, Vec<[f32; 4]>) {
+    let blur = shadow.blur as i32;
+    let left = (blur - shadow.dx).max(0) as usize;
+    let top = (blur - shadow.dy).max(0) as usize;
+    let right = (shadow.dx + blur).max(0) as usize;
+    let bottom = (shadow.dy + blur).max(0) as usize;
+    let out_width = left + width + right;
+    let out_height = top + height + bottom;
+    let mut alpha = vec![0.0_f32; out_width * out_height];
+
+    // Origin of the unblurred shadow within the enlarged image.
+    let shadow_x = (left as i32 + shadow.dx) as usize;
+    let shadow_y = (top as i32 + shadow.dy) as usize;
+
+    for (row, pixel_row) in pixels.chunks_exact(width.max(1)).enumerate() {
+        let row_start = ((shadow_y + row) * out_width) + shadow_x;
+
+        for (dst, pixel) in alpha[row_start..(row_start + pixel_row.len())]
+            .iter_mut()
+            .zip(pixel_row.iter())
+        {
+            *dst = pixel[3];
+        }
+    }
+
+    box_blur(&mut alpha, out_width, out_height, shadow.blur as usize);
+
+    let color_alpha = shadow.color[3] as f32 / 255.0;
+    let mut out = alpha
+        .into_iter()
+        .map(|value| {
+            let src_alpha = value * color_alpha;
+
+            [
+                (shadow.color[0] as f32 / 255.0) * src_alpha,
+                (shadow.color[1] as f32 / 255.0) * src_alpha,
+                (shadow.color[2] as f32 / 255.0) * src_alpha,
+                src_alpha,
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    for (row, pixel_row) in pixels.chunks_exact(width.max(1)).enumerate() {
+        let row_start = ((top + row) * out_width) + left;
+
+        for (dst, src) in out[row_start..(row_start + pixel_row.len())]
+            .iter_mut()
+            .zip(pixel_row.iter())
+        {
+            for (dst, src_value) in dst.iter_mut().zip(src.iter()) {
+                *dst = src_value + (*dst * (1.0 - src[3]));
+            }
+        }
+    }
+
+    (out_width, out_height, top + baseline, out)
+}
+
+/// Blur `values` in place with a box of `2 * radius + 1` pixels. Values outside are zero.
+fn box_blur(values: &mut [f32], width: usize, height: usize, radius: usize) {
+    if radius == 0 || width == 0 || height == 0 {
+        return;
+    }
+
+    let scale = 1.0 / (2 * radius + 1) as f32;
+    let mut line = Vec::with_capacity(width.max(height));
+
+    // Blur the `len` values starting at `start` that are `step` apart.
+    let mut blur_line = |values: &mut [f32], start: usize, len: usize, step: usize| {
+        line.clear();
+        line.extend((0..len).map(|i| values[start + (i * step)]));
+        let mut sum = line[..radius.min(len)].iter().sum::<f32>();
+
+        for i in 0..len {
+            if i + radius < len {
+                sum += line[i + radius];
+            }
+
+            values[start + (i * step)] = sum * scale;
+
+            if i >= radius {
+                sum -= line[i - radius];
+            }
+        }
+    };
+
+    for y in 0..height {
+        blur_line(values, y * width, width, 1);
+    }
+
+    for x in 0..width {
+        blur_line(values, x, height, width);
+    }
+}
+
+/// Add the horizontal coverage of the span `x1..x2` to each pixel of the row.
+fn accumulate_span(coverage: &mut [f32], x1: f32, x2: f32) {
+    let x1 = x1.max(0.0);
+    let x2 = x2.min(coverage.len() as f32);
+
+    if x2 <= x1 {
+        return;
+    }
+
+    let first = x1 as usize;
+    let last = x2 as usize;
+
+    if first == last {
+        coverage[first] += x2 - x1;
+        return;
+    }
+
+    coverage[first] += (first + 1) as f32 - x1;
+
+    for value in coverage[(first + 1)..last].iter_mut() {
+        *value += 1.0;
+    }
+
+    if last < coverage.len() {
+        coverage[last] += x2 - last as f32;
+    }
+}
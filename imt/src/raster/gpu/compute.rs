@@ -1,22 +1,29 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use vulkano::buffer::subbuffer::Subbuffer;
 use vulkano::buffer::{Buffer, BufferCreateInfo, BufferUsage};
+use vulkano::command_buffer::allocator::CommandBufferAllocator;
 use vulkano::command_buffer::{
-    AutoCommandBufferBuilder, CommandBufferExecFuture, CommandBufferUsage, CopyBufferInfo,
-    PrimaryCommandBufferAbstract,
+    AutoCommandBufferBuilder, BufferImageCopy, CommandBufferExecFuture, CommandBufferUsage,
+    CopyBufferInfo, CopyImageToBufferInfo, PrimaryAutoCommandBuffer, PrimaryCommandBufferAbstract,
 };
 use vulkano::descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet};
 use vulkano::format::Format;
-use vulkano::image::{ImageCreateFlags, ImageDimensions, ImageUsage, StorageImage};
+use vulkano::image::{
+    ImageAspects, ImageCreateFlags, ImageDimensions, ImageSubresourceLayers, ImageUsage,
+    StorageImage,
+};
 use vulkano::memory::allocator::{AllocationCreateInfo, MemoryUsage};
 use vulkano::pipeline::{Pipeline, PipelineBindPoint};
-use vulkano::sync::GpuFuture;
+use vulkano::query::{QueryPool, QueryPoolCreateInfo, QueryResultFlags, QueryType};
+use vulkano::sync::{self, GpuFuture, PipelineStage};
 
-use crate::parse::OutlineGeometry;
 use crate::raster::gpu::image_view::ImtImageView;
-use crate::raster::gpu::shaders::nonzero_cs;
+use crate::raster::gpu::pool::GpuGlyphBitmap;
+use crate::raster::gpu::shaders::{downscale_rgba_cs, hinting_cs, nonzero_cs};
 use crate::raster::gpu::GpuRasterizer;
+use crate::raster::rle::RleMask;
 use crate::raster::ScaledGlyph;
 
 #[derive(Debug, Clone)]
@@ -26,50 +33,203 @@ pub struct GpuRasteredGlyph {
     pub bearing_x: i16,
     pub bearing_y: i16,
     pub advance_w: i16,
-    pub bitmap: Arc<ImtImageView>,
+    /// `R8G8B8A8_UNORM` image with the coverage of each subpixel in the color channels.
+    ///
+    /// Coverage is linear unless `GpuRasterizerOptions::srgb` is enabled. The image is owned by
+    /// the rasterizer when `GpuRasterizerOptions::resident_images` is enabled.
+    ///
+    /// When rasterized with `GpuRasterizer::process_layers` this is a view of a single layer of
+    /// the provided image, of which the glyph only occupies the top-left `width` by `height`.
+    pub bitmap: GpuGlyphBitmap,
     pub unique_id: u64,
+    /// Only present when `GpuRasterizerOptions::debug_images` is enabled.
+    pub debug_images: Option<GpuRasterDebugImages>,
+    /// Only present when `GpuRasterizerOptions::profiling` is enabled.
+    pub stats: Option<GpuRasterStats>,
+}
+
+/// Statistics of rasterizing a glyph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuRasterStats {
+    /// Amount of line segments the outline was flattened into.
+    pub segments: usize,
+    /// Dimensions of the oversampled coverage image.
+    pub nonzero_extent: [u32; 2],
+    /// Time the device spent executing the dispatches of this glyph.
+    ///
+    /// `None` if the queue does not support timestamp queries.
+    pub dispatch_time: Option<Duration>,
+}
+
+/// The intermediate images produced while rasterizing a glyph.
+#[derive(Debug, Clone)]
+pub struct GpuRasterDebugImages {
+    /// Coverage oversampled 12x horizontally and 4x vertically. (`R8_UNORM`)
+    pub nonzero: Arc<ImtImageView>,
+    /// Coverage downscaled to 3x horizontally, one value per subpixel. (`R8_UNORM`)
+    ///
+    /// `None` when `GpuRasterizerOptions::skip_hinting` is enabled as the downscaled values are
+    /// written to the bitmap directly.
+    pub downscale: Option<Arc<ImtImageView>>,
+}
+
+impl GpuRasteredGlyph {
+    /// Fetch the image of `bitmap`.
+    ///
+    /// Returns `None` if the bitmap is resident and has been released.
+    pub fn image_view(&self, rasterizer: &GpuRasterizer) -> Option<Arc<ImtImageView>> {
+        match &self.bitmap {
+            GpuGlyphBitmap::Image(image) => Some(image.clone()),
+            GpuGlyphBitmap::Resident(handle) => rasterizer.resident_image(*handle),
+        }
+    }
+
+    /// Read `bitmap` back from the device as row-major pixels.
+    ///
+    /// # Notes
+    /// - This waits for the copy to complete.
+    ///
+    /// # Panics
+    /// - If the bitmap is resident and has been released.
+    pub fn read_bitmap(&self, rasterizer: &GpuRasterizer) -> Vec<[u8; 4]> {
+        let buffer: Subbuffer<[u8]> = Buffer::new_slice(
+            &rasterizer.mem_alloc,
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                usage: MemoryUsage::Download,
+                ..Default::default()
+            },
+            (self.width * self.height * 4) as _,
+        )
+        .unwrap();
+
+        let mut cmd_buf = AutoCommandBufferBuilder::primary(
+            &rasterizer.cmd_alloc,
+            rasterizer.queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+
+        let image_view = self
+            .image_view(rasterizer)
+            .expect("resident bitmap has been released");
+
+        cmd_buf
+            .copy_image_to_buffer(CopyImageToBufferInfo {
+                regions: [BufferImageCopy {
+                    image_subresource: ImageSubresourceLayers {
+                        aspects: ImageAspects::COLOR,
+                        mip_level: 0,
+                        array_layers: image_view
+                            .image_view_ref()
+                            .subresource_range()
+                            .array_layers
+                            .clone(),
+                    },
+                    image_extent: [self.width, self.height, 1],
+                    ..Default::default()
+                }]
+                .into(),
+                ..CopyImageToBufferInfo::image_buffer(
+                    image_view.image_view_ref().image().clone(),
+                    buffer.clone(),
+                )
+            })
+            .unwrap();
+
+        cmd_buf
+            .build()
+            .unwrap()
+            .execute(rasterizer.queue.clone())
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        buffer
+            .read()
+            .unwrap()
+            .chunks_exact(4)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+            .collect()
+    }
+
+    /// Read `bitmap` back from the device and run-length encode it.
+    pub fn to_rle(&self, rasterizer: &GpuRasterizer) -> RleMask {
+        RleMask::encode(self.width, self.height, &self.read_bitmap(rasterizer)).unwrap()
+    }
 }
 
 pub(super) fn raster(
     glyph: &ScaledGlyph,
     rasterizer: &GpuRasterizer,
+    target: Option<Arc<ImtImageView>>,
     previous: Option<Box<dyn GpuFuture + Send + Sync>>,
+    antialias: bool,
 ) -> (
     GpuRasteredGlyph,
     CommandBufferExecFuture<Box<dyn GpuFuture + Send + Sync>>,
+    Option<Arc<QueryPool>>,
 ) {
-    let outline = glyph.outline.as_ref().unwrap();
-    let mut segment_data: Vec<[f32; 4]> = Vec::new();
-
-    for geometry in outline.geometry.iter() {
-        if let OutlineGeometry::Segment {
-            p1,
-            p2,
-        } = geometry
-        {
-            segment_data.push([p1.x, p1.y, p2.x, p2.y]);
-        } else {
-            for i in 0..8 {
-                let p1 = geometry.evaluate(i as f32 / 8.0);
-                let p2 = geometry.evaluate((i + 1) as f32 / 8.0);
-                segment_data.push([p1.x, p1.y, p2.x, p2.y]);
-            }
-        }
-    }
+    let mut cmd_buf = AutoCommandBufferBuilder::primary(
+        &rasterizer.cmd_alloc,
+        rasterizer.queue.queue_family_index(),
+        CommandBufferUsage::OneTimeSubmit,
+    )
+    .unwrap();
+
+    let (rastered, query_pool) = record(
+        glyph,
+        rasterizer,
+        &mut cmd_buf,
+        target,
+        rasterizer.options.profiling,
+        antialias,
+    );
+
+    let previous = match previous {
+        Some(future) => {
+            future
+                .then_signal_semaphore_and_flush()
+                .unwrap()
+                .boxed_send_sync()
+        },
+        None => sync::now(rasterizer.queue.device().clone()).boxed_send_sync(),
+    };
+
+    let future = previous
+        .then_execute(rasterizer.queue.clone(), cmd_buf.build().unwrap())
+        .unwrap();
+
+    (rastered, future, query_pool)
+}
+
+/// Record the commands to rasterize `glyph` into `cmd_buf`.
+///
+/// The bitmap is written to `target` when provided, otherwise an image is allocated or taken from
+/// the pool. Timestamps are only written when `timestamps` is enabled and supported by the queue.
+/// When `antialias` is disabled coverage is thresholded by the final stage.
+pub(super) fn record<A: CommandBufferAllocator>(
+    glyph: &ScaledGlyph,
+    rasterizer: &GpuRasterizer,
+    cmd_buf: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer<A::Alloc>, A>,
+    target: Option<Arc<ImtImageView>>,
+    timestamps: bool,
+    antialias: bool,
+) -> (GpuRasteredGlyph, Option<Arc<QueryPool>>) {
+    let segment_data = rasterizer.segment_data(glyph);
 
     let nonzero_info = nonzero_cs::Info {
         extent: [glyph.width as f32 * 12.0, glyph.height as f32 * 4.0],
         numSegments: segment_data.len() as _,
         numRays: 2,
+        jitter: rasterizer.options.ray_jitter as u32,
     };
 
-    let mut tx_cmd_b = AutoCommandBufferBuilder::primary(
-        &rasterizer.cmd_alloc,
-        rasterizer.queue.queue_family_index(),
-        CommandBufferUsage::OneTimeSubmit,
-    )
-    .unwrap();
-
     let segment_data_len = segment_data.len();
 
     let nonzero_segdata_cpu = Buffer::from_iter(
@@ -82,7 +242,7 @@ pub(super) fn raster(
             usage: MemoryUsage::Upload,
             ..Default::default()
         },
-        segment_data,
+        segment_data.iter().copied(),
     )
     .unwrap();
 
@@ -100,36 +260,15 @@ pub(super) fn raster(
     )
     .unwrap();
 
-    tx_cmd_b
+    cmd_buf
         .copy_buffer(CopyBufferInfo::buffers(
             nonzero_segdata_cpu,
             nonzero_segdata.clone(),
         ))
         .unwrap();
 
-    let tx_cmd = match previous {
-        Some(future) => {
-            future
-                .then_signal_semaphore_and_flush()
-                .unwrap()
-                .then_execute_same_queue(tx_cmd_b.build().unwrap())
-                .unwrap()
-                .then_signal_semaphore_and_flush()
-                .unwrap()
-                .boxed_send_sync()
-        },
-        None => {
-            tx_cmd_b
-                .build()
-                .unwrap()
-                .execute(rasterizer.queue.clone())
-                .unwrap()
-                .then_signal_semaphore_and_flush()
-                .unwrap()
-                .boxed_send_sync()
-        },
-    };
-
+    // Intermediate images must remain UNORM: coverage is linear and an sRGB format would
+    // apply a transfer function on every load and store.
     let nonzero_image = ImtImageView::from_storage(
         StorageImage::with_usage(
             &rasterizer.mem_alloc,
@@ -147,39 +286,41 @@ pub(super) fn raster(
     )
     .unwrap();
 
-    let downscale_image = ImtImageView::from_storage(
-        StorageImage::with_usage(
-            &rasterizer.mem_alloc,
-            ImageDimensions::Dim2d {
-                width: glyph.width * 3,
-                height: glyph.height * 1,
-                array_layers: 1,
-            },
-            Format::R8_UNORM,
-            ImageUsage::STORAGE,
-            ImageCreateFlags::empty(),
-            [rasterizer.queue.queue_family_index()],
-        )
-        .unwrap(),
-    )
-    .unwrap();
+    let resident = target.is_none() && rasterizer.options.resident_images;
 
-    let hinting_image = ImtImageView::from_storage(
-        StorageImage::with_usage(
-            &rasterizer.mem_alloc,
-            ImageDimensions::Dim2d {
-                width: glyph.width,
-                height: glyph.height,
-                array_layers: 1,
-            },
-            Format::R8G8B8A8_UNORM,
-            ImageUsage::STORAGE | ImageUsage::SAMPLED,
-            ImageCreateFlags::empty(),
-            [rasterizer.queue.queue_family_index()],
-        )
-        .unwrap(),
-    )
-    .unwrap();
+    let existing_image = if target.is_some() {
+        target
+    } else if resident {
+        rasterizer
+            .image_pool
+            .lock()
+            .unwrap()
+            .take_image([glyph.width, glyph.height])
+    } else {
+        None
+    };
+
+    let bitmap_image = match existing_image {
+        Some(image) => image,
+        None => {
+            ImtImageView::from_storage(
+                StorageImage::with_usage(
+                    &rasterizer.mem_alloc,
+                    ImageDimensions::Dim2d {
+                        width: glyph.width,
+                        height: glyph.height,
+                        array_layers: 1,
+                    },
+                    Format::R8G8B8A8_UNORM,
+                    ImageUsage::STORAGE | ImageUsage::SAMPLED | ImageUsage::TRANSFER_SRC,
+                    ImageCreateFlags::empty(),
+                    [rasterizer.queue.queue_family_index()],
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        },
+    };
 
     let nonzero_desc_set = PersistentDescriptorSet::new(
         &rasterizer.set_alloc,
@@ -198,44 +339,37 @@ pub(super) fn raster(
     )
     .unwrap();
 
-    let downscale_desc_set = PersistentDescriptorSet::new(
-        &rasterizer.set_alloc,
-        rasterizer
-            .downscale_pipeline
-            .layout()
-            .set_layouts()
-            .get(0)
-            .unwrap()
-            .clone(),
-        [
-            WriteDescriptorSet::image_view(0, nonzero_image.clone()),
-            WriteDescriptorSet::image_view(1, downscale_image.clone()),
-        ],
-    )
-    .unwrap();
+    // Timestamps are only supported if the queue family has valid bits.
+    let query_pool = if timestamps
+        && rasterizer
+            .queue
+            .device()
+            .physical_device()
+            .queue_family_properties()[rasterizer.queue.queue_family_index() as usize]
+            .timestamp_valid_bits
+            .is_some()
+    {
+        let query_pool = QueryPool::new(
+            rasterizer.queue.device().clone(),
+            QueryPoolCreateInfo {
+                query_count: 2,
+                ..QueryPoolCreateInfo::query_type(QueryType::Timestamp)
+            },
+        )
+        .unwrap();
 
-    let hinting_desc_set = PersistentDescriptorSet::new(
-        &rasterizer.set_alloc,
-        rasterizer
-            .hinting_pipeline
-            .layout()
-            .set_layouts()
-            .get(0)
-            .unwrap()
-            .clone(),
-        [
-            WriteDescriptorSet::image_view(0, downscale_image.clone()),
-            WriteDescriptorSet::image_view(1, hinting_image.clone()),
-        ],
-    )
-    .unwrap();
+        unsafe {
+            cmd_buf
+                .reset_query_pool(query_pool.clone(), 0..2)
+                .unwrap()
+                .write_timestamp(query_pool.clone(), 0, PipelineStage::TopOfPipe)
+                .unwrap();
+        }
 
-    let mut cmd_buf = AutoCommandBufferBuilder::primary(
-        &rasterizer.cmd_alloc,
-        rasterizer.queue.queue_family_index(),
-        CommandBufferUsage::OneTimeSubmit,
-    )
-    .unwrap();
+        Some(query_pool)
+    } else {
+        None
+    };
 
     cmd_buf
         .bind_pipeline_compute(rasterizer.nonzero_pipeline.clone())
@@ -251,28 +385,156 @@ pub(super) fn raster(
             nonzero_info,
         )
         .dispatch([glyph.width * 12, glyph.height * 4, 1])
-        .unwrap()
-        .bind_pipeline_compute(rasterizer.downscale_pipeline.clone())
-        .bind_descriptor_sets(
-            PipelineBindPoint::Compute,
-            rasterizer.downscale_pipeline.layout().clone(),
-            0,
-            downscale_desc_set,
+        .unwrap();
+
+    let downscale_image = if rasterizer.options.skip_hinting {
+        let downscale_rgba_desc_set = PersistentDescriptorSet::new(
+            &rasterizer.set_alloc,
+            rasterizer
+                .downscale_rgba_pipeline
+                .layout()
+                .set_layouts()
+                .get(0)
+                .unwrap()
+                .clone(),
+            [
+                WriteDescriptorSet::image_view(0, nonzero_image.clone()),
+                WriteDescriptorSet::image_view(1, bitmap_image.clone()),
+            ],
         )
-        .dispatch([glyph.width * 3, glyph.height, 1])
-        .unwrap()
-        .bind_pipeline_compute(rasterizer.hinting_pipeline.clone())
-        .bind_descriptor_sets(
-            PipelineBindPoint::Compute,
-            rasterizer.hinting_pipeline.layout().clone(),
-            0,
-            hinting_desc_set,
+        .unwrap();
+
+        cmd_buf
+            .bind_pipeline_compute(rasterizer.downscale_rgba_pipeline.clone())
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                rasterizer.downscale_rgba_pipeline.layout().clone(),
+                0,
+                downscale_rgba_desc_set,
+            )
+            .push_constants(
+                rasterizer.downscale_rgba_pipeline.layout().clone(),
+                0,
+                downscale_rgba_cs::Options {
+                    antialias: antialias as u32,
+                },
+            )
+            .dispatch([glyph.width, glyph.height, 1])
+            .unwrap();
+
+        None
+    } else {
+        let downscale_image = ImtImageView::from_storage(
+            StorageImage::with_usage(
+                &rasterizer.mem_alloc,
+                ImageDimensions::Dim2d {
+                    width: glyph.width * 3,
+                    height: glyph.height * 1,
+                    array_layers: 1,
+                },
+                Format::R8_UNORM,
+                ImageUsage::STORAGE,
+                ImageCreateFlags::empty(),
+                [rasterizer.queue.queue_family_index()],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let downscale_desc_set = PersistentDescriptorSet::new(
+            &rasterizer.set_alloc,
+            rasterizer
+                .downscale_pipeline
+                .layout()
+                .set_layouts()
+                .get(0)
+                .unwrap()
+                .clone(),
+            [
+                WriteDescriptorSet::image_view(0, nonzero_image.clone()),
+                WriteDescriptorSet::image_view(1, downscale_image.clone()),
+            ],
         )
-        .dispatch([glyph.width, glyph.height, 1])
         .unwrap();
 
-    let exec_cmd = cmd_buf.build().unwrap();
-    let future = tx_cmd.then_execute_same_queue(exec_cmd).unwrap();
+        let hinting_desc_set = PersistentDescriptorSet::new(
+            &rasterizer.set_alloc,
+            rasterizer
+                .hinting_pipeline
+                .layout()
+                .set_layouts()
+                .get(0)
+                .unwrap()
+                .clone(),
+            [
+                WriteDescriptorSet::image_view(0, downscale_image.clone()),
+                WriteDescriptorSet::image_view(1, bitmap_image.clone()),
+            ],
+        )
+        .unwrap();
+
+        cmd_buf
+            .bind_pipeline_compute(rasterizer.downscale_pipeline.clone())
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                rasterizer.downscale_pipeline.layout().clone(),
+                0,
+                downscale_desc_set,
+            )
+            .dispatch([glyph.width * 3, glyph.height, 1])
+            .unwrap()
+            .bind_pipeline_compute(rasterizer.hinting_pipeline.clone())
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                rasterizer.hinting_pipeline.layout().clone(),
+                0,
+                hinting_desc_set,
+            )
+            .push_constants(
+                rasterizer.hinting_pipeline.layout().clone(),
+                0,
+                hinting_cs::Options {
+                    antialias: antialias as u32,
+                },
+            )
+            .dispatch([glyph.width, glyph.height, 1])
+            .unwrap();
+
+        Some(downscale_image)
+    };
+
+    if let Some(query_pool) = query_pool.as_ref() {
+        unsafe {
+            cmd_buf
+                .write_timestamp(query_pool.clone(), 1, PipelineStage::BottomOfPipe)
+                .unwrap();
+        }
+    }
+
+    let debug_images = if rasterizer.options.debug_images {
+        Some(GpuRasterDebugImages {
+            nonzero: nonzero_image,
+            downscale: downscale_image,
+        })
+    } else {
+        None
+    };
+
+    let bitmap = if resident {
+        GpuGlyphBitmap::Resident(rasterizer.image_pool.lock().unwrap().insert(bitmap_image))
+    } else {
+        GpuGlyphBitmap::Image(bitmap_image)
+    };
+
+    let stats = if rasterizer.options.profiling {
+        Some(GpuRasterStats {
+            segments: segment_data_len,
+            nonzero_extent: [glyph.width * 12, glyph.height * 4],
+            dispatch_time: None,
+        })
+    } else {
+        None
+    };
 
     (
         GpuRasteredGlyph {
@@ -281,9 +543,38 @@ pub(super) fn raster(
             bearing_x: glyph.bearing_x,
             bearing_y: glyph.bearing_y,
             advance_w: glyph.advance_w,
-            bitmap: hinting_image,
+            bitmap,
             unique_id: glyph.unique_id,
+            debug_images,
+            stats,
         },
-        future,
+        query_pool,
     )
 }
+
+/// Read the timestamps written by `raster` once its commands have completed.
+pub(super) fn dispatch_time(
+    rasterizer: &GpuRasterizer,
+    query_pool: &Arc<QueryPool>,
+) -> Option<Duration> {
+    let mut timestamps = [0_u64; 2];
+
+    if !query_pool
+        .queries_range(0..2)?
+        .get_results(&mut timestamps, QueryResultFlags::WAIT)
+        .ok()?
+    {
+        return None;
+    }
+
+    let period = rasterizer
+        .queue
+        .device()
+        .physical_device()
+        .properties()
+        .timestamp_period as f64;
+
+    Some(Duration::from_nanos(
+        (timestamps[1].saturating_sub(timestamps[0]) as f64 * period) as u64,
+    ))
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use vulkano::device::{Device, DeviceOwned};
 use vulkano::format::{Format, FormatFeatures};
-use vulkano::image::view::{ImageView, ImageViewCreationError, ImageViewType};
+use vulkano::image::view::{ImageView, ImageViewCreateInfo, ImageViewCreationError, ImageViewType};
 use vulkano::image::{
     AttachmentImage, ImageAccess, ImageDescriptorLayouts, ImageDimensions, ImageInner, ImageLayout,
     ImageSubresourceRange, ImageUsage, ImageViewAbstract, StorageImage,
@@ -30,6 +30,21 @@ impl ImtImageView {
         }))
     }
 
+    /// Create a `ImtImageView` of a single array layer of a vulkano `StorageImage`.
+    pub fn from_storage_layer(
+        image: Arc<StorageImage>,
+        layer: u32,
+    ) -> Result<Arc<Self>, ImageViewCreationError> {
+        let image = Arc::new(ImtImageVarient::Storage(image));
+        let mut create_info = ImageViewCreateInfo::from_image(image.as_ref());
+        create_info.view_type = ImageViewType::Dim2d;
+        create_info.subresource_range.array_layers = layer..(layer + 1);
+
+        Ok(Arc::new(Self {
+            view: ImageView::new(image, create_info)?,
+        }))
+    }
+
     /// Create a `ImtImageView` from a vulkano `AttachmentImage`.
     pub fn from_attachment(
         image: Arc<AttachmentImage>,
@@ -1,50 +1,157 @@
 pub mod compute;
 pub mod image_view;
+pub mod pool;
 pub mod shaders;
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use vulkano::buffer::subbuffer::Subbuffer;
 use vulkano::buffer::{Buffer, BufferCreateInfo, BufferUsage};
-use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
+use vulkano::command_buffer::allocator::{CommandBufferAllocator, StandardCommandBufferAllocator};
 use vulkano::command_buffer::{
     AutoCommandBufferBuilder, CommandBufferExecFuture, CommandBufferUsage, CopyBufferInfo,
-    PrimaryCommandBufferAbstract,
+    PrimaryAutoCommandBuffer, PrimaryCommandBufferAbstract,
 };
 use vulkano::descriptor_set::allocator::StandardDescriptorSetAllocator;
 use vulkano::device::Queue;
+use vulkano::format::Format;
+use vulkano::image::{ImageAccess, ImageDimensions, ImageUsage, StorageImage};
 use vulkano::memory::allocator::{AllocationCreateInfo, MemoryUsage, StandardMemoryAllocator};
 use vulkano::pipeline::ComputePipeline;
 use vulkano::shader::ShaderModule;
 use vulkano::sync::GpuFuture;
 
-use crate::raster::gpu::compute::{raster, GpuRasteredGlyph};
+use crate::raster::gpu::compute::{dispatch_time, raster, record, GpuRasteredGlyph};
+use crate::raster::gpu::image_view::ImtImageView;
+use crate::raster::gpu::pool::{
+    GlyphHandle, ImagePool, SegmentCache, DEFAULT_RELEASED_IMAGE_CAPACITY,
+};
 use crate::raster::gpu::shaders::*;
-use crate::raster::ScaledGlyph;
+use crate::raster::{ScaledGlyph, DEFAULT_CURVE_TOLERANCE};
+
+/// Options used to create a `GpuRasterizer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuRasterizerOptions {
+    /// Retain the intermediate images of the pipeline in `GpuRasteredGlyph::debug_images`.
+    ///
+    /// This is intended for debugging rasterization artifacts and keeps the oversampled images
+    /// alive for as long as the glyph, so it should be left disabled otherwise.
+    pub debug_images: bool,
+    /// Skip the hinting stage and write the downscaled subpixels directly to the bitmap.
+    ///
+    /// This saves a compute pass per glyph. At large sizes hinting has little benefit and may
+    /// over-sharpen, so skipping it is often preferable. The bitmap is still `R8G8B8A8_UNORM`
+    /// with one subpixel per color channel.
+    pub skip_hinting: bool,
+    /// Record statistics of each glyph in `GpuRasteredGlyph::stats`.
+    ///
+    /// This uses timestamp queries to measure the time spent in the compute dispatches, which
+    /// adds a small amount of overhead, so it should be left disabled otherwise.
+    pub profiling: bool,
+    /// Maximum distance in pixels between a curve and the segments approximating it.
+    ///
+    /// Curves are subdivided adaptively to meet this. Lower values produce smoother curves at
+    /// the cost of more segments to process, see `ScaledGlyph::segment_data`. Defaults to
+    /// `DEFAULT_CURVE_TOLERANCE`.
+    pub curve_tolerance: f32,
+    /// Encode the bitmap with the sRGB transfer function.
+    ///
+    /// By default the bitmap contains linear coverage. Compositors that blend in sRGB space,
+    /// e.g. when sampling the bitmap as if it were `R8G8B8A8_SRGB`, expect encoded values. The
+    /// format of the bitmap remains `R8G8B8A8_UNORM` either way, as sRGB formats generally do not
+    /// support storage usage.
+    pub srgb: bool,
+    /// Keep bitmaps resident in the rasterizer and return `GpuGlyphBitmap::Resident` handles.
+    ///
+    /// Released bitmaps are reused for later glyphs of the same dimensions, which avoids an
+    /// allocation per glyph when rendering many glyphs. Bitmaps stay alive until released with
+    /// `GpuRasterizer::release`.
+    pub resident_images: bool,
+    /// Maximum amount of released bitmaps kept for reuse, see `resident_images`.
+    ///
+    /// Bitmaps released while this many are kept are freed instead. Defaults to
+    /// `DEFAULT_RELEASED_IMAGE_CAPACITY`.
+    pub released_image_capacity: usize,
+    /// Offset the origin of the rays cast for each sample within its pixel.
+    ///
+    /// Samples are otherwise taken on a regular grid, which can produce moiré on glyphs with
+    /// regular patterns, e.g. grids or hatching. The offset is derived from a hash of the sample
+    /// coordinates, so results remain deterministic. This adds a small amount of work per sample.
+    pub ray_jitter: bool,
+    /// Amount of glyphs to keep the flattened segment data of, keyed on `ScaledGlyph::unique_id`.
+    ///
+    /// Rasterizing a glyph again, e.g. after its bitmap was released, then skips flattening its
+    /// outline. This is useful when bitmaps are frequently evicted while the glyphs remain the
+    /// same. Once full the least recently used glyph is evicted. Defaults to `0`, which disables
+    /// the cache.
+    ///
+    /// # Notes
+    /// - `unique_id` includes `Font::id`, so glyphs of different fonts do not collide.
+    /// - Glyphs that are not created with `ScaledGlyph::evaluate`, or whose outline is modified
+    ///   afterwards, must be given a `unique_id` of their own.
+    pub segment_cache_capacity: usize,
+}
+
+impl Default for GpuRasterizerOptions {
+    fn default() -> Self {
+        Self {
+            debug_images: false,
+            skip_hinting: false,
+            profiling: false,
+            curve_tolerance: DEFAULT_CURVE_TOLERANCE,
+            srgb: false,
+            resident_images: false,
+            released_image_capacity: DEFAULT_RELEASED_IMAGE_CAPACITY,
+            ray_jitter: false,
+            segment_cache_capacity: 0,
+        }
+    }
+}
+
+/// Errors from `GpuRasterizer::process_layers`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuRasterErr {
+    /// The image is not a 2D `R8G8B8A8_UNORM` image with storage usage.
+    InvalidImage,
+    /// The image does not have enough array layers for the glyphs.
+    LayerOutOfRange,
+    /// A glyph is larger than the extent of the image.
+    GlyphTooLarge,
+}
 
 #[allow(dead_code)]
 pub struct GpuRasterizer {
+    options: GpuRasterizerOptions,
     queue: Arc<Queue>,
     mem_alloc: StandardMemoryAllocator,
     cmd_alloc: StandardCommandBufferAllocator,
     set_alloc: StandardDescriptorSetAllocator,
     nonzero_cs: Arc<ShaderModule>,
     downscale_cs: Arc<ShaderModule>,
+    downscale_rgba_cs: Arc<ShaderModule>,
     hinting_cs: Arc<ShaderModule>,
     nonzero_pipeline: Arc<ComputePipeline>,
     downscale_pipeline: Arc<ComputePipeline>,
+    downscale_rgba_pipeline: Arc<ComputePipeline>,
     hinting_pipeline: Arc<ComputePipeline>,
     nonzero_raydata: Subbuffer<[[f32; 2]]>,
+    image_pool: Mutex<ImagePool>,
+    segment_cache: Mutex<SegmentCache>,
 }
 
 impl GpuRasterizer {
     pub fn new(queue: Arc<Queue>) -> Self {
+        Self::with_options(queue, GpuRasterizerOptions::default())
+    }
+
+    pub fn with_options(queue: Arc<Queue>, options: GpuRasterizerOptions) -> Self {
         let mem_alloc = StandardMemoryAllocator::new_default(queue.device().clone());
         let cmd_alloc =
             StandardCommandBufferAllocator::new(queue.device().clone(), Default::default());
         let set_alloc = StandardDescriptorSetAllocator::new(queue.device().clone());
         let nonzero_cs = nonzero_cs::load(queue.device().clone()).unwrap();
         let downscale_cs = downscale_cs::load(queue.device().clone()).unwrap();
+        let downscale_rgba_cs = downscale_rgba_cs::load(queue.device().clone()).unwrap();
         let hinting_cs = hinting_cs::load(queue.device().clone()).unwrap();
 
         // TODO: Set local size here
@@ -67,11 +174,25 @@ impl GpuRasterizer {
         )
         .unwrap();
 
+        // TODO: Set local size here
+        let downscale_rgba_pipeline = ComputePipeline::new(
+            queue.device().clone(),
+            downscale_rgba_cs.entry_point("main").unwrap(),
+            &downscale_rgba_cs::SpecializationConstants {
+                OUTPUT_SRGB: options.srgb as i32,
+            },
+            None,
+            |_| {},
+        )
+        .unwrap();
+
         // TODO: Set local size here
         let hinting_pipeline = ComputePipeline::new(
             queue.device().clone(),
             hinting_cs.entry_point("main").unwrap(),
-            &(),
+            &hinting_cs::SpecializationConstants {
+                OUTPUT_SRGB: options.srgb as i32,
+            },
             None,
             |_| {},
         )
@@ -141,37 +262,193 @@ impl GpuRasterizer {
             .wait(None)
             .unwrap();
 
+        let image_pool = ImagePool::new(options.released_image_capacity);
+        let segment_cache = SegmentCache::new(options.segment_cache_capacity);
+
         Self {
+            options,
             queue,
             mem_alloc,
             cmd_alloc,
             set_alloc,
             nonzero_cs,
             downscale_cs,
+            downscale_rgba_cs,
             hinting_cs,
             nonzero_pipeline,
             downscale_pipeline,
+            downscale_rgba_pipeline,
             hinting_pipeline,
             nonzero_raydata,
+            image_pool: Mutex::new(image_pool),
+            segment_cache: Mutex::new(segment_cache),
         }
     }
 
+    pub fn options(&self) -> &GpuRasterizerOptions {
+        &self.options
+    }
+
+    /// Fetch the image of a resident bitmap.
+    ///
+    /// Returns `None` if `handle` has been released.
+    pub fn resident_image(&self, handle: GlyphHandle) -> Option<Arc<ImtImageView>> {
+        self.image_pool.lock().unwrap().get(handle)
+    }
+
+    /// Release a resident bitmap, allowing its image to be reused.
+    ///
+    /// Returns `false` if `handle` has already been released, including by `clear_resident`.
+    ///
+    /// # Notes
+    /// - The image is not reused while other references to it, e.g. those returned by
+    ///   `resident_image`, are still alive.
+    pub fn release(&self, handle: GlyphHandle) -> bool {
+        self.image_pool.lock().unwrap().release(handle)
+    }
+
+    /// The amount of bitmaps currently resident.
+    pub fn resident_count(&self) -> usize {
+        self.image_pool.lock().unwrap().len()
+    }
+
+    /// Release all resident bitmaps and free the images kept for reuse.
+    pub fn clear_resident(&self) {
+        self.image_pool.lock().unwrap().clear();
+    }
+
+    /// The amount of glyphs with cached segment data.
+    ///
+    /// See `GpuRasterizerOptions::segment_cache_capacity`.
+    pub fn segment_cache_len(&self) -> usize {
+        self.segment_cache.lock().unwrap().len()
+    }
+
+    /// Clear the cached segment data of all glyphs.
+    pub fn clear_segment_cache(&self) {
+        self.segment_cache.lock().unwrap().clear();
+    }
+
+    /// The segment data of `glyph`, from the cache when available.
+    pub(super) fn segment_data(&self, glyph: &ScaledGlyph) -> Arc<Vec<[f32; 4]>> {
+        if self.options.segment_cache_capacity == 0 {
+            return Arc::new(glyph.segment_data(self.options.curve_tolerance));
+        }
+
+        if let Some(segment_data) = self.segment_cache.lock().unwrap().get(glyph.unique_id) {
+            return segment_data;
+        }
+
+        let segment_data = Arc::new(glyph.segment_data(self.options.curve_tolerance));
+
+        self.segment_cache
+            .lock()
+            .unwrap()
+            .insert(glyph.unique_id, segment_data.clone());
+
+        segment_data
+    }
+
     pub fn process(&self, glyphs: &[ScaledGlyph]) -> Vec<GpuRasteredGlyph> {
+        self.process_with_antialias(glyphs, true)
+    }
+
+    /// Same as `process`, but allows disabling antialiasing.
+    ///
+    /// When `antialias` is `false` the coverage of each subpixel is thresholded to either fully
+    /// covered or not covered at all. Axis-aligned edges, e.g. of box-drawing characters, then
+    /// tile without seams. Unlike `CpuRasterizer::render_mono` the bitmap remains
+    /// `R8G8B8A8_UNORM`.
+    pub fn process_with_antialias(
+        &self,
+        glyphs: &[ScaledGlyph],
+        antialias: bool,
+    ) -> Vec<GpuRasteredGlyph> {
+        self.process_targets(glyphs.iter().map(|glyph| (glyph, None)), antialias)
+    }
+
+    /// Rasterize each glyph into its own array layer of `image`, starting at `first_layer`.
+    ///
+    /// This allows many glyphs to share a single image that can be sampled in one draw by
+    /// indexing the layer. Each glyph occupies the top-left `width` by `height` of its layer,
+    /// the remainder of the layer may be overwritten.
+    ///
+    /// # Notes
+    /// - `image` must be a 2D `R8G8B8A8_UNORM` image with storage usage. For `read_bitmap` it
+    ///   also requires transfer source usage.
+    /// - `GpuRasterizerOptions::resident_images` does not apply to these glyphs.
+    pub fn process_layers(
+        &self,
+        glyphs: &[ScaledGlyph],
+        image: Arc<StorageImage>,
+        first_layer: u32,
+    ) -> Result<Vec<GpuRasteredGlyph>, GpuRasterErr> {
+        let (width, height, array_layers) = match image.dimensions() {
+            ImageDimensions::Dim2d {
+                width,
+                height,
+                array_layers,
+            } => (width, height, array_layers),
+            _ => return Err(GpuRasterErr::InvalidImage),
+        };
+
+        if image.format() != Format::R8G8B8A8_UNORM
+            || !image.usage().intersects(ImageUsage::STORAGE)
+        {
+            return Err(GpuRasterErr::InvalidImage);
+        }
+
+        let layer_end = u32::try_from(glyphs.len())
+            .ok()
+            .and_then(|count| count.checked_add(first_layer))
+            .ok_or(GpuRasterErr::LayerOutOfRange)?;
+
+        if layer_end > array_layers {
+            return Err(GpuRasterErr::LayerOutOfRange);
+        }
+
+        if glyphs
+            .iter()
+            .any(|glyph| glyph.width > width || glyph.height > height)
+        {
+            return Err(GpuRasterErr::GlyphTooLarge);
+        }
+
+        let mut targets = Vec::with_capacity(glyphs.len());
+
+        for (glyph, layer) in glyphs.iter().zip(first_layer..) {
+            targets.push((
+                glyph,
+                Some(ImtImageView::from_storage_layer(image.clone(), layer).unwrap()),
+            ));
+        }
+
+        Ok(self.process_targets(targets, true))
+    }
+
+    fn process_targets<'a, I>(&self, glyphs: I, antialias: bool) -> Vec<GpuRasteredGlyph>
+    where
+        I: IntoIterator<Item = (&'a ScaledGlyph, Option<Arc<ImtImageView>>)>,
+    {
         let mut previous = None;
-        let mut output = Vec::with_capacity(glyphs.len());
+        let mut output = Vec::new();
+        let mut query_pools = Vec::new();
 
-        for glyph in glyphs.iter() {
-            let (rastered, future) = raster(
-                &glyph,
+        for (glyph, target) in glyphs {
+            let (rastered, future, query_pool) = raster(
+                glyph,
                 self,
+                target,
                 previous.take().map(
                     |v: CommandBufferExecFuture<Box<dyn GpuFuture + Send + Sync>>| {
                         v.boxed_send_sync()
                     },
                 ),
+                antialias,
             );
 
             previous = Some(future);
+            query_pools.push(query_pool);
             output.push(rastered);
         }
 
@@ -183,6 +460,34 @@ impl GpuRasterizer {
                 .unwrap();
         }
 
+        for (rastered, query_pool) in output.iter_mut().zip(query_pools) {
+            if let (Some(stats), Some(query_pool)) = (rastered.stats.as_mut(), query_pool) {
+                stats.dispatch_time = dispatch_time(self, &query_pool);
+            }
+        }
+
         output
     }
+
+    /// Record the rasterization of `glyphs` into `builder` without submitting it.
+    ///
+    /// This allows the rasterization to be batched with other work, e.g. within the command
+    /// buffer of a frame. The bitmaps of the returned glyphs are only valid once `builder` has
+    /// been built, submitted and has finished executing.
+    ///
+    /// # Notes
+    /// - `builder` must be submitted to a queue of the same queue family as the queue this
+    ///   rasterizer was created with.
+    /// - When `profiling` is enabled `GpuRasteredGlyph::stats` is provided, but `dispatch_time`
+    ///   is always `None` as the results can not be read back.
+    pub fn record_into<A: CommandBufferAllocator>(
+        &self,
+        builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer<A::Alloc>, A>,
+        glyphs: &[ScaledGlyph],
+    ) -> Vec<GpuRasteredGlyph> {
+        glyphs
+            .iter()
+            .map(|glyph| record(glyph, self, builder, None, false, true).0)
+            .collect()
+    }
 }
@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::raster::gpu::image_view::ImtImageView;
+
+/// The default of `GpuRasterizerOptions::released_image_capacity`.
+pub const DEFAULT_RELEASED_IMAGE_CAPACITY: usize = 256;
+
+/// Handle of a bitmap kept resident in a `GpuRasterizer`.
+///
+/// Only returned when `GpuRasterizerOptions::resident_images` is enabled. Use
+/// `GpuRasterizer::resident_image` to access the image and `GpuRasterizer::release` once it is
+/// no longer needed. Once released a handle is never valid again, even when its slot is reused
+/// for another bitmap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GlyphHandle {
+    index: u32,
+    generation: u32,
+}
+
+/// The bitmap of a `GpuRasteredGlyph`.
+#[derive(Debug, Clone)]
+pub enum GpuGlyphBitmap {
+    /// The glyph owns its image.
+    Image(Arc<ImtImageView>),
+    /// The image is owned by the rasterizer.
+    Resident(GlyphHandle),
+}
+
+/// Values addressed by `GlyphHandle`.
+///
+/// The slots of removed values are reused with the next generation, so handles of removed
+/// values do not address the values that replace them.
+#[derive(Debug)]
+pub(crate) struct HandleSlots<T> {
+    /// The current generation of each slot along with its value.
+    slots: Vec<(u32, Option<T>)>,
+    free_slots: Vec<u32>,
+}
+
+impl<T> Default for HandleSlots<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+        }
+    }
+}
+
+impl<T> HandleSlots<T> {
+    pub(crate) fn insert(&mut self, value: T) -> GlyphHandle {
+        match self.free_slots.pop() {
+            Some(index) => {
+                let (generation, slot) = &mut self.slots[index as usize];
+                *slot = Some(value);
+
+                GlyphHandle {
+                    index,
+                    generation: *generation,
+                }
+            },
+            None => {
+                self.slots.push((0, Some(value)));
+
+                GlyphHandle {
+                    index: self.slots.len() as u32 - 1,
+                    generation: 0,
+                }
+            },
+        }
+    }
+
+    pub(crate) fn get(&self, handle: GlyphHandle) -> Option<&T> {
+        match self.slots.get(handle.index as usize)? {
+            (generation, Some(value)) if *generation == handle.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn remove(&mut self, handle: GlyphHandle) -> Option<T> {
+        let (generation, slot) = self.slots.get_mut(handle.index as usize)?;
+
+        if *generation != handle.generation {
+            return None;
+        }
+
+        let value = slot.take()?;
+        *generation = generation.wrapping_add(1);
+        self.free_slots.push(handle.index);
+        Some(value)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.slots.len() - self.free_slots.len()
+    }
+
+    /// Remove every value. Slots are kept so that existing handles remain invalid.
+    pub(crate) fn clear(&mut self) {
+        for (index, (generation, slot)) in self.slots.iter_mut().enumerate() {
+            if slot.take().is_some() {
+                *generation = generation.wrapping_add(1);
+                self.free_slots.push(index as u32);
+            }
+        }
+    }
+}
+
+/// Bitmaps owned by a `GpuRasterizer` along with released images available for reuse.
+#[derive(Debug)]
+pub(super) struct ImagePool {
+    images: HandleSlots<Arc<ImtImageView>>,
+    free_images: BTreeMap<[u32; 2], Vec<Arc<ImtImageView>>>,
+    free_image_count: usize,
+    free_image_capacity: usize,
+}
+
+impl ImagePool {
+    pub(super) fn new(free_image_capacity: usize) -> Self {
+        Self {
+            images: HandleSlots::default(),
+            free_images: BTreeMap::new(),
+            free_image_count: 0,
+            free_image_capacity,
+        }
+    }
+
+    /// Take a released image of the provided extent that is no longer referenced elsewhere.
+    ///
+    /// Images still referenced, e.g. by a pending command buffer, are left in the pool.
+    pub(super) fn take_image(&mut self, extent: [u32; 2]) -> Option<Arc<ImtImageView>> {
+        let images = self.free_images.get_mut(&extent)?;
+        let index = images
+            .iter()
+            .position(|image| Arc::strong_count(image) == 1)?;
+        self.free_image_count -= 1;
+        Some(images.swap_remove(index))
+    }
+
+    pub(super) fn insert(&mut self, image: Arc<ImtImageView>) -> GlyphHandle {
+        self.images.insert(image)
+    }
+
+    pub(super) fn get(&self, handle: GlyphHandle) -> Option<Arc<ImtImageView>> {
+        self.images.get(handle).cloned()
+    }
+
+    /// Release a bitmap, keeping its image for reuse unless `free_image_capacity` is reached.
+    pub(super) fn release(&mut self, handle: GlyphHandle) -> bool {
+        let image = match self.images.remove(handle) {
+            Some(image) => image,
+            None => return false,
+        };
+
+        if self.free_image_count < self.free_image_capacity {
+            let dimensions = image.dimensions();
+
+            self.free_images
+                .entry([dimensions.width(), dimensions.height()])
+                .or_default()
+                .push(image);
+
+            self.free_image_count += 1;
+        }
+
+        true
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.images.clear();
+        self.free_images.clear();
+        self.free_image_count = 0;
+    }
+}
+
+/// Flattened segment data of recently used glyphs, keyed on `ScaledGlyph::unique_id`.
+///
+/// Once `capacity` is reached the least recently used entry is evicted.
+#[derive(Debug, Default)]
+pub(super) struct SegmentCache {
+    capacity: usize,
+    /// Incremented on every use, orders entries by when they were last used.
+    tick: u64,
+    segment_data: HashMap<u64, (Arc<Vec<[f32; 4]>>, u64)>,
+    /// `unique_id` of each entry keyed on the tick it was last used.
+    order: BTreeMap<u64, u64>,
+}
+
+impl SegmentCache {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    pub(super) fn get(&mut self, unique_id: u64) -> Option<Arc<Vec<[f32; 4]>>> {
+        let (segment_data, last_used) = self.segment_data.get_mut(&unique_id)?;
+        self.order.remove(last_used);
+        self.tick += 1;
+        *last_used = self.tick;
+        self.order.insert(self.tick, unique_id);
+        Some(segment_data.clone())
+    }
+
+    pub(super) fn insert(&mut self, unique_id: u64, segment_data: Arc<Vec<[f32; 4]>>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.tick += 1;
+
+        if let Some((_, last_used)) = self
+            .segment_data
+            .insert(unique_id, (segment_data, self.tick))
+        {
+            self.order.remove(&last_used);
+        }
+
+        self.order.insert(self.tick, unique_id);
+
+        while self.segment_data.len() > self.capacity {
+            let (_, least_recent) = self.order.pop_first().unwrap();
+            self.segment_data.remove(&least_recent);
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.segment_data.len()
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.segment_data.clear();
+        self.order.clear();
+    }
+}
@@ -0,0 +1,59 @@
+// The bicubic 4x4 downscale shared by downscale_cs and downscale_rgba_cs. Must be included after
+// srcImage is declared.
+
+float pixelValue(ivec2 reqCoords) {
+    ivec2 imageExtent = imageSize(srcImage);
+
+    if(reqCoords.x < 0 || reqCoords.x >= imageExtent.x
+        || reqCoords.y < 0 || reqCoords.y >= imageExtent.y)
+    {
+        return 0.0;
+    }
+
+    return imageLoad(srcImage, reqCoords).r;
+}
+
+float CubicHermite (float A, float B, float C, float D, float t) {
+	float t2 = t*t;
+    float t3 = t*t*t;
+    float a = -A/2.0 + (3.0*B)/2.0 - (3.0*C)/2.0 + D/2.0;
+    float b = A - (5.0*B)/2.0 + 2.0*C - D / 2.0;
+    float c = -A/2.0 + C/2.0;
+   	float d = B;
+    return a*t3 + b*t2 + c*t + d;
+}
+
+// The value of the 4x4 block of srcImage starting at srcCoords, interpolated at its center.
+float downscaledValue(ivec2 srcCoords) {
+    return CubicHermite(
+        CubicHermite(
+            pixelValue(srcCoords + ivec2(0, 0)),
+            pixelValue(srcCoords + ivec2(1, 0)),
+            pixelValue(srcCoords + ivec2(2, 0)),
+            pixelValue(srcCoords + ivec2(3, 0)),
+            0.5
+        ),
+        CubicHermite(
+            pixelValue(srcCoords + ivec2(0, 1)),
+            pixelValue(srcCoords + ivec2(1, 1)),
+            pixelValue(srcCoords + ivec2(2, 1)),
+            pixelValue(srcCoords + ivec2(3, 1)),
+            0.5
+        ),
+        CubicHermite(
+            pixelValue(srcCoords + ivec2(0, 2)),
+            pixelValue(srcCoords + ivec2(1, 2)),
+            pixelValue(srcCoords + ivec2(2, 2)),
+            pixelValue(srcCoords + ivec2(3, 2)),
+            0.5
+        ),
+        CubicHermite(
+            pixelValue(srcCoords + ivec2(0, 3)),
+            pixelValue(srcCoords + ivec2(1, 3)),
+            pixelValue(srcCoords + ivec2(2, 3)),
+            pixelValue(srcCoords + ivec2(3, 3)),
+            0.5
+        ),
+        0.5
+    );
+}
@@ -0,0 +1,34 @@
+#version 450
+
+layout(local_size_x = 8, local_size_y = 4, local_size_z = 1) in;
+
+// Coverage is linear. The images are UNORM storage images, which apply no transfer function on
+// load or store, so filtering here operates in linear space. Any encoding is done explicitly by
+// encodeOutput once the final value is known.
+layout(set = 0, binding = 0, r8) readonly uniform image2D srcImage;
+layout(set = 0, binding = 1, rgba8) writeonly uniform image2D dstImage;
+
+#include "output.glsl"
+#include "downscale.glsl"
+
+// Same as downscale_cs, but writes the three subpixels of a pixel directly as RGB so that the
+// hinting stage can be skipped.
+void main() {
+    ivec2 srcCoords = ivec2(
+        int(gl_GlobalInvocationID.x) * 12,
+        int(gl_GlobalInvocationID.y) * 4
+    );
+
+    imageStore(
+        dstImage,
+        ivec2(gl_GlobalInvocationID.x, gl_GlobalInvocationID.y),
+        vec4(
+            encodeOutput(applyAntialias(clamp(vec3(
+                downscaledValue(srcCoords + ivec2(0, 0)),
+                downscaledValue(srcCoords + ivec2(4, 0)),
+                downscaledValue(srcCoords + ivec2(8, 0))
+            ), 0.0, 1.0))),
+            1.0
+        )
+    );
+}
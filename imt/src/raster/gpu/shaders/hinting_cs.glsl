@@ -5,6 +5,8 @@ layout(local_size_x = 8, local_size_y = 4, local_size_z = 1) in;
 layout(set = 0, binding = 0, r8) readonly uniform image2D srcImage;
 layout(set = 0, binding = 1, rgba8) writeonly uniform image2D dstImage;
 
+#include "output.glsl"
+
 float pixelValue(ivec2 reqCoords) {
     ivec2 imageExtent = imageSize(srcImage);
 
@@ -35,11 +37,11 @@ void main() {
         dstImage,
         ivec2(gl_GlobalInvocationID.x, gl_GlobalInvocationID.y),
         vec4(
-            vec3(
+            encodeOutput(applyAntialias(vec3(
                 a + b + c,
                 b + c + d,
                 c + d + e
-            ),
+            ))),
             1.0
         )
     );
@@ -12,6 +12,13 @@ pub mod downscale_cs {
     }
 }
 
+pub mod downscale_rgba_cs {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "./src/raster/gpu/shaders/downscale_rgba_cs.glsl"
+    }
+}
+
 pub mod hinting_cs {
     vulkano_shaders::shader! {
         ty: "compute",
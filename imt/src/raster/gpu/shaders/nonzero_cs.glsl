@@ -6,6 +6,7 @@ layout(push_constant) uniform Info {
     vec2 extent;
     uint numSegments;
     uint numRays;
+    uint jitter;
 } info;
 
 layout(set = 0, binding = 0) readonly buffer RayData {
@@ -27,6 +28,22 @@ bool ray_intersects(vec2 l1p1, vec2 l1p2, vec2 l2p1, vec2 l2p2) {
     return (t >= 0.0 && t <= 1.0) && (u >= 0.0 && u <= 1.0);
 }
 
+// Integer hash of the pixel coordinates, see https://nullprogram.com/blog/2018/07/31/
+uint hash(uint x) {
+    x ^= x >> 16;
+    x *= 0x7feb352du;
+    x ^= x >> 15;
+    x *= 0x846ca68bu;
+    x ^= x >> 16;
+    return x;
+}
+
+// Offset within the pixel in the range of -0.5..0.5, stable for a given pixel.
+vec2 jitter_offset(uvec2 pixel) {
+    uint h = hash(pixel.x ^ hash(pixel.y));
+    return vec2(float(h & 0xffffu), float(h >> 16)) / 65535.0 - 0.5;
+}
+
 bool sample_filled(vec2 raySrc) {
     // Resources
     // - https://en.wikipedia.org/wiki/Nonzero-rule
@@ -60,9 +77,15 @@ bool sample_filled(vec2 raySrc) {
 }
 
 void main() {
+    vec2 pixel = vec2(gl_GlobalInvocationID.xy);
+
+    if(info.jitter != 0u) {
+        pixel += jitter_offset(gl_GlobalInvocationID.xy);
+    }
+
     vec2 raySrc = vec2(
-        float(gl_GlobalInvocationID.x) / float(info.extent.x),
-        float(gl_GlobalInvocationID.y) / float(info.extent.y)
+        pixel.x / float(info.extent.x),
+        pixel.y / float(info.extent.y)
     );
 
     uint fillCount = 0;
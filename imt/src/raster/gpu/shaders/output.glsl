@@ -0,0 +1,28 @@
+// Options of the shaders that write the final RGBA glyph image, i.e. hinting_cs and
+// downscale_rgba_cs when hinting is skipped.
+
+// When non-zero the output is encoded with the sRGB transfer function.
+layout(constant_id = 0) const int OUTPUT_SRGB = 0;
+
+layout(push_constant) uniform Options {
+    // When zero coverage is thresholded to fully covered or not covered at all.
+    uint antialias;
+} options;
+
+vec3 applyAntialias(vec3 coverage) {
+    if(options.antialias != 0) {
+        return coverage;
+    }
+
+    return step(vec3(0.5), coverage);
+}
+
+vec3 encodeOutput(vec3 linear) {
+    if(OUTPUT_SRGB == 0) {
+        return linear;
+    }
+
+    vec3 lower = linear * 12.92;
+    vec3 higher = (1.055 * pow(linear, vec3(1.0 / 2.4))) - 0.055;
+    return mix(higher, lower, lessThan(linear, vec3(0.0031308)));
+}
@@ -0,0 +1,297 @@
+use alloc::vec::Vec;
+
+use crate::parse::Outline;
+use crate::raster::flatten_contours;
+
+/// A filled triangle mesh of an outline, see `Outline::to_mesh`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    /// Positions in the same units as the outline.
+    pub vertices: Vec<[f32; 2]>,
+    /// Three indices into `vertices` per triangle, counter-clockwise with `Y` up.
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// The amount of triangles in the mesh.
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+}
+
+impl Outline {
+    /// Triangulate the area enclosed by the outline.
+    ///
+    /// Curves are subdivided until no segment deviates from the curve by more than
+    /// `flatten_tolerance`, in the same units as the outline. Use the outline in font units,
+    /// e.g. from `Font::varied_outline`, and scale the vertices to control the final size.
+    ///
+    /// Contours are normalized so that filled areas are counter-clockwise and holes clockwise,
+    /// regardless of the direction used by the font. Holes are then joined to the contour
+    /// around them and the result is triangulated by ear clipping.
+    ///
+    /// # Notes
+    /// - The direction of the contour enclosing the largest area is considered to be filled.
+    ///   Contours that wind the opposite direction are holes of the smallest contour around
+    ///   them, or are filled as well when they are not within one or cross another hole.
+    /// - Overlapping contours are triangulated independently, so their triangles overlap. This
+    ///   is common in variable fonts and does not affect the filled area.
+    /// - Contours that cross themselves may not be triangulated correctly, some of the
+    ///   triangles may then be clockwise.
+    /// - Returns an empty `Mesh` if the outline does not enclose any area.
+    pub fn to_mesh(&self, flatten_tolerance: f32) -> Mesh {
+        let mut mesh = Mesh::default();
+        let mut contours: Vec<Vec<u32>> = Vec::new();
+
+        for polyline in flatten_contours(self, [1.0, 1.0], flatten_tolerance) {
+            if polyline.len() < 3 {
+                continue;
+            }
+
+            let start = mesh.vertices.len() as u32;
+            contours.push((start..(start + polyline.len() as u32)).collect());
+            mesh.vertices.extend(polyline);
+        }
+
+        let mut areas: Vec<f32> = contours
+            .iter()
+            .map(|contour| polygon_area(&mesh.vertices, contour))
+            .collect();
+
+        let dominant = match areas
+            .iter()
+            .copied()
+            .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+        {
+            Some(area) if area != 0.0 => area.signum(),
+            _ => return Mesh::default(),
+        };
+
+        // Normalize filled areas to be counter-clockwise.
+        if dominant < 0.0 {
+            for (contour, area) in contours.iter_mut().zip(areas.iter_mut()) {
+                contour.reverse();
+                *area = -*area;
+            }
+        }
+
+        let mut polygons: Vec<(Vec<u32>, f32, Vec<Vec<u32>>)> = Vec::new();
+        let mut holes = Vec::new();
+
+        for (contour, area) in contours.into_iter().zip(areas) {
+            if area > 0.0 {
+                polygons.push((contour, area, Vec::new()));
+            } else if area < 0.0 {
+                holes.push((contour, area));
+            }
+        }
+
+        for (i, (mut hole, area)) in holes.iter().cloned().enumerate() {
+            let vertices = &mesh.vertices;
+
+            // Holes that cross each other can't both be joined to the same contour.
+            let crosses = holes.iter().enumerate().any(|(j, (other, _))| {
+                i != j
+                    && contour_edges(vertices, &hole)
+                        .any(|a| contour_edges(vertices, other).any(|b| segments_cross(a, b)))
+            });
+
+            let around = if crosses {
+                None
+            } else {
+                polygons
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (contour, ..))| {
+                        hole.iter().all(|index| {
+                            point_in_polygon(vertices, contour, vertices[*index as usize])
+                        })
+                    })
+                    .min_by(|(_, (_, a, _)), (_, (_, b, _))| a.total_cmp(b))
+                    .map(|(i, _)| i)
+            };
+
+            match around {
+                Some(i) => polygons[i].2.push(hole),
+                None => {
+                    hole.reverse();
+                    polygons.push((hole, -area, Vec::new()));
+                },
+            }
+        }
+
+        for (contour, _, holes) in polygons {
+            let polygon = join_holes(&mesh.vertices, contour, holes);
+            ear_clip(&mesh.vertices, polygon, &mut mesh.indices);
+        }
+
+        mesh
+    }
+}
+
+/// Join holes to the polygon around them with a bridge to the nearest visible vertex.
+///
+/// Each bridge is traversed in both directions, so the result is a single polygon that can be
+/// ear clipped.
+fn join_holes(vertices: &[[f32; 2]], mut polygon: Vec<u32>, mut holes: Vec<Vec<u32>>) -> Vec<u32> {
+    let rightmost = |hole: &Vec<u32>| {
+        (0..hole.len())
+            .max_by(|a, b| {
+                vertices[hole[*a] as usize][0].total_cmp(&vertices[hole[*b] as usize][0])
+            })
+            .unwrap()
+    };
+
+    // Holes furthest right are joined first, as those are least likely to be obstructed.
+    holes.sort_by(|a, b| {
+        let a = vertices[a[rightmost(a)] as usize][0];
+        let b = vertices[b[rightmost(b)] as usize][0];
+        b.total_cmp(&a)
+    });
+
+    for (i, hole) in holes.iter().enumerate() {
+        let start = rightmost(hole);
+        let from = vertices[hole[start] as usize];
+
+        let obstructed = |to: [f32; 2]| {
+            contour_edges(vertices, &polygon)
+                .chain(
+                    holes[i..]
+                        .iter()
+                        .flat_map(|hole| contour_edges(vertices, hole)),
+                )
+                .any(|edge| segments_cross([from, to], edge))
+        };
+
+        let distance = |index: u32| {
+            let [x, y] = vertices[index as usize];
+            ((x - from[0]) * (x - from[0])) + ((y - from[1]) * (y - from[1]))
+        };
+
+        let bridge = (0..polygon.len())
+            .filter(|j| !obstructed(vertices[polygon[*j] as usize]))
+            .min_by(|a, b| distance(polygon[*a]).total_cmp(&distance(polygon[*b])));
+
+        // Every vertex is obstructed only for malformed outlines, the hole is ignored.
+        let bridge = match bridge {
+            Some(some) => some,
+            None => continue,
+        };
+
+        let mut joined = Vec::with_capacity(polygon.len() + hole.len() + 2);
+        joined.extend_from_slice(&polygon[..=bridge]);
+        joined.extend_from_slice(&hole[start..]);
+        joined.extend_from_slice(&hole[..=start]);
+        joined.extend_from_slice(&polygon[bridge..]);
+        polygon = joined;
+    }
+
+    polygon
+}
+
+fn contour_edges<'a>(
+    vertices: &'a [[f32; 2]],
+    contour: &'a [u32],
+) -> impl Iterator<Item = [[f32; 2]; 2]> + 'a {
+    (0..contour.len()).map(move |i| {
+        [
+            vertices[contour[i] as usize],
+            vertices[contour[(i + 1) % contour.len()] as usize],
+        ]
+    })
+}
+
+/// Triangulate a counter-clockwise polygon, appending the triangles to `indices`.
+fn ear_clip(vertices: &[[f32; 2]], mut polygon: Vec<u32>, indices: &mut Vec<u32>) {
+    let point = |index: u32| vertices[index as usize];
+    let mut i = 0;
+    let mut attempts = 0;
+
+    while polygon.len() > 3 {
+        let len = polygon.len();
+        let [prev, current, next] = [
+            polygon[(i + len - 1) % len],
+            polygon[i % len],
+            polygon[(i + 1) % len],
+        ];
+        let [a, b, c] = [point(prev), point(current), point(next)];
+        let area = cross(a, b, c);
+
+        // Collinear points and spikes enclose no area.
+        let degenerate = area == 0.0;
+
+        let ear = degenerate
+            || (area > 0.0
+                && !polygon.iter().any(|index| {
+                    let p = point(*index);
+                    p != a && p != b && p != c && in_triangle(p, a, b, c)
+                }));
+
+        // Every vertex has been tried, clip regardless to guarantee progress.
+        if ear || attempts >= len {
+            if !degenerate {
+                indices.extend_from_slice(&[prev, current, next]);
+            }
+
+            polygon.remove(i % len);
+            attempts = 0;
+            i %= polygon.len();
+        } else {
+            i = (i + 1) % len;
+            attempts += 1;
+        }
+    }
+
+    if polygon.len() == 3 && cross(point(polygon[0]), point(polygon[1]), point(polygon[2])) > 0.0 {
+        indices.extend_from_slice(&polygon);
+    }
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `c`, positive when counter-clockwise.
+fn cross(a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> f32 {
+    ((b[0] - a[0]) * (c[1] - a[1])) - ((b[1] - a[1]) * (c[0] - a[0]))
+}
+
+fn in_triangle(p: [f32; 2], a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> bool {
+    cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+}
+
+fn polygon_area(vertices: &[[f32; 2]], polygon: &[u32]) -> f32 {
+    (0..polygon.len())
+        .map(|i| {
+            let [x1, y1] = vertices[polygon[i] as usize];
+            let [x2, y2] = vertices[polygon[(i + 1) % polygon.len()] as usize];
+            (x1 * y2) - (x2 * y1)
+        })
+        .sum::<f32>()
+        / 2.0
+}
+
+/// Even-odd test of `point` against a polygon.
+fn point_in_polygon(vertices: &[[f32; 2]], polygon: &[u32], point: [f32; 2]) -> bool {
+    let mut inside = false;
+
+    for i in 0..polygon.len() {
+        let [x1, y1] = vertices[polygon[i] as usize];
+        let [x2, y2] = vertices[polygon[(i + 1) % polygon.len()] as usize];
+
+        if (y1 > point[1]) != (y2 > point[1])
+            && point[0] < x1 + ((point[1] - y1) / (y2 - y1)) * (x2 - x1)
+        {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+/// Check if two line segments cross at a single point that is not an end point of either.
+fn segments_cross(a: [[f32; 2]; 2], b: [[f32; 2]; 2]) -> bool {
+    let d1 = cross(a[0], a[1], b[0]);
+    let d2 = cross(a[0], a[1], b[1]);
+    let d3 = cross(b[0], b[1], a[0]);
+    let d4 = cross(b[0], b[1], a[1]);
+
+    ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+}
@@ -1,8 +1,31 @@
-use crate::parse::{Font, Outline};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::parse::{Font, Outline, OutlineGeometry, OutlinePoint};
+use crate::util::float;
+use crate::util::pixel::{f32_to_dimension, pixel_bounds};
 use crate::util::variation::*;
-use crate::util::ImtUtilError;
 
+pub mod backend;
+pub mod cpu;
+#[cfg(feature = "gpu")]
 pub mod gpu;
+pub mod mesh;
+pub mod rle;
+pub mod stroke;
+
+/// Default maximum distance in pixels between a curve and the segments approximating it.
+///
+/// At common text sizes this results in around eight segments for the largest curves.
+pub const DEFAULT_CURVE_TOLERANCE: f32 = 0.1;
+/// Upper limit of segments a single curve is subdivided into.
+const MAX_CURVE_SUBDIVISIONS: usize = 64;
+/// Curve tolerance used by `ScaledGlyph::scanline_coverage`.
+const SCANLINE_CURVE_TOLERANCE: f32 = 0.01;
+/// Outlines whose contours enclose less than this area in font units in total, see
+/// `Outline::enclosed_area`, are treated as having no outline.
+const DEGENERATE_AREA: f32 = 1.0;
 
 /// A glyph outline that is scaled with bearings and advance.
 ///
@@ -17,17 +40,96 @@ pub struct ScaledGlyph {
     /// Height the image should be
     pub height: u32,
     /// Left offset from pen location (does not effect location)
+    ///
+    /// This is negative when ink extends left of the pen location, e.g. for overhanging marks.
     pub bearing_x: i16,
     /// Distance from baseline
     pub bearing_y: i16,
     /// Amount to advance pen location
     pub advance_w: i16,
+    /// Unrounded amount to advance pen location
+    ///
+    /// Accumulating this instead of `advance_w` keeps rounding error from adding up over long
+    /// lines, see `layout::pen_positions`.
+    pub advance_w_f32: f32,
     /// Outline point values will be between `0..=1` with `Y` down.
     pub outline: Option<Outline>,
-    /// An unique ID derived from glyph_id, size, and axis coordinates.
+    /// An unique ID derived from the font, glyph_id, size, and axis coordinates.
     pub unique_id: u64,
 }
 
+/// The measurements and outline of a glyph for rendering, see `Font::prepare_glyph`.
+///
+/// # Notes
+/// - All measurements are in pixels at the size the glyph was prepared at.
+/// - Bounds and bearings are relative to the pen location on the baseline with `Y` up, while
+///   the outline is normalized to the bitmap with `Y` down.
+#[derive(Debug, Clone)]
+pub struct PreparedGlyph {
+    /// Amount to advance the pen location, rounded up to a whole pixel.
+    pub advance: i16,
+    /// Unrounded amount to advance the pen location.
+    pub advance_f32: f32,
+    /// Distance from the pen location to the left edge of the bitmap.
+    pub bearing_x: i16,
+    /// Distance from the baseline to the bottom edge of the bitmap.
+    pub bearing_y: i16,
+    /// Width of the bitmap.
+    pub width: u32,
+    /// Height of the bitmap.
+    pub height: u32,
+    /// Outline with point values between `0..=1` of the bitmap and `Y` down.
+    ///
+    /// This is `None` if the glyph does not have an outline, e.g. a space. All bounds will be
+    /// zero in this case.
+    pub outline: Option<Outline>,
+    /// An unique ID derived from the font, glyph_id, size, and axis coordinates.
+    pub unique_id: u64,
+}
+
+impl PreparedGlyph {
+    /// The bounds of the bitmap as `[x_min, y_min, x_max, y_max]` relative to the pen location.
+    pub fn bounds(&self) -> [i32; 4] {
+        let x_min = self.bearing_x as i32;
+        let y_min = self.bearing_y as i32;
+        [
+            x_min,
+            y_min,
+            x_min + self.width as i32,
+            y_min + self.height as i32,
+        ]
+    }
+
+    /// Convert into a `ScaledGlyph` for use with a rasterizer.
+    pub fn into_scaled(self) -> ScaledGlyph {
+        ScaledGlyph {
+            width: self.width,
+            height: self.height,
+            bearing_x: self.bearing_x,
+            bearing_y: self.bearing_y,
+            advance_w: self.advance,
+            advance_w_f32: self.advance_f32,
+            outline: self.outline,
+            unique_id: self.unique_id,
+        }
+    }
+}
+
+impl From<ScaledGlyph> for PreparedGlyph {
+    fn from(glyph: ScaledGlyph) -> Self {
+        Self {
+            advance: glyph.advance_w,
+            advance_f32: glyph.advance_w_f32,
+            bearing_x: glyph.bearing_x,
+            bearing_y: glyph.bearing_y,
+            width: glyph.width,
+            height: glyph.height,
+            outline: glyph.outline,
+            unique_id: glyph.unique_id,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScaledGlyphErr {
     /// Glyph data is missing
@@ -38,32 +140,42 @@ pub enum ScaledGlyphErr {
     Malformed,
 }
 
-#[inline(always)]
-fn round_left(v: f32) -> f32 {
-    v.trunc() - v.is_sign_negative() as i8 as f32
-}
-
-#[inline(always)]
-fn round_right(v: f32) -> f32 {
-    v.trunc() + v.is_sign_positive() as i8 as f32
+impl fmt::Display for ScaledGlyphErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Missing => "glyph data is missing",
+            Self::InvalidCoords => "coordinates are invalid",
+            Self::Malformed => "font is malformed",
+        })
+    }
 }
 
-#[inline]
-fn f32_to_dimension(v: f32) -> Option<u32> {
-    if v < 0.0 {
-        None
-    } else {
-        let int = v as u32;
+#[cfg(feature = "std")]
+impl std::error::Error for ScaledGlyphErr {}
 
-        if int == 0 {
-            None
-        } else {
-            Some(int)
-        }
-    }
+/// Grid-fitting applied to a `ScaledGlyph` with `ScaledGlyph::apply_hinting`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Hinting {
+    /// The outline is left as is.
+    #[default]
+    None,
+    /// Snap every point of the outline to a grid of `scale` pixels.
+    ///
+    /// This is intended for designs made for a base size, e.g. icons, rendered at an integer
+    /// multiple `scale` of that size. Stems between straight edges are then always an integer
+    /// amount of pixels wide with edges on pixel boundaries, so they are rendered without
+    /// antialiasing. The glyph should be evaluated at the base size times `scale`.
+    PixelPerfect { scale: u32 },
 }
 
 impl ScaledGlyph {
+    /// Scale the outline of a glyph to `size` with variations applied for `coords`.
+    ///
+    /// # Notes
+    /// - Returns `ScaledGlyphErr::Missing` if `glyph_id` is not less than `Font::num_glyphs`, so
+    ///   ids from untrusted sources, e.g. the glyph streams of a PDF, can be passed directly.
+    /// - `None` for `coords` produces the default instance of a variable font, identical to
+    ///   passing the default of every axis. See `Font::default_is_renderable`.
     pub fn evaluate(
         font: &Font,
         coords: Option<&[f32]>,
@@ -71,6 +183,41 @@ impl ScaledGlyph {
         glyph_id: u16,
         size: f32,
     ) -> Result<Self, ScaledGlyphErr> {
+        Self::evaluate_inner(font, coords, coords_normalized, glyph_id, size, false)
+            .map(|(glyph, _)| glyph)
+    }
+
+    /// Same as `evaluate`, but also returns the outline in font units prior to scaling.
+    ///
+    /// The returned outline has variations applied, but keeps the original point values that
+    /// scaling replaces, e.g. for editors that display and modify the points of a glyph.
+    ///
+    /// # Notes
+    /// - The outline is kept even when it encloses no area and `ScaledGlyph::outline` is `None`.
+    /// - This retains a second copy of the points, contours and geometry of the outline, roughly
+    ///   doubling the memory used per glyph. Prefer `evaluate` when the points aren't needed.
+    pub fn evaluate_with_source(
+        font: &Font,
+        coords: Option<&[f32]>,
+        coords_normalized: bool,
+        glyph_id: u16,
+        size: f32,
+    ) -> Result<(Self, Option<Outline>), ScaledGlyphErr> {
+        Self::evaluate_inner(font, coords, coords_normalized, glyph_id, size, true)
+    }
+
+    fn evaluate_inner(
+        font: &Font,
+        coords: Option<&[f32]>,
+        coords_normalized: bool,
+        glyph_id: u16,
+        size: f32,
+        retain_source: bool,
+    ) -> Result<(Self, Option<Outline>), ScaledGlyphErr> {
+        if glyph_id >= font.num_glyphs() {
+            return Err(ScaledGlyphErr::Missing);
+        }
+
         let coords = match coords {
             Some(coords) => {
                 let mut coords = coords.to_vec();
@@ -83,12 +230,15 @@ impl ScaledGlyph {
                 Some(coords)
             },
             None => None,
-        };
+        }
+        // The default instance has no variations to apply.
+        .filter(|coords| !is_default_normalized(coords));
 
         let unique_id = match coords.as_ref() {
-            Some(coords) => unique_id(glyph_id, size, Some(coords), 0),
+            Some(coords) => unique_id(font.id(), glyph_id, size, Some(coords), 0),
             None => {
                 unique_id(
+                    font.id(),
                     glyph_id,
                     size,
                     None,
@@ -100,46 +250,74 @@ impl ScaledGlyph {
             },
         };
 
+        let metrics_glyph_id = font.metrics_glyph_id(glyph_id);
+
         let mut advance_w = font
-            .hmtx_table()
-            .hor_metric
-            .get(glyph_id as usize)
-            .ok_or(ScaledGlyphErr::Missing)?
-            .advance_width as f32;
+            .try_hmtx_table()
+            .map_err(|_| ScaledGlyphErr::Missing)?
+            .advance_width(metrics_glyph_id)
+            .ok_or(ScaledGlyphErr::Missing)? as f32;
 
         if let Some(coords) = coords.as_ref() {
-            advance_w +=
-                advance_width(font, glyph_id, coords).map_err(|_| ScaledGlyphErr::InvalidCoords)?;
+            advance_w += advance_width(font, metrics_glyph_id, coords)
+                .map_err(|_| ScaledGlyphErr::InvalidCoords)?;
         }
 
         let scaler = (1.0 / font.head_table().units_per_em as f32) * size;
         advance_w *= scaler;
 
-        let mut outline = match font.glyf_table().outlines.get(&glyph_id) {
-            Some(some) => some.clone(),
+        let width_before = match font
+            .try_glyf_table()
+            .map_err(|_| ScaledGlyphErr::Missing)?
+            .outlines
+            .get(&glyph_id)
+        {
+            Some(base) => base.x_max - base.x_min,
+            None => 0.0,
+        };
+
+        let mut outline = match font.try_varied_outline(glyph_id, coords.as_ref())? {
+            Some(some) => some,
             None => {
-                return Ok(Self {
+                return Ok((
+                    Self {
+                        width: 0,
+                        height: 0,
+                        bearing_x: 0,
+                        bearing_y: 0,
+                        advance_w: float::ceil(advance_w) as i16,
+                        advance_w_f32: advance_w,
+                        outline: None,
+                        unique_id,
+                    },
+                    None,
+                ));
+            },
+        };
+
+        let source = if retain_source {
+            Some(outline.clone())
+        } else {
+            None
+        };
+
+        advance_w += ((outline.x_max - outline.x_min) - width_before) * scaler;
+
+        // Contours that enclose no area would not produce any coverage.
+        if outline.enclosed_area() < DEGENERATE_AREA {
+            return Ok((
+                Self {
                     width: 0,
                     height: 0,
                     bearing_x: 0,
                     bearing_y: 0,
-                    advance_w: advance_w.ceil() as i16,
+                    advance_w: float::ceil(advance_w) as i16,
+                    advance_w_f32: advance_w,
                     outline: None,
                     unique_id,
-                });
-            },
-        };
-
-        if let Some(coords) = coords.as_ref() {
-            let width_before = outline.x_max - outline.x_min;
-
-            match outline_apply_gvar(font, glyph_id, &mut outline, coords) {
-                Err(ImtUtilError::InvalidCoords) => return Err(ScaledGlyphErr::InvalidCoords),
-                Err(ImtUtilError::MalformedOutline) => return Err(ScaledGlyphErr::Malformed),
-                _ => (),
-            }
-
-            advance_w += ((outline.x_max - outline.x_min) - width_before) * scaler;
+                },
+                source,
+            ));
         }
 
         // Horizonal
@@ -147,8 +325,7 @@ impl ScaledGlyph {
         let x_max_raw = outline.x_max * scaler;
         let x_min_raw = outline.x_min * scaler;
         let width_raw = x_max_raw - x_min_raw;
-        let x_max_whole = round_right(x_max_raw);
-        let x_min_whole = round_left(x_min_raw);
+        let (x_min_whole, x_max_whole) = pixel_bounds(x_min_raw, x_max_raw);
         let width_whole = x_max_whole - x_min_whole;
         let x_offset = (x_min_raw - x_min_whole) - x_min_raw;
         let width = f32_to_dimension(width_whole).ok_or(ScaledGlyphErr::Malformed)?;
@@ -159,8 +336,7 @@ impl ScaledGlyph {
 
         let y_max_raw = outline.y_max * scaler;
         let y_min_raw = outline.y_min * scaler;
-        let y_max_whole = round_right(y_max_raw);
-        let y_min_whole = round_left(y_min_raw);
+        let (y_min_whole, y_max_whole) = pixel_bounds(y_min_raw, y_max_raw);
         let height_whole = y_max_whole - y_min_whole;
         let y_offset = (y_min_raw - y_min_whole) - y_min_raw;
         let height = f32_to_dimension(height_whole).ok_or(ScaledGlyphErr::Malformed)?;
@@ -173,25 +349,247 @@ impl ScaledGlyph {
             point.y = (height_whole - ((point.y * scaler) + y_offset)) / height_whole;
         }
 
-        outline.rebuild().unwrap();
+        // Scaling only moves points, the contours & control flags that `rebuild` validates are
+        // unchanged from the outline that was already built. This can not fail in practice, but
+        // is propagated instead of panicking should that ever change.
+        outline.rebuild().map_err(|_| ScaledGlyphErr::Malformed)?;
 
-        Ok(Self {
-            width,
-            height,
-            bearing_x,
-            bearing_y,
-            advance_w: advance_w.ceil() as i16,
-            outline: Some(outline),
-            unique_id,
-        })
+        Ok((
+            Self {
+                width,
+                height,
+                bearing_x,
+                bearing_y,
+                advance_w: float::ceil(advance_w) as i16,
+                advance_w_f32: advance_w,
+                outline: Some(outline),
+                unique_id,
+            },
+            source,
+        ))
+    }
+
+    /// Apply grid-fitting to the outline.
+    ///
+    /// The bounds, bearings and advance are updated to match the fitted outline. `unique_id` is
+    /// updated as well, so hinted and unhinted glyphs can be cached alongside each other.
+    ///
+    /// # Notes
+    /// - With `Hinting::PixelPerfect` curves still produce partial coverage along their edges.
+    /// - A `scale` of zero is treated as one.
+    /// - Returns `ScaledGlyphErr::Malformed` if the fitted outline can not be rebuilt, the glyph
+    ///   is left unchanged in that case.
+    pub fn apply_hinting(&mut self, hinting: Hinting) -> Result<(), ScaledGlyphErr> {
+        use core::hash::Hasher;
+
+        let scale = match hinting {
+            Hinting::None => return Ok(()),
+            Hinting::PixelPerfect {
+                scale,
+            } => scale.max(1) as f32,
+        };
+
+        let advance_w = (float::round(self.advance_w_f32 / scale) * scale).max(0.0);
+        let mut hasher = crate::util::hash::FnvHasher::default();
+        hasher.write_u64(self.unique_id);
+        hasher.write_u32(scale as u32);
+        let unique_id = hasher.finish();
+
+        // Without points there is nothing to fit and the bounds below would be infinite.
+        if let Some(outline) = self
+            .outline
+            .as_ref()
+            .filter(|outline| !outline.points.is_empty())
+        {
+            let mut outline = outline.clone();
+            let (width, height) = (self.width as f32, self.height as f32);
+            let (bearing_x, bearing_y) = (self.bearing_x as f32, self.bearing_y as f32);
+            let snap = |value: f32| float::round(value / scale) * scale;
+
+            // Points in pixels relative to the pen location with Y up.
+            for point in outline.points.iter_mut() {
+                point.x = snap(bearing_x + (point.x * width));
+                point.y = snap(bearing_y + ((1.0 - point.y) * height));
+            }
+
+            let (mut x_min, mut x_max) = (f32::INFINITY, f32::NEG_INFINITY);
+            let (mut y_min, mut y_max) = (f32::INFINITY, f32::NEG_INFINITY);
+
+            for point in outline.points.iter() {
+                x_min = x_min.min(point.x);
+                x_max = x_max.max(point.x);
+                y_min = y_min.min(point.y);
+                y_max = y_max.max(point.y);
+            }
+
+            let width = (x_max - x_min).max(1.0);
+            let height = (y_max - y_min).max(1.0);
+
+            for point in outline.points.iter_mut() {
+                point.x = (point.x - x_min) / width;
+                point.y = (height - (point.y - y_min)) / height;
+            }
+
+            outline.rebuild().map_err(|_| ScaledGlyphErr::Malformed)?;
+
+            self.outline = Some(outline);
+            self.width = width as u32;
+            self.height = height as u32;
+            self.bearing_x = x_min as i16;
+            self.bearing_y = y_min as i16;
+        }
+
+        self.advance_w_f32 = advance_w;
+        self.advance_w = advance_w as i16;
+        self.unique_id = unique_id;
+        Ok(())
+    }
+
+    /// Flatten the outline into line segments of `[x1, y1, x2, y2]`.
+    ///
+    /// Curves are subdivided until no segment deviates from the curve by more than
+    /// `curve_tolerance` pixels. Points are in the same `0..=1` space as `outline`.
+    ///
+    /// # Notes
+    /// - The result only depends on the glyph and `curve_tolerance`, so it may be stored and
+    ///   reused when rasterizing the same glyph again.
+    /// - Returns an empty `Vec` if the glyph does not have an outline.
+    pub fn segment_data(&self, curve_tolerance: f32) -> Vec<[f32; 4]> {
+        let outline = match self.outline.as_ref() {
+            Some(some) => some,
+            None => return Vec::new(),
+        };
+
+        let mut segment_data = Vec::with_capacity(outline.geometry.len());
+
+        for geometry in outline.geometry.iter() {
+            match geometry {
+                OutlineGeometry::Segment {
+                    p1,
+                    p2,
+                } => {
+                    segment_data.push([p1.x, p1.y, p2.x, p2.y]);
+                },
+                OutlineGeometry::QuadraticCurve {
+                    p1, ..
+                } => {
+                    let scale = [self.width as f32, self.height as f32];
+                    let mut start = p1.clone();
+
+                    for end in flatten_geometry(geometry, scale, curve_tolerance) {
+                        segment_data.push([start.x, start.y, end.x, end.y]);
+                        start = end;
+                    }
+                },
+            }
+        }
+
+        segment_data
+    }
+
+    /// Compute the coverage of each pixel within row `y` of the glyph's image.
+    ///
+    /// Coverage is the exact fraction of the pixel's area that is inside the outline, computed
+    /// from the area between each edge and the right of the row rather than by sampling.
+    ///
+    /// # Notes
+    /// - Rows are counted from the top of the image (`Y` down) and the returned `Vec` has one
+    ///   value per pixel from left to right, between `0.0..=1.0`.
+    /// - Returns an empty `Vec` if the glyph does not have an outline or `y` is not within
+    ///   `height`.
+    /// - Where contours overlap the winding is clamped, so coverage is exact for the nonzero rule
+    ///   except for pixels along the edges of the overlap.
+    pub fn scanline_coverage(&self, y: u32) -> Vec<f32> {
+        if self.outline.is_none() || y >= self.height {
+            return Vec::new();
+        }
+
+        let width = self.width as f32;
+        let height = self.height as f32;
+        let row = y as f32;
+        // An extra value on the right for edges that lie exactly on the right of the image.
+        let mut accumulation = vec![0.0_f32; self.width as usize + 2];
+
+        for [x1, y1, x2, y2] in self.segment_data(SCANLINE_CURVE_TOLERANCE) {
+            let (x1, y1, x2, y2) = (x1 * width, y1 * height, x2 * width, y2 * height);
+
+            if y1 == y2 {
+                continue;
+            }
+
+            // Clip the edge to the row.
+            let t_top = (row - y1) / (y2 - y1);
+            let t_bottom = ((row + 1.0) - y1) / (y2 - y1);
+            let t_start = t_top.min(t_bottom).max(0.0);
+            let t_end = t_top.max(t_bottom).min(1.0);
+
+            if t_start >= t_end {
+                continue;
+            }
+
+            accumulate_edge(
+                &mut accumulation,
+                [x1 + ((x2 - x1) * t_start), y1 + ((y2 - y1) * t_start)],
+                [x1 + ((x2 - x1) * t_end), y1 + ((y2 - y1) * t_end)],
+            );
+        }
+
+        let mut winding = 0.0;
+
+        accumulation[..(self.width as usize)]
+            .iter()
+            .map(|value| {
+                winding += value;
+                winding.abs().min(1.0)
+            })
+            .collect()
     }
 }
 
-fn unique_id(glyph_id: u16, size: f32, coords: Option<&[f32]>, axis_count: usize) -> u64 {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::Hasher;
+/// Accumulate the signed area right of an edge that lies within a single row.
+///
+/// The coverage of a pixel is the sum of `accumulation` up to and including the pixel.
+fn accumulate_edge(accumulation: &mut [f32], p1: [f32; 2], p2: [f32; 2]) {
+    let max_x = (accumulation.len() - 2) as f32;
+    let x1 = p1[0].clamp(0.0, max_x);
+    let x2 = p2[0].clamp(0.0, max_x);
+    let dy = p2[1] - p1[1];
+    let (x_min, x_max) = (x1.min(x2), x1.max(x2));
 
-    let mut hasher = DefaultHasher::default();
+    // Split the edge into pieces that each lie within a single pixel. Within a pixel the
+    // coverage is split between it and the next pixel based on where the piece lies.
+    let mut add_piece = |start: f32, end: f32, dy: f32| {
+        let pixel = (float::floor(start) as usize).min(accumulation.len() - 2);
+        let inside = ((start + end) / 2.0) - pixel as f32;
+        accumulation[pixel] += dy * (1.0 - inside);
+        accumulation[pixel + 1] += dy * inside;
+    };
+
+    if x_max - x_min <= f32::EPSILON {
+        add_piece(x_min, x_min, dy);
+        return;
+    }
+
+    let mut start = x_min;
+
+    while start < x_max {
+        let end = (float::floor(start) + 1.0).min(x_max);
+        add_piece(start, end, dy * ((end - start) / (x_max - x_min)));
+        start = end;
+    }
+}
+
+fn unique_id(
+    font_id: u64,
+    glyph_id: u16,
+    size: f32,
+    coords: Option<&[f32]>,
+    axis_count: usize,
+) -> u64 {
+    use core::hash::Hasher;
+
+    let mut hasher = crate::util::hash::FnvHasher::default();
+    hasher.write_u64(font_id);
     hasher.write_u16(glyph_id);
     hasher.write_u32(size.to_bits());
 
@@ -210,3 +608,101 @@ fn unique_id(glyph_id: u16, size: f32, coords: Option<&[f32]>, axis_count: usize
 
     hasher.finish()
 }
+
+/// Flatten a segment or curve into the end points of the line segments approximating it, the
+/// start point is not included.
+///
+/// Curves are subdivided until no segment deviates from the curve by more than `tolerance`,
+/// measured after scaling the geometry by `scale`. The points themselves are not scaled.
+pub(crate) fn flatten_geometry(
+    geometry: &OutlineGeometry,
+    scale: [f32; 2],
+    tolerance: f32,
+) -> impl Iterator<Item = OutlinePoint> + '_ {
+    let (steps, end) = match geometry {
+        OutlineGeometry::Segment {
+            p2, ..
+        } => (1, p2),
+        OutlineGeometry::QuadraticCurve {
+            p1,
+            p2,
+            p3,
+        } => {
+            // A quadratic split into n even parts deviates by |p1 - 2p2 + p3| / 4n² at most.
+            let dx = (p1.x - (2.0 * p2.x) + p3.x) * scale[0];
+            let dy = (p1.y - (2.0 * p2.y) + p3.y) * scale[1];
+            let deviation = float::sqrt((dx * dx) + (dy * dy)) / 4.0;
+
+            let steps = if tolerance > 0.0 {
+                (float::ceil(float::sqrt(deviation / tolerance)) as usize)
+                    .clamp(1, MAX_CURVE_SUBDIVISIONS)
+            } else {
+                MAX_CURVE_SUBDIVISIONS
+            };
+
+            (steps, p3)
+        },
+    };
+
+    // The end point is exact, so contours close on the start of their first geometry.
+    (1..=steps).map(move |i| {
+        if i == steps {
+            end.clone()
+        } else {
+            geometry.evaluate(i as f32 / steps as f32)
+        }
+    })
+}
+
+/// Flatten the geometry of an outline into polylines, one per contour without the closing point.
+///
+/// Points are scaled by `scale` and curves are flattened with `flatten_geometry`, so `tolerance`
+/// is in scaled units.
+pub(crate) fn flatten_contours(
+    outline: &Outline,
+    scale: [f32; 2],
+    tolerance: f32,
+) -> Vec<Vec<[f32; 2]>> {
+    let mut polylines = Vec::new();
+    let mut current: Vec<[f32; 2]> = Vec::new();
+    let scaled = |p: &OutlinePoint| [p.x * scale[0], p.y * scale[1]];
+
+    for geometry in outline.geometry.iter() {
+        let start = match geometry {
+            OutlineGeometry::Segment {
+                p1, ..
+            }
+            | OutlineGeometry::QuadraticCurve {
+                p1, ..
+            } => scaled(p1),
+        };
+
+        if current.last() != Some(&start) {
+            if !current.is_empty() {
+                polylines.push(core::mem::take(&mut current));
+            }
+
+            current.push(start);
+        }
+
+        for point in flatten_geometry(geometry, scale, tolerance) {
+            let point = scaled(&point);
+
+            if current.last() != Some(&point) {
+                current.push(point);
+            }
+        }
+
+        // The contour has been closed.
+        if current.len() > 1 && current.first() == current.last() {
+            current.pop();
+            polylines.push(core::mem::take(&mut current));
+        }
+    }
+
+    if !current.is_empty() {
+        polylines.push(current);
+    }
+
+    polylines
+}
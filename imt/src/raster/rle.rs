@@ -0,0 +1,72 @@
+use alloc::vec::Vec;
+
+/// A glyph bitmap stored as runs of identical pixels.
+///
+/// Runs are in row-major order and may span multiple rows. Glyphs usually have large regions
+/// without coverage, so this is considerably smaller than the bitmap itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RleMask {
+    pub width: u32,
+    pub height: u32,
+    pub runs: Vec<RleRun>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RleRun {
+    /// Amount of consecutive pixels with `value`. Never zero.
+    pub length: u32,
+    pub value: [u8; 4],
+}
+
+impl RleMask {
+    /// Encode a row-major bitmap.
+    ///
+    /// Returns `None` if the length of `pixels` does not equal `width * height`.
+    pub fn encode(width: u32, height: u32, pixels: &[[u8; 4]]) -> Option<Self> {
+        if width as usize * height as usize != pixels.len() {
+            return None;
+        }
+
+        let mut runs: Vec<RleRun> = Vec::new();
+
+        for pixel in pixels.iter() {
+            match runs.last_mut() {
+                Some(run) if run.value == *pixel => run.length += 1,
+                _ => {
+                    runs.push(RleRun {
+                        length: 1,
+                        value: *pixel,
+                    })
+                },
+            }
+        }
+
+        Some(Self {
+            width,
+            height,
+            runs,
+        })
+    }
+
+    /// Decode into a row-major bitmap of `width * height` pixels.
+    ///
+    /// # Notes
+    /// - If the runs do not cover the whole mask the remaining pixels are zero.
+    pub fn decode(&self) -> Vec<[u8; 4]> {
+        let len = self.width as usize * self.height as usize;
+        let mut pixels = Vec::with_capacity(len);
+
+        for run in self.runs.iter() {
+            let length = (run.length as usize).min(len - pixels.len());
+            pixels.resize(pixels.len() + length, run.value);
+        }
+
+        pixels.resize(len, [0; 4]);
+        pixels
+    }
+
+    /// The amount of bytes used by the runs.
+    pub fn encoded_size(&self) -> usize {
+        self.runs.len() * core::mem::size_of::<RleRun>()
+    }
+}
@@ -0,0 +1,300 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use core::hash::Hasher;
+
+use crate::parse::glyf_table::OutlineRawPoint;
+use crate::parse::Outline;
+use crate::raster::{flatten_contours, ScaledGlyph, DEFAULT_CURVE_TOLERANCE};
+use crate::util::float;
+use crate::util::hash::FnvHasher;
+
+/// Miter joins longer than this multiple of half the stroke width are beveled instead.
+const MITER_LIMIT: f32 = 4.0;
+/// Amount of segments a full circle of a round join is subdivided into.
+const ROUND_SUBDIVISIONS: f32 = 16.0;
+
+/// Defines how the corners of a stroke are joined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Join {
+    /// Corners are extended to a point. Falls back to `Bevel` for very sharp corners.
+    #[default]
+    Miter,
+    /// Corners are rounded.
+    Round,
+    /// Corners are cut off.
+    Bevel,
+}
+
+/// Defines the stroke used by `ScaledGlyph::stroke`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+    /// Width of the stroke in pixels.
+    pub width: f32,
+    pub join: Join,
+}
+
+impl ScaledGlyph {
+    /// Create a glyph whose outline is the stroke of this glyph's outline.
+    ///
+    /// The result can be rasterized like any other `ScaledGlyph`.
+    ///
+    /// # Notes
+    /// - The stroke is centered on the outline, so the image grows by about half the width on
+    ///   each side. `width`, `height`, `bearing_x` & `bearing_y` are adjusted accordingly, while
+    ///   `advance_w` is left unchanged.
+    /// - Contours are stroked as closed, like they are filled. The ends of a contour that doubles
+    ///   back on itself, e.g. a single line, are capped by the join: round with `Join::Round` and
+    ///   flat otherwise.
+    /// - Curves are flattened within `DEFAULT_CURVE_TOLERANCE` pixels before stroking.
+    /// - Returns `None` if the glyph does not have an outline or the width is not positive.
+    pub fn stroke(&self, style: &StrokeStyle) -> Option<Self> {
+        let outline = self.outline.as_ref()?;
+
+        if style.width.is_nan() || style.width <= 0.0 {
+            return None;
+        }
+
+        let polygons = stroke_polygons(outline, self.width as f32, self.height as f32, style);
+        let mut x_min = f32::INFINITY;
+        let mut x_max = f32::NEG_INFINITY;
+        let mut y_min = f32::INFINITY;
+        let mut y_max = f32::NEG_INFINITY;
+
+        for [x, y] in polygons.iter().flatten() {
+            x_min = x_min.min(*x);
+            x_max = x_max.max(*x);
+            y_min = y_min.min(*y);
+            y_max = y_max.max(*y);
+        }
+
+        if polygons.is_empty() {
+            return None;
+        }
+
+        let x_origin = float::floor(x_min);
+        let y_origin = float::floor(y_min);
+        let width = (float::ceil(x_max) - x_origin) as u32;
+        let height = (float::ceil(y_max) - y_origin) as u32;
+
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let mut points = Vec::new();
+        let mut contours = Vec::with_capacity(polygons.len());
+
+        for (c, polygon) in polygons.into_iter().enumerate() {
+            let start = points.len();
+
+            for [x, y] in polygon {
+                points.push(OutlineRawPoint {
+                    c: c as u16,
+                    x: (x - x_origin) / width as f32,
+                    y: (y - y_origin) / height as f32,
+                    control: false,
+                });
+            }
+
+            contours.push(start..points.len());
+        }
+
+        let mut stroked = Outline {
+            x_min: 0.0,
+            y_min: 0.0,
+            x_max: 0.0,
+            y_max: 0.0,
+            points,
+            contours,
+            geometry: Vec::new(),
+        };
+
+        stroked.rebuild().ok()?;
+
+        let mut hasher = FnvHasher::default();
+        hasher.write_u64(self.unique_id);
+        hasher.write_u32(style.width.to_bits());
+        hasher.write_u8(style.join as u8);
+
+        Some(Self {
+            width,
+            height,
+            bearing_x: (self.bearing_x as i32 + x_origin as i32) as i16,
+            bearing_y: (self.bearing_y as i32 + self.height as i32
+                - y_origin as i32
+                - height as i32) as i16,
+            advance_w: self.advance_w,
+            advance_w_f32: self.advance_w_f32,
+            outline: Some(stroked),
+            unique_id: hasher.finish(),
+        })
+    }
+}
+
+/// Produce the polygons that make up the stroke of `outline` in pixel space.
+///
+/// All polygons share the same orientation, so their union is filled by the nonzero rule.
+fn stroke_polygons(
+    outline: &Outline,
+    scale_x: f32,
+    scale_y: f32,
+    style: &StrokeStyle,
+) -> Vec<Vec<[f32; 2]>> {
+    let half = style.width / 2.0;
+    let mut polygons = Vec::new();
+
+    for contour in flatten_contours(outline, [scale_x, scale_y], DEFAULT_CURVE_TOLERANCE) {
+        let len = contour.len();
+
+        if len < 2 {
+            continue;
+        }
+
+        for i in 0..len {
+            let p1 = contour[i];
+            let p2 = contour[(i + 1) % len];
+            let p3 = contour[(i + 2) % len];
+            let n1 = normal(p1, p2, half);
+            let n2 = normal(p2, p3, half);
+
+            push_polygon(
+                &mut polygons,
+                vec![
+                    [p1[0] + n1[0], p1[1] + n1[1]],
+                    [p2[0] + n1[0], p2[1] + n1[1]],
+                    [p2[0] - n1[0], p2[1] - n1[1]],
+                    [p1[0] - n1[0], p1[1] - n1[1]],
+                ],
+            );
+
+            push_join(&mut polygons, p2, n1, n2, half, style.join);
+        }
+    }
+
+    polygons
+}
+
+/// The left normal of the line `p1` to `p2` with a length of `half`.
+fn normal(p1: [f32; 2], p2: [f32; 2], half: f32) -> [f32; 2] {
+    let dx = p2[0] - p1[0];
+    let dy = p2[1] - p1[1];
+    let len = float::sqrt(dx * dx + dy * dy);
+
+    if len == 0.0 {
+        [0.0, 0.0]
+    } else {
+        [-dy / len * half, dx / len * half]
+    }
+}
+
+/// Add the polygon that fills the outer corner at `p` between two segments.
+fn push_join(
+    polygons: &mut Vec<Vec<[f32; 2]>>,
+    p: [f32; 2],
+    n1: [f32; 2],
+    n2: [f32; 2],
+    half: f32,
+    join: Join,
+) {
+    // The outer side of the corner is opposite of the direction being turned towards.
+    let side = if (n1[0] * n2[1]) - (n1[1] * n2[0]) > 0.0 {
+        -1.0
+    } else {
+        1.0
+    };
+
+    let o1 = [n1[0] * side, n1[1] * side];
+    let o2 = [n2[0] * side, n2[1] * side];
+    let bevel = vec![
+        p,
+        [p[0] + o1[0], p[1] + o1[1]],
+        [p[0] + o2[0], p[1] + o2[1]],
+    ];
+
+    match join {
+        Join::Bevel => push_polygon(polygons, bevel),
+        Join::Miter => {
+            let mx = o1[0] + o2[0];
+            let my = o1[1] + o2[1];
+            let m_len = float::sqrt(mx * mx + my * my);
+
+            if m_len == 0.0 {
+                return;
+            }
+
+            let (mx, my) = (mx / m_len, my / m_len);
+            let cos = ((mx * o1[0]) + (my * o1[1])) / half;
+
+            if cos <= 0.0 || half / cos > half * MITER_LIMIT {
+                push_polygon(polygons, bevel);
+                return;
+            }
+
+            let miter_len = half / cos;
+
+            push_polygon(
+                polygons,
+                vec![
+                    p,
+                    [p[0] + o1[0], p[1] + o1[1]],
+                    [p[0] + mx * miter_len, p[1] + my * miter_len],
+                    [p[0] + o2[0], p[1] + o2[1]],
+                ],
+            );
+        },
+        Join::Round => {
+            let a1 = float::atan2(o1[1], o1[0]);
+            let mut delta = float::atan2(o2[1], o2[0]) - a1;
+
+            if delta > PI {
+                delta -= 2.0 * PI;
+            } else if delta < -PI {
+                delta += 2.0 * PI;
+            }
+
+            // Near a reversal, e.g. at either end of a single line, the sign of `delta` is
+            // arbitrary. The arc must pass the direction of travel to cap the end.
+            let travel = [n1[1] - n2[1], n2[0] - n1[0]];
+            let a_mid = a1 + (delta / 2.0);
+
+            if delta.abs() > PI / 2.0
+                && (float::cos(a_mid) * travel[0]) + (float::sin(a_mid) * travel[1]) < 0.0
+            {
+                delta -= 2.0 * PI * delta.signum();
+            }
+
+            let steps =
+                float::ceil((delta.abs() / (2.0 * PI)) * ROUND_SUBDIVISIONS).max(1.0) as usize;
+            let mut polygon = Vec::with_capacity(steps + 2);
+            polygon.push(p);
+
+            for i in 0..=steps {
+                let a = a1 + (delta * (i as f32 / steps as f32));
+                polygon.push([p[0] + (float::cos(a) * half), p[1] + (float::sin(a) * half)]);
+            }
+
+            push_polygon(polygons, polygon);
+        },
+    }
+}
+
+/// Add a polygon with its orientation normalized. Degenerate polygons are discarded.
+fn push_polygon(polygons: &mut Vec<Vec<[f32; 2]>>, mut polygon: Vec<[f32; 2]>) {
+    let mut area = 0.0;
+
+    for i in 0..polygon.len() {
+        let [x1, y1] = polygon[i];
+        let [x2, y2] = polygon[(i + 1) % polygon.len()];
+        area += (x1 * y2) - (x2 * y1);
+    }
+
+    if area.abs() < 1e-6 {
+        return;
+    }
+
+    if area < 0.0 {
+        polygon.reverse();
+    }
+
+    polygons.push(polygon);
+}
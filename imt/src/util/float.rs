@@ -0,0 +1,110 @@
+//! `f32` math that lives in `std`, backed by `libm` for `no_std` builds.
+//!
+//! These are free functions rather than a trait, as newer toolchains provide some of these
+//! methods on `f32` in `core` as well, which would shadow the trait methods.
+
+pub(crate) fn floor(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    {
+        x.floor()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        libm::floorf(x)
+    }
+}
+
+pub(crate) fn ceil(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    {
+        x.ceil()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        libm::ceilf(x)
+    }
+}
+
+pub(crate) fn round(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    {
+        x.round()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        libm::roundf(x)
+    }
+}
+
+pub(crate) fn sqrt(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    {
+        x.sqrt()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        libm::sqrtf(x)
+    }
+}
+
+/// Raise `x` to an integer power by repeated multiplication, like `f32::powi`.
+pub(crate) fn powi(x: f32, n: i32) -> f32 {
+    #[cfg(feature = "std")]
+    {
+        x.powi(n)
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        let mut base = x;
+        let mut exp = n.unsigned_abs();
+        let mut value = 1.0;
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                value *= base;
+            }
+
+            base *= base;
+            exp >>= 1;
+        }
+
+        if n < 0 {
+            1.0 / value
+        } else {
+            value
+        }
+    }
+}
+
+pub(crate) fn sin(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    {
+        x.sin()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        libm::sinf(x)
+    }
+}
+
+pub(crate) fn cos(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    {
+        x.cos()
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        libm::cosf(x)
+    }
+}
+
+pub(crate) fn atan2(y: f32, x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    {
+        y.atan2(x)
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        libm::atan2f(y, x)
+    }
+}
@@ -1,5 +1,12 @@
+pub(crate) mod float;
+pub(crate) mod hash;
+pub(crate) mod pixel;
+pub mod shaping;
+pub mod unicode;
 pub mod variation;
 
+use core::fmt;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ImtUtilError {
     NoData,
@@ -7,4 +14,22 @@ pub enum ImtUtilError {
     MissingTable,
     MalformedFont,
     MalformedOutline,
+    /// An axis tag does not correspond to an axis of the font.
+    UnknownAxis,
+}
+
+impl fmt::Display for ImtUtilError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NoData => "no data",
+            Self::InvalidCoords => "invalid coordinates",
+            Self::MissingTable => "missing table",
+            Self::MalformedFont => "malformed font",
+            Self::MalformedOutline => "malformed outline",
+            Self::UnknownAxis => "unknown axis",
+        })
+    }
 }
+
+#[cfg(feature = "std")]
+impl std::error::Error for ImtUtilError {}
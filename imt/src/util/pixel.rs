@@ -0,0 +1,29 @@
+use crate::util::float;
+
+/// Expand the scaled extent of an outline along one axis to whole pixels.
+///
+/// # Notes
+/// - Values already on the pixel grid are not expanded, this keeps glyphs that lie entirely
+///   above or below the baseline (e.g. an underscore) from gaining an empty row.
+/// - The extent is always at least one pixel.
+#[inline]
+pub(crate) fn pixel_bounds(min_raw: f32, max_raw: f32) -> (f32, f32) {
+    let min_whole = float::floor(min_raw);
+    let max_whole = float::ceil(max_raw).max(min_whole + 1.0);
+    (min_whole, max_whole)
+}
+
+#[inline]
+pub(crate) fn f32_to_dimension(v: f32) -> Option<u32> {
+    if v < 0.0 {
+        None
+    } else {
+        let int = v as u32;
+
+        if int == 0 {
+            None
+        } else {
+            Some(int)
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::parse::{tag, Font, ItemVariationStore, LayoutTable, LookupSubtable, PosValue};
+use crate::util::variation::item_variation_delta;
+use crate::util::ImtUtilError;
+
+/// The script whose features are applied, see `LayoutTable::feature_lookups`.
+const LATIN: u32 = tag(b"latn");
+
+/// Adjustment of the position of a glyph by `GPOS`, in font units.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GlyphAdjustment {
+    pub x_advance: f32,
+    pub x_offset: f32,
+    /// `Y` up.
+    pub y_offset: f32,
+}
+
+/// A glyph within a run being shaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClusterGlyph {
+    pub glyph_id: u16,
+    /// The index of the `char`, or other unit, of the text the glyph was produced from.
+    pub cluster: usize,
+}
+
+/// Apply the single & ligature substitution lookups of a `GSUB` feature, e.g. `liga`, to a run
+/// of glyphs.
+///
+/// A ligature replaces its first glyph and keeps its `cluster`, the other glyphs it is formed
+/// from are removed. Glyphs skipped by the lookup flags, e.g. marks, are kept in place.
+///
+/// # Notes
+/// - Does nothing if the font does not have a `GSUB` table or the feature.
+/// - The lookups of the Latin script are used, see `LayoutTable::feature_lookups`.
+pub fn apply_substitutions(font: &Font, glyphs: &mut Vec<ClusterGlyph>, feature_tag: &[u8; 4]) {
+    let gsub = match font.gsub_table() {
+        Some(some) => some,
+        None => return,
+    };
+
+    for lookup_index in gsub.feature_lookups(LATIN, u32::from_be_bytes(*feature_tag)) {
+        let lookup = &gsub.lookups[lookup_index as usize];
+
+        let ignored = |glyph_id: u16| -> bool {
+            let glyph_class = font
+                .gdef_table()
+                .map(|gdef| gdef.glyph_class(glyph_id))
+                .unwrap_or(0);
+
+            lookup.ignores(glyph_class)
+        };
+
+        let mut i = 0;
+
+        while i < glyphs.len() {
+            if ignored(glyphs[i].glyph_id) {
+                i += 1;
+                continue;
+            }
+
+            for subtable in lookup.subtables.iter() {
+                match subtable {
+                    LookupSubtable::SingleSubst(single_subst) => {
+                        if let Some(substitute) = single_subst.substitute(glyphs[i].glyph_id) {
+                            glyphs[i].glyph_id = substitute;
+                            break;
+                        }
+                    },
+                    LookupSubtable::LigatureSubst(ligature_subst) => {
+                        let matched = ligature_subst
+                            .ligatures(glyphs[i].glyph_id)
+                            .iter()
+                            .find_map(|ligature| {
+                                let mut matched = Vec::with_capacity(ligature.components.len());
+                                let mut j = i + 1;
+
+                                for component in ligature.components.iter() {
+                                    while j < glyphs.len() && ignored(glyphs[j].glyph_id) {
+                                        j += 1;
+                                    }
+
+                                    if j >= glyphs.len() || glyphs[j].glyph_id != *component {
+                                        return None;
+                                    }
+
+                                    matched.push(j);
+                                    j += 1;
+                                }
+
+                                Some((ligature.ligature_glyph, matched))
+                            });
+
+                        if let Some((ligature_glyph, matched)) = matched {
+                            glyphs[i].glyph_id = ligature_glyph;
+
+                            for j in matched.into_iter().rev() {
+                                glyphs.remove(j);
+                            }
+
+                            break;
+                        }
+                    },
+                    _ => (),
+                }
+            }
+
+            i += 1;
+        }
+    }
+}
+
+/// Apply the pair adjustment lookups of a `GPOS` feature, e.g. `kern`, to a run of glyphs.
+///
+/// The returned `Vec` has an adjustment for every glyph of `glyph_ids`.
+///
+/// # Notes
+/// - `coords` should be normalized, an empty slice is the default instance.
+/// - Returns no adjustments if the font does not have a `GPOS` table or the feature.
+/// - The lookups of the Latin script are used, see `LayoutTable::feature_lookups`.
+pub fn pair_adjustments(
+    font: &Font,
+    glyph_ids: &[u16],
+    feature_tag: &[u8; 4],
+    coords: &[f32],
+) -> Result<Vec<GlyphAdjustment>, ImtUtilError> {
+    if coords.iter().any(|coord| *coord < -1.0 || *coord > 1.0) {
+        return Err(ImtUtilError::InvalidCoords);
+    }
+
+    let mut adjustments = vec![GlyphAdjustment::default(); glyph_ids.len()];
+
+    let gpos = match font.gpos_table() {
+        Some(some) => some,
+        None => return Ok(adjustments),
+    };
+
+    let store = font
+        .gdef_table()
+        .and_then(|gdef| gdef.item_variation_store.as_ref());
+
+    let store = match store {
+        Some(store) if !coords.is_empty() => {
+            if coords.len() != store.axis_count {
+                return Err(ImtUtilError::InvalidCoords);
+            }
+
+            Some(store)
+        },
+        _ => None,
+    };
+
+    for lookup_index in gpos.feature_lookups(LATIN, u32::from_be_bytes(*feature_tag)) {
+        apply_pair_lookup(
+            font,
+            gpos,
+            lookup_index as usize,
+            glyph_ids,
+            store.map(|store| (store, coords)),
+            &mut adjustments,
+        );
+    }
+
+    Ok(adjustments)
+}
+
+fn apply_pair_lookup(
+    font: &Font,
+    gpos: &LayoutTable,
+    lookup_index: usize,
+    glyph_ids: &[u16],
+    variations: Option<(&ItemVariationStore, &[f32])>,
+    adjustments: &mut [GlyphAdjustment],
+) {
+    let lookup = &gpos.lookups[lookup_index];
+
+    // Glyphs the lookup skips are treated as if they weren't present.
+    let considered: Vec<usize> = (0..glyph_ids.len())
+        .filter(|i| {
+            let glyph_class = font
+                .gdef_table()
+                .map(|gdef| gdef.glyph_class(glyph_ids[*i]))
+                .unwrap_or(0);
+
+            !lookup.ignores(glyph_class)
+        })
+        .collect();
+
+    let mut i = 0;
+
+    while i + 1 < considered.len() {
+        let (first, second) = (considered[i], considered[i + 1]);
+        i += 1;
+
+        for subtable in lookup.subtables.iter() {
+            let pair_pos = match subtable {
+                LookupSubtable::PairPos(pair_pos) => pair_pos,
+                _ => continue,
+            };
+
+            if let Some([value_1, value_2]) = pair_pos.values(glyph_ids[first], glyph_ids[second]) {
+                apply_value(&mut adjustments[first], &value_1, variations);
+                apply_value(&mut adjustments[second], &value_2, variations);
+
+                if pair_pos.has_second_value() {
+                    i += 1;
+                }
+
+                break;
+            }
+        }
+    }
+}
+
+fn apply_value(
+    adjustment: &mut GlyphAdjustment,
+    value: &PosValue,
+    variations: Option<(&ItemVariationStore, &[f32])>,
+) {
+    let delta = |variation: Option<[usize; 2]>| -> f32 {
+        match (variations, variation) {
+            (Some((store, coords)), Some([outer_index, inner_index])) => {
+                item_variation_delta(store, outer_index, inner_index, coords).unwrap_or(0.0)
+            },
+            _ => 0.0,
+        }
+    };
+
+    adjustment.x_advance += value.x_advance as f32 + delta(value.x_advance_variation);
+    adjustment.x_offset += value.x_placement as f32 + delta(value.x_placement_variation);
+    adjustment.y_offset += value.y_placement as f32 + delta(value.y_placement_variation);
+}
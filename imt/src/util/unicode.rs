@@ -0,0 +1,36 @@
+use core::ops::RangeInclusive;
+
+/// Ranges of characters with the `Default_Ignorable_Code_Point` property.
+///
+/// These are invisible formatting and control characters that are not rendered when a font has
+/// no glyph for them, e.g. joiners, bidirectional marks and variation selectors.
+const DEFAULT_IGNORABLE_RANGES: [RangeInclusive<u32>; 17] = [
+    0x00AD..=0x00AD,   // Soft Hyphen
+    0x034F..=0x034F,   // Combining Grapheme Joiner
+    0x061C..=0x061C,   // Arabic Letter Mark
+    0x115F..=0x1160,   // Hangul Fillers
+    0x17B4..=0x17B5,   // Khmer Inherent Vowels
+    0x180B..=0x180F,   // Mongolian Variation Selectors
+    0x200B..=0x200F,   // Zero Width Space, Joiners & Directional Marks
+    0x202A..=0x202E,   // Directional Formatting
+    0x2060..=0x206F,   // Word Joiner & Invisible Operators
+    0x3164..=0x3164,   // Hangul Filler
+    0xFE00..=0xFE0F,   // Variation Selectors
+    0xFEFF..=0xFEFF,   // Zero Width No-Break Space (BOM)
+    0xFFA0..=0xFFA0,   // Halfwidth Hangul Filler
+    0xFFF0..=0xFFF8,   // Unassigned Specials
+    0x1BCA0..=0x1BCA3, // Shorthand Format Controls
+    0x1D173..=0x1D17A, // Musical Symbol Formatting
+    0xE0000..=0xE0FFF, // Tags & Variation Selectors Supplement
+];
+
+/// Check if a character is default ignorable, i.e. it should have zero advance and not be
+/// rendered.
+///
+/// # Notes
+/// - These characters may still affect shaping, e.g. U+200D ZERO WIDTH JOINER.
+pub fn is_default_ignorable(c: char) -> bool {
+    DEFAULT_IGNORABLE_RANGES
+        .iter()
+        .any(|range| range.contains(&(c as u32)))
+}
@@ -1,8 +1,174 @@
-use std::cmp::Ord;
-
-use crate::parse::{Font, Outline};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ord;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, VecDeque};
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use crate::parse::{AvarTable, Font, ItemVariationStore, Outline, TupleVariation};
 use crate::util::ImtUtilError;
 
+/// A bounded cache of outlines with `gvar` applied, keyed on font, glyph and normalized
+/// coordinates.
+///
+/// Once `capacity` is reached the oldest outline is evicted. Outlines are keyed on `Font::id`, so
+/// a cache can be shared between fonts.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct VariedOutlineCache {
+    capacity: usize,
+    state: Mutex<VariedOutlineCacheState>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct VariedOutlineCacheState {
+    outlines: HashMap<(u64, u16, Vec<u32>), Outline>,
+    order: VecDeque<(u64, u16, Vec<u32>)>,
+}
+
+#[cfg(feature = "std")]
+impl VariedOutlineCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(VariedOutlineCacheState::default()),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().outlines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Fetch a copy of the cached outline for the font, glyph and normalized coordinates.
+    ///
+    /// `font_id` is the `Font::id` of the font the outline is from.
+    pub fn get(&self, font_id: u64, glyph_index: u16, coords: &[f32]) -> Option<Outline> {
+        self.state
+            .lock()
+            .unwrap()
+            .outlines
+            .get(&Self::key(font_id, glyph_index, coords))
+            .cloned()
+    }
+
+    /// Insert an outline for the font, glyph and normalized coordinates.
+    ///
+    /// `font_id` is the `Font::id` of the font the outline is from.
+    pub fn insert(&self, font_id: u64, glyph_index: u16, coords: &[f32], outline: Outline) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let key = Self::key(font_id, glyph_index, coords);
+        let mut state = self.state.lock().unwrap();
+
+        if state.outlines.insert(key.clone(), outline).is_some() {
+            return;
+        }
+
+        state.order.push_back(key);
+
+        while state.order.len() > self.capacity {
+            let oldest = state.order.pop_front().unwrap();
+            state.outlines.remove(&oldest);
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.outlines.clear();
+        state.order.clear();
+    }
+
+    fn key(font_id: u64, glyph_index: u16, coords: &[f32]) -> (u64, u16, Vec<u32>) {
+        (
+            font_id,
+            glyph_index,
+            coords.iter().map(|coord| coord.to_bits()).collect(),
+        )
+    }
+}
+
+/// How a `CoordBuilder` handles a tag that does not correspond to an axis of the font.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingAxisPolicy {
+    /// The value is discarded.
+    #[default]
+    Ignore,
+    /// `CoordBuilder::build` returns `ImtUtilError::UnknownAxis`.
+    Error,
+}
+
+/// Builds user space coordinates for every axis of a font from values set by tag.
+///
+/// Axes that are not set use their default value. This allows applying a common set of axes,
+/// e.g. `opsz` & `wght`, across fonts that may not have all of them.
+#[derive(Debug, Clone)]
+pub struct CoordBuilder<'a> {
+    font: &'a Font,
+    values: Vec<([u8; 4], f32)>,
+    on_missing: MissingAxisPolicy,
+}
+
+impl<'a> CoordBuilder<'a> {
+    pub fn new(font: &'a Font) -> Self {
+        Self {
+            font,
+            values: Vec::new(),
+            on_missing: MissingAxisPolicy::default(),
+        }
+    }
+
+    /// Set the value of the axis with the provided tag. Later values take precedence.
+    pub fn set(mut self, tag: &[u8; 4], value: f32) -> Self {
+        self.values.push((*tag, value));
+        self
+    }
+
+    /// Set how tags that do not correspond to an axis are handled.
+    pub fn on_missing(mut self, policy: MissingAxisPolicy) -> Self {
+        self.on_missing = policy;
+        self
+    }
+
+    /// Build the coordinates. These are in user space and not normalized.
+    ///
+    /// # Notes
+    /// - Returns `ImtUtilError::MissingTable` if the font does not have a `fvar` table.
+    /// - Returns `ImtUtilError::UnknownAxis` if a tag does not correspond to an axis and the
+    ///   policy is `MissingAxisPolicy::Error`.
+    pub fn build(&self) -> Result<Vec<f32>, ImtUtilError> {
+        let fvar = self.font.fvar_table().ok_or(ImtUtilError::MissingTable)?;
+        let mut coords: Vec<f32> = fvar.axes.iter().map(|axis| axis.default_value).collect();
+
+        for (tag, value) in self.values.iter() {
+            let tag = u32::from_be_bytes(*tag);
+
+            match fvar.axes.iter().position(|axis| axis.axis_tag == tag) {
+                Some(index) => coords[index] = *value,
+                None => {
+                    match self.on_missing {
+                        MissingAxisPolicy::Ignore => (),
+                        MissingAxisPolicy::Error => return Err(ImtUtilError::UnknownAxis),
+                    }
+                },
+            }
+        }
+
+        Ok(coords)
+    }
+}
+
 pub fn normalize_axis_coords(font: &Font, coords: &mut Vec<f32>) -> Result<(), ImtUtilError> {
     let fvar = font.fvar_table().ok_or(ImtUtilError::MissingTable)?;
 
@@ -11,6 +177,12 @@ pub fn normalize_axis_coords(font: &Font, coords: &mut Vec<f32>) -> Result<(), I
     }
 
     for (i, coord) in coords.iter_mut().enumerate() {
+        // Checked first since the default may coincide with either end of the range.
+        if *coord == fvar.axes[i].default_value {
+            *coord = 0.0;
+            continue;
+        }
+
         if *coord <= fvar.axes[i].min_value {
             *coord = -1.0;
             continue;
@@ -58,8 +230,8 @@ pub fn normalize_axis_coords(font: &Font, coords: &mut Vec<f32>) -> Result<(), I
                 } else if *coord == maps[k + 1].from_coord {
                     *coord = maps[k + 1].to_coord;
                 } else {
-                    *coord = ((((maps[k + 1].from_coord - *coord)
-                        / (maps[k + 1].from_coord / maps[k].from_coord))
+                    *coord = ((((*coord - maps[k].from_coord)
+                        / (maps[k + 1].from_coord - maps[k].from_coord))
                         * (maps[k + 1].to_coord - maps[k].to_coord))
                         + maps[k].to_coord)
                         .clamp(-1.0, 1.0);
@@ -68,9 +240,51 @@ pub fn normalize_axis_coords(font: &Font, coords: &mut Vec<f32>) -> Result<(), I
         }
     }
 
+    if let Some(avar) = font.avar_table() {
+        avar_apply_store(avar, coords);
+    }
+
     Ok(())
 }
 
+/// Apply the deltas of an `avar` version 2 table to normalized coordinates.
+///
+/// Every delta is computed from the coordinates prior to any being applied, allowing the value
+/// of one axis to depend on others.
+pub(crate) fn avar_apply_store(avar: &AvarTable, coords: &mut [f32]) {
+    let store = match avar.item_variation_store.as_ref() {
+        Some(some) => some,
+        None => return,
+    };
+
+    if coords.len() != store.axis_count {
+        return;
+    }
+
+    let deltas: Vec<f32> = (0..coords.len())
+        .map(|i| {
+            let [outer_index, inner_index] = match avar.axis_index_map.as_ref() {
+                Some(im) if !im.map_data.is_empty() => im.map_data[i.min(im.map_data.len() - 1)],
+                _ => [0, i],
+            };
+
+            // Deltas are in F2DOT14 units.
+            item_variation_delta(store, outer_index, inner_index, coords).unwrap_or(0.0) / 16384.0
+        })
+        .collect();
+
+    for (coord, delta) in coords.iter_mut().zip(deltas) {
+        *coord = (*coord + delta).clamp(-1.0, 1.0);
+    }
+}
+
+/// Whether normalized coords describe the default instance.
+///
+/// Values within half of an F2Dot14 step of zero are treated as zero.
+pub fn is_default_normalized(coords: &[f32]) -> bool {
+    coords.iter().all(|coord| coord.abs() < 1.0 / 32768.0)
+}
+
 pub fn advance_width(
     font: &Font,
     glyph_index: u16,
@@ -102,21 +316,118 @@ pub fn advance_width(
         None => [0, glyph_index as usize],
     };
 
-    if outer_index >= hvar.item_variation_store.item_data.len() {
-        return Ok(0.0);
+    // Glyphs not covered by HVAR fall back to the phantom points of gvar.
+    match item_variation_delta(&hvar.item_variation_store, outer_index, inner_index, coords) {
+        Some(delta) => Ok(delta),
+        None => phantom_advance_delta(font, glyph_index, coords),
     }
+}
 
-    let item_data = &hvar.item_variation_store.item_data[outer_index];
+/// Compute the advance delta of a glyph from the horizontal phantom points in `gvar`.
+///
+/// # Notes
+/// - Returns `Ok(0.0)` if the font does not have a `gvar` table or the glyph has no variations.
+pub(crate) fn phantom_advance_delta(
+    font: &Font,
+    glyph_index: u16,
+    coords: &[f32],
+) -> Result<f32, ImtUtilError> {
+    let gvar = match font.gvar_table() {
+        Some(some) => some,
+        None => return Ok(0.0),
+    };
 
-    if inner_index >= item_data.delta_sets.len() {
-        return Ok(0.0);
+    if coords.len() != gvar.axis_count {
+        return Err(ImtUtilError::InvalidCoords);
     }
 
+    let glyph_variation = match gvar.glyph_variations.get(&glyph_index) {
+        Some(some) => some,
+        None => return Ok(0.0),
+    };
+
+    // The phantom points follow the points of the outline. The first two are the left & right
+    // side bearing points, their difference in X is the advance.
+    let left_i = font
+        .try_glyf_table()
+        .map_err(|_| ImtUtilError::MissingTable)?
+        .point_count(glyph_index);
+
+    let right_i = left_i + 1;
     let mut total_delta = 0.0;
 
-    'delta_data: for (i, delta_data) in item_data.delta_sets[inner_index].data.iter().enumerate() {
+    for tuple in glyph_variation.tuples.iter() {
+        let tuple_scaler = match tuple_scaler(tuple, coords) {
+            Some(some) => some,
+            None => continue,
+        };
+
+        // Phantom points are never inferred, so those without an explicit delta have none.
+        let delta_x = |point_i: usize| -> f32 {
+            let delta_i = if tuple.points.is_empty() {
+                Some(point_i)
+            } else {
+                tuple.points.iter().position(|i| *i as usize == point_i)
+            };
+
+            delta_i
+                .and_then(|delta_i| tuple.deltas.get(delta_i))
+                .map(|[x, _]| *x as f32)
+                .unwrap_or(0.0)
+        };
+
+        total_delta += (delta_x(right_i) - delta_x(left_i)) * tuple_scaler;
+    }
+
+    Ok(total_delta)
+}
+
+/// Retrieve the `MVAR` delta of a font-wide metric such as `mvar_table::value_tag::HASC`.
+///
+/// # Notes
+/// - Returns `Ok(0.0)` if the font does not have a `MVAR` table or the metric does not vary.
+pub fn metric_delta(font: &Font, value_tag: u32, coords: &[f32]) -> Result<f32, ImtUtilError> {
+    if coords.iter().any(|coord| *coord < -1.0 || *coord > 1.0) {
+        return Err(ImtUtilError::InvalidCoords);
+    }
+
+    let mvar = match font.mvar_table() {
+        Some(some) => some,
+        None => return Ok(0.0),
+    };
+
+    let (record, store) = match (
+        mvar.value_record(value_tag),
+        mvar.item_variation_store.as_ref(),
+    ) {
+        (Some(record), Some(store)) => (record, store),
+        _ => return Ok(0.0),
+    };
+
+    if coords.len() != store.axis_count {
+        return Err(ImtUtilError::InvalidCoords);
+    }
+
+    Ok(item_variation_delta(store, record.outer_index, record.inner_index, coords).unwrap_or(0.0))
+}
+
+/// Sum the deltas of a delta set within an `ItemVariationStore` for the provided coordinates.
+///
+/// Returns `None` if the indexes are out of bounds.
+pub(crate) fn item_variation_delta(
+    store: &ItemVariationStore,
+    outer_index: usize,
+    inner_index: usize,
+    coords: &[f32],
+) -> Option<f32> {
+    let item_data = store.item_data.get(outer_index)?;
+    let delta_set = item_data.delta_sets.get(inner_index)?;
+
+    let mut total_delta = 0.0;
+
+    'delta_data: for (i, delta_data) in delta_set.data.iter().enumerate() {
         let delta = delta_data.as_f32();
-        let region = &hvar.item_variation_store.regions[item_data.region_indexes[i]];
+        let region = &store.regions[item_data.region_indexes[i]];
 
         let mut all_ignored = true;
         let mut scaler = 1.0;
@@ -153,15 +464,73 @@ pub fn advance_width(
         }
     }
 
-    Ok(total_delta)
+    Some(total_delta)
 }
 
+/// Apply the `gvar` deltas for the provided normalized coordinates to an outline.
+///
+/// Glyphs without variation data are left unchanged.
+///
+/// # Notes
+/// - The deltas of a composite glyph move its components rather than points, so `outline` is
+///   replaced by the composite of the varied components, see `Font::varied_outline`.
 pub fn outline_apply_gvar(
     font: &Font,
     glyph_index: u16,
     outline: &mut Outline,
     coords: &Vec<f32>,
 ) -> Result<(), ImtUtilError> {
+    let is_composite = font
+        .try_glyf_table()
+        .map(|glyf| glyf.composites.contains_key(&glyph_index))
+        .unwrap_or(false);
+
+    if is_composite {
+        if coords.iter().any(|coord| *coord < -1.0 || *coord > 1.0) {
+            return Err(ImtUtilError::InvalidCoords);
+        }
+
+        let gvar = font.gvar_table().ok_or(ImtUtilError::MissingTable)?;
+
+        if coords.len() != gvar.axis_count {
+            return Err(ImtUtilError::InvalidCoords);
+        }
+
+        *outline = font
+            .varied_outline(glyph_index, Some(coords))
+            .ok_or(ImtUtilError::MalformedOutline)?;
+
+        return Ok(());
+    }
+
+    let point_deltas = gvar_point_deltas(font, glyph_index, outline, coords)?;
+
+    for (i, [dx, dy]) in point_deltas.into_iter().enumerate() {
+        // Phantom points are ignored, advances are varied by `advance_width` instead.
+        if i >= outline.points.len() {
+            break;
+        }
+
+        outline.points[i].x += dx;
+        outline.points[i].y += dy;
+    }
+
+    outline
+        .rebuild()
+        .map_err(|_| ImtUtilError::MalformedOutline)
+}
+
+/// Compute the `gvar` deltas of the components of a composite glyph, followed by the four
+/// phantom points.
+///
+/// Unlike the points of simple glyphs, deltas of components that are not referenced by a tuple
+/// are not inferred.
+pub(crate) fn gvar_component_deltas(
+    font: &Font,
+    glyph_index: u16,
+    component_count: usize,
+    coords: &[f32],
+) -> Result<Vec<[f32; 2]>, ImtUtilError> {
     if coords.iter().any(|coord| *coord < -1.0 || *coord > 1.0) {
         return Err(ImtUtilError::InvalidCoords);
     }
@@ -172,68 +541,66 @@ pub fn outline_apply_gvar(
         return Err(ImtUtilError::InvalidCoords);
     }
 
-    let glyph_variation = gvar
-        .glyph_variations
-        .get(&glyph_index)
-        .ok_or(ImtUtilError::NoData)?;
+    let mut deltas = vec![[0.0, 0.0]; component_count + 4];
 
-    let mut point_deltas = vec![[0.0, 0.0]; outline.points.len() + 4];
-
-    'tuple: for tuple in glyph_variation.tuples.iter() {
-        let mut tuple_scaler = 1.0;
-        let mut tuple_applies = false;
+    let glyph_variation = match gvar.glyph_variations.get(&glyph_index) {
+        Some(some) => some,
+        None => return Ok(deltas),
+    };
 
-        for (axis_i, axis_coord) in coords.iter().enumerate() {
-            let peak = tuple.peak[axis_i];
+    for tuple in glyph_variation.tuples.iter() {
+        let tuple_scaler = match tuple_scaler(tuple, coords) {
+            Some(some) => some,
+            None => continue,
+        };
 
-            // If the peak is at zero it is ignored.
-            if peak == 0.0 {
-                continue;
-            }
+        for (i, [x, y]) in tuple.deltas.iter().enumerate() {
+            let point_i = if tuple.points.is_empty() {
+                i
+            } else {
+                tuple.points[i] as usize
+            };
 
-            // If the axis coord equals the peak the scaler is one
-            if peak == *axis_coord {
-                tuple_applies = true;
-                continue;
+            if let Some(delta) = deltas.get_mut(point_i) {
+                delta[0] += *x as f32 * tuple_scaler;
+                delta[1] += *y as f32 * tuple_scaler;
             }
+        }
+    }
 
-            if let Some(interm) = &tuple.interm {
-                let start = interm.start[axis_i];
-                let end = interm.end[axis_i];
+    Ok(deltas)
+}
 
-                // Out of range
-                if *axis_coord < start || *axis_coord > end {
-                    continue 'tuple;
-                }
+/// Compute the `gvar` deltas of every point of an outline, followed by the four phantom points.
+pub(crate) fn gvar_point_deltas(
+    font: &Font,
+    glyph_index: u16,
+    outline: &Outline,
+    coords: &[f32],
+) -> Result<Vec<[f32; 2]>, ImtUtilError> {
+    if coords.iter().any(|coord| *coord < -1.0 || *coord > 1.0) {
+        return Err(ImtUtilError::InvalidCoords);
+    }
 
-                // Scaler will be zero
-                if *axis_coord == start || *axis_coord == end {
-                    continue 'tuple;
-                }
+    let gvar = font.gvar_table().ok_or(ImtUtilError::MissingTable)?;
 
-                tuple_applies = true;
+    if coords.len() != gvar.axis_count {
+        return Err(ImtUtilError::InvalidCoords);
+    }
 
-                if *axis_coord < peak {
-                    tuple_scaler *= (*axis_coord - start) / (peak - start);
-                } else {
-                    tuple_scaler *= (end - *axis_coord) / (end - peak);
-                }
-            } else {
-                // Out of range
-                if *axis_coord == 0.0 || *axis_coord < peak.min(0.0) || *axis_coord > peak.max(0.0)
-                {
-                    continue 'tuple;
-                }
+    // Glyphs without an entry are valid, they just do not vary.
+    let glyph_variation = match gvar.glyph_variations.get(&glyph_index) {
+        Some(some) => some,
+        None => return Ok(vec![[0.0, 0.0]; outline.points.len() + 4]),
+    };
 
-                tuple_applies = true;
-                tuple_scaler *= *axis_coord / peak;
-            }
-        }
+    let mut point_deltas = vec![[0.0, 0.0]; outline.points.len() + 4];
 
-        // All axes were ignored, so delta does not apply
-        if !tuple_applies {
-            continue;
-        }
+    for tuple in glyph_variation.tuples.iter() {
+        let tuple_scaler = match tuple_scaler(tuple, coords) {
+            Some(some) => some,
+            None => continue,
+        };
 
         if tuple.points.is_empty() {
             for (i, [x, y]) in tuple.deltas.iter().enumerate() {
@@ -314,27 +681,77 @@ pub fn outline_apply_gvar(
         }
     }
 
-    for (i, [dx, dy]) in point_deltas.into_iter().enumerate() {
-        // TODO: Should these be retained in case of the 'hvar' table is missing? The code above
-        //       will have to infer these also.
+    Ok(point_deltas)
+}
 
-        // Phantom points are ignored
-        if i >= outline.points.len() {
-            break;
+/// Compute the scaler of a `TupleVariation` for the provided normalized coordinates.
+///
+/// Returns `None` if the tuple does not apply to the coordinates.
+pub(crate) fn tuple_scaler(tuple: &TupleVariation, coords: &[f32]) -> Option<f32> {
+    let mut tuple_scaler = 1.0;
+    let mut tuple_applies = false;
+
+    for (axis_i, axis_coord) in coords.iter().enumerate() {
+        let peak = tuple.peak[axis_i];
+
+        // If the peak is at zero it is ignored.
+        if peak == 0.0 {
+            continue;
         }
 
-        outline.points[i].x += dx;
-        outline.points[i].y += dy;
+        // If the axis coord equals the peak the scaler is one
+        if peak == *axis_coord {
+            tuple_applies = true;
+            continue;
+        }
+
+        if let Some(interm) = &tuple.interm {
+            let start = interm.start[axis_i];
+            let end = interm.end[axis_i];
+
+            // Out of range
+            if *axis_coord < start || *axis_coord > end {
+                return None;
+            }
+
+            // Scaler will be zero
+            if *axis_coord == start || *axis_coord == end {
+                return None;
+            }
+
+            tuple_applies = true;
+
+            if *axis_coord < peak {
+                tuple_scaler *= (*axis_coord - start) / (peak - start);
+            } else {
+                tuple_scaler *= (end - *axis_coord) / (end - peak);
+            }
+        } else {
+            // Out of range
+            if *axis_coord == 0.0 || *axis_coord < peak.min(0.0) || *axis_coord > peak.max(0.0) {
+                return None;
+            }
+
+            tuple_applies = true;
+            tuple_scaler *= *axis_coord / peak;
+        }
     }
 
-    outline
-        .rebuild()
-        .map_err(|_| ImtUtilError::MalformedOutline)
+    // All axes were ignored, so delta does not apply
+    if !tuple_applies {
+        return None;
+    }
+
+    Some(tuple_scaler)
 }
 
 // impl pseudo-code from:
 // https://learn.microsoft.com/en-us/typography/opentype/spec/gvar#inferred-deltas-for-un-referenced-point-numbers
-fn infer_delta(px: f32, tx: f32, fx: f32, pd: f32, fd: f32) -> f32 {
+/// Infer the delta of an untouched point at `tx` from its previous & following touched points.
+///
+/// Follows the interpolation of untouched points described by the `gvar` specification. When
+/// both neighbors share a coordinate their delta is only used if it is equal.
+pub(crate) fn infer_delta(px: f32, tx: f32, fx: f32, pd: f32, fd: f32) -> f32 {
     if px == fx {
         if pd == fd {
             pd